@@ -0,0 +1,260 @@
+//! A small HTTP service for inspecting and patching the configuration of a
+//! running application, so that operators can tweak a node without logging
+//! into the host and restarting it.
+//!
+//! Every request must carry the `admin.token` as a bearer token:
+//! - `GET /config` returns the active configuration as JSON, with inline
+//!   secrets redacted.
+//! - `GET /config/provenance` returns the value of every key along with the
+//!   layer that set it, see [`ConfigHandle::provenance`].
+//! - `PATCH /config` patches reloadable keys with the JSON object in the
+//!   body, see [`ConfigHandle::patch`].
+
+use crate::{error::ConfigError, reload::ConfigHandle, sources::ConfigFormat};
+use figment::value::Dict;
+use serde_json::{json, Map, Value};
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+use zeroize::Zeroizing;
+
+/// Largest request accepted, headers and body together.
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+/// How long a client may stay silent while sending its request, or take to
+/// read the response.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a client may take to send its whole request, so that a slow one
+/// can't hold the service.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl ConfigHandle {
+    /// Serves the admin API on `admin.listen` from a dedicated thread, if
+    /// the `[admin]` section is set, returning the bound address. Requests
+    /// are served one at a time, over plain HTTP only.
+    pub fn spawn_admin(&self) -> io::Result<Option<SocketAddr>> {
+        let Some(admin) = self.current().admin.clone() else {
+            return Ok(None);
+        };
+        let Some(address) = admin.listen.socket_addr() else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the admin service only listens on TCP addresses",
+            ));
+        };
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let handle = self.clone();
+        let token = Zeroizing::new(admin.token.expose_secret().clone());
+        std::thread::Builder::new()
+            .name("config-admin".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    // A client that goes away, or a bug in answering it, only
+                    // affects its own request.
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                        stream.and_then(|stream| handle.serve(stream, &token))
+                    }));
+                }
+            })?;
+        Ok(Some(address))
+    }
+
+    /// Reads a single request from `stream` and answers it.
+    fn serve(&self, mut stream: TcpStream, token: &str) -> io::Result<()> {
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let response = match read_request(&mut stream)? {
+            Ok(request) if request.is_authorized(token) => self.respond(&request),
+            Ok(_) => Response::error(401, "missing or wrong bearer token"),
+            Err(response) => response,
+        };
+        response.write(&mut stream)
+    }
+
+    fn respond(&self, request: &Request) -> Response {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/config") => match self.current().to_format(ConfigFormat::Json) {
+                Ok(body) => Response::new(200, body),
+                Err(error) => Response::error(500, error),
+            },
+            ("GET", "/config/provenance") => match self.provenance() {
+                Ok(explanations) => {
+                    let keys: Map<_, _> = explanations
+                        .into_iter()
+                        .map(|explanation| {
+                            let value = json!({
+                                "value": explanation.value,
+                                "origin": explanation.origin,
+                            });
+                            (explanation.key, value)
+                        })
+                        .collect();
+                    Response::json(200, &Value::Object(keys))
+                }
+                Err(error) => Response::error(500, error),
+            },
+            ("PATCH", "/config") => {
+                let overrides: Dict = match serde_json::from_slice(&request.body) {
+                    Ok(overrides) => overrides,
+                    Err(error) => {
+                        return Response::error(400, format!("invalid JSON object: {error}"))
+                    }
+                };
+                match self.patch(overrides) {
+                    Ok(outcome) => {
+                        let render = |changes: &[_]| -> Vec<String> {
+                            changes.iter().map(ToString::to_string).collect()
+                        };
+                        let body = json!({
+                            "generation": outcome.generation,
+                            "applied": render(&outcome.applied),
+                            "restart-required": render(&outcome.restart_required),
+                        });
+                        Response::json(200, &body)
+                    }
+                    Err(error @ ConfigError::Invalid(_)) => Response::error(400, error),
+                    Err(error) => Response::error(500, error),
+                }
+            }
+            (_, "/config" | "/config/provenance") => {
+                Response::error(405, format!("{} is not allowed", request.method))
+            }
+            (_, path) => Response::error(404, format!("no such resource `{path}`")),
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    /// The path, without the query.
+    path: String,
+    /// The value of the `Authorization` header, if any.
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Whether the request carries `token` as its bearer token.
+    fn is_authorized(&self, token: &str) -> bool {
+        let Some(given) = self
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+        // Compares every byte, so that the time taken doesn't reveal how
+        // much of the token was right.
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+/// Reads a request from `stream`, or returns the response to a malformed
+/// one.
+fn read_request(stream: &mut TcpStream) -> io::Result<Result<Request, Response>> {
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(Err(Response::error(408, "request took too long")));
+        }
+        stream.set_read_timeout(Some(remaining.min(IO_TIMEOUT)))?;
+        let read = stream.read(&mut chunk)?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if buffer.len() > MAX_REQUEST_SIZE {
+            return Ok(Err(Response::error(413, "request too large")));
+        }
+
+        let mut headers = [httparse::EMPTY_HEADER; 32];
+        let mut parsed = httparse::Request::new(&mut headers);
+        let header_len = match parsed.parse(&buffer) {
+            Ok(httparse::Status::Complete(len)) => len,
+            Ok(httparse::Status::Partial) => continue,
+            Err(error) => return Ok(Err(Response::error(400, error))),
+        };
+        let header = |name: &str| {
+            parsed
+                .headers
+                .iter()
+                .find(|header| header.name.eq_ignore_ascii_case(name))
+                .and_then(|header| std::str::from_utf8(header.value).ok())
+        };
+        let content_length = match header("Content-Length").map(str::parse::<usize>) {
+            None => 0,
+            Some(Ok(len)) => len,
+            Some(Err(_)) => return Ok(Err(Response::error(400, "invalid Content-Length"))),
+        };
+        // Checked before adding, as the length comes from the client.
+        if content_length > MAX_REQUEST_SIZE - header_len {
+            return Ok(Err(Response::error(413, "request too large")));
+        }
+        if buffer.len() < header_len + content_length {
+            continue;
+        }
+
+        let path = parsed.path.unwrap_or_default();
+        return Ok(Ok(Request {
+            method: parsed.method.unwrap_or_default().to_owned(),
+            path: path.split('?').next().unwrap_or_default().to_owned(),
+            authorization: header("Authorization").map(str::to_owned),
+            body: buffer[header_len..header_len + content_length].to_vec(),
+        }));
+    }
+}
+
+struct Response {
+    status: u16,
+    /// A JSON document.
+    body: String,
+}
+
+impl Response {
+    fn new(status: u16, body: String) -> Self {
+        Self { status, body }
+    }
+
+    fn json(status: u16, body: &Value) -> Self {
+        Self::new(status, format!("{body:#}\n"))
+    }
+
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self::json(status, &json!({ "error": message.to_string() }))
+    }
+
+    fn write(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Content Too Large",
+            _ => "Internal Server Error",
+        };
+        let mut head = format!(
+            "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            self.body.len()
+        );
+        if self.status == 401 {
+            head.push_str("WWW-Authenticate: Bearer\r\n");
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(self.body.as_bytes())?;
+        stream.flush()
+    }
+}
@@ -0,0 +1,131 @@
+//! Support for renamed configuration keys, see [`consts::DEPRECATED_KEYS`],
+//! and for sections set to a single value, see [`consts::SHORTHAND_KEYS`].
+
+use crate::{consts, provenance::Locator, warning::ConfigWarning};
+use figment::{
+    value::{Dict, Map, Tag, Value},
+    Figment, Metadata, Profile, Provider,
+};
+
+/// Merges `provider` into `figment`, expanding its shorthands and copying
+/// the value of every deprecated key it sets to the key's replacement. If
+/// the provider sets both, the replacement wins.
+pub fn merge_aliased(figment: Figment, provider: impl Provider) -> Figment {
+    figment.merge(Renamed(Figment::from(Shorthands(provider))))
+}
+
+/// Wraps a provider, copying the value of every deprecated key it sets to
+/// the key's replacement, see [`merge_aliased`]. The copies keep the source
+/// of the original, e.g. to resolve relative paths against its file.
+struct Renamed<P>(P);
+
+impl<P: Provider> Provider for Renamed<P> {
+    fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        let mut data = self.0.data()?;
+        for dict in data.values_mut() {
+            for (old, new) in consts::DEPRECATED_KEYS {
+                if find(dict, new).is_some() {
+                    continue;
+                }
+                if let Some(value) = find(dict, old).cloned() {
+                    insert(dict, new, value);
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.0.profile()
+    }
+
+    fn __metadata_map(&self) -> Option<Map<Tag, Metadata>> {
+        self.0.__metadata_map()
+    }
+}
+
+/// The value of the dotted `key` in `dict`.
+fn find<'a>(dict: &'a Dict, key: &str) -> Option<&'a Value> {
+    match key.split_once('.') {
+        Some((parent, rest)) => find(dict.get(parent)?.as_dict()?, rest),
+        None => dict.get(key),
+    }
+}
+
+/// Sets the dotted `key` in `dict` to `value`, creating the tables on the
+/// way. A value in the way of a table is replaced.
+fn insert(dict: &mut Dict, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((parent, rest)) => {
+            let child = dict
+                .entry(parent.to_owned())
+                .or_insert_with(|| Value::from(Dict::new()));
+            if child.as_dict().is_none() {
+                *child = Value::from(Dict::new());
+            }
+            if let Value::Dict(_, child) = child {
+                insert(child, rest, value);
+            }
+        }
+        None => {
+            dict.insert(key.to_owned(), value);
+        }
+    }
+}
+
+/// Wraps a provider, turning every section it sets to a single value into
+/// a table with the key that value stands for, e.g. `metrics = ":9000"`
+/// into `metrics.listen = ":9000"`. Merged as is, the value would replace
+/// the whole section of the lower layers.
+pub struct Shorthands<P>(pub P);
+
+impl<P: Provider> Provider for Shorthands<P> {
+    fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        let mut data = self.0.data()?;
+        for dict in data.values_mut() {
+            for (section, key) in consts::SHORTHAND_KEYS {
+                let Some(value) = dict.remove(*section) else {
+                    continue;
+                };
+                let value = match value {
+                    Value::Dict(..) => value,
+                    value => Value::from(Dict::from([(key.to_string(), value)])),
+                };
+                dict.insert(section.to_string(), value);
+            }
+        }
+        Ok(data)
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.0.profile()
+    }
+
+    // Keeps the source of every value of a wrapped `Figment`, such as a
+    // configuration file with its includes.
+    fn __metadata_map(&self) -> Option<Map<Tag, Metadata>> {
+        self.0.__metadata_map()
+    }
+}
+
+/// Returns a warning for every deprecated key set in `figment`.
+pub fn deprecation_warnings(figment: &Figment) -> Vec<ConfigWarning> {
+    let mut locator = Locator::default();
+    consts::DEPRECATED_KEYS
+        .iter()
+        .filter(|(old, _)| figment.contains(old))
+        .map(|(old, new)| ConfigWarning::DeprecatedKey {
+            key: old.to_string(),
+            replacement: new.to_string(),
+            origin: locator.of(figment, old).map(|source| source.to_string()),
+        })
+        .collect()
+}
@@ -0,0 +1,487 @@
+use crate::{
+    aliases::{self, Shorthands},
+    consts,
+    doctor::{self, Diagnosis},
+    error::ConfigError,
+    explain::Explanation,
+    provenance,
+    report::{self, ConfigReport},
+    secrets::{self, SecretError},
+    sources::{self, DotEnv, FileOptions, KeyOverrides, StdinDocument},
+    strict,
+    types::{parse_endpoint, ExpandedPath, Secret, SecretRef, SerdeKeypair},
+    validate::{Validate, ValidationError},
+    version,
+    warning::ConfigWarning,
+    BindAddress, MagicBlockParams, RemoteCluster,
+};
+use clap::{
+    builder::TypedValueParser, parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches,
+};
+use figment::{
+    providers::{Format, Serialized, Toml},
+    value::{Dict, Map, Value},
+    Figment, Metadata, Profile, Provider,
+};
+use solana_keypair::Keypair;
+use std::{
+    ffi::{OsStr, OsString},
+    time::Duration,
+};
+
+/// Assembles [`MagicBlockParams`] from its layered sources, with knobs for
+/// embedding applications.
+///
+/// The layers, from lowest to highest precedence, are:
+/// 1. Built-in defaults (struct `Default`s and clap defaults)
+/// 2. Embedded defaults, see [`ConfigBuilder::with_embedded_defaults`]
+/// 3. Explicitly given CLI arguments
+/// 4. Configuration files
+/// 5. Custom providers, see [`ConfigBuilder::with_provider`]
+/// 6. `--set KEY=VALUE` overrides
+/// 7. The `--env-file`, unless disabled with `--no-env`
+/// 8. Environment variables, unless disabled with `--no-env`
+/// 9. Runtime patches, see [`ConfigHandle::patch`]
+///
+/// [`ConfigHandle::patch`]: crate::ConfigHandle::patch
+#[derive(Default)]
+pub struct ConfigBuilder {
+    embedded_defaults: Option<String>,
+    providers: Vec<Box<dyn Provider>>,
+    strict_config: bool,
+    no_env: bool,
+    skip_preflight: bool,
+    dry_run: bool,
+    runtime_overrides: Dict,
+    stdin: StdinDocument,
+}
+
+impl ConfigBuilder {
+    /// Adds a TOML document, typically compiled in with `include_str!`, as
+    /// the lowest-precedence layer above the built-in defaults. This lets
+    /// downstream binaries ship curated defaults of their own.
+    pub fn with_embedded_defaults(mut self, toml: &str) -> Self {
+        self.embedded_defaults = Some(toml.to_owned());
+        self
+    }
+
+    /// Adds a custom configuration source, such as a company-internal config
+    /// service. Custom providers are merged in the order they are added, on
+    /// top of the configuration files but below the `.env` file and the
+    /// environment, so operators can still override them.
+    pub fn with_provider<P: Provider + 'static>(self, provider: P) -> Self {
+        self.with_boxed_provider(Box::new(provider))
+    }
+
+    /// Like [`ConfigBuilder::with_provider`], for already boxed providers.
+    pub fn with_boxed_provider(mut self, provider: Box<dyn Provider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Rejects configuration keys that don't map to any field, as if
+    /// `--strict-config` was given. Typos then fail the build instead of
+    /// being silently ignored.
+    pub fn with_strict_config(mut self, strict: bool) -> Self {
+        self.strict_config = strict;
+        self
+    }
+
+    /// Ignores `MBV_*` environment variables, both as a layer and as
+    /// fallbacks of command-line options, along with the `--env-file`, as if
+    /// `--no-env` was given.
+    pub fn with_no_env(mut self, no_env: bool) -> Self {
+        self.no_env = no_env;
+        self
+    }
+
+    /// Skips the `--preflight` port checks, e.g. when reloading while the
+    /// ports are bound by the running application.
+    pub(crate) fn without_preflight(mut self) -> Self {
+        self.skip_preflight = true;
+        self
+    }
+
+    /// Merges `overrides` above every other layer, e.g. the patches a running
+    /// application was given.
+    pub(crate) fn with_runtime_overrides(mut self, overrides: Dict) -> Self {
+        self.runtime_overrides = overrides;
+        self
+    }
+
+    /// Reads a configuration document given as `-` from `stdin`, which
+    /// holds it once read, rather than from stdin itself, so that reloads
+    /// see the document the application was started with.
+    pub(crate) fn with_stdin(mut self, stdin: StdinDocument) -> Self {
+        self.stdin = stdin;
+        self
+    }
+
+    /// Parses `args` and assembles the final configuration from all sources.
+    ///
+    /// When no configuration file is given, the one picked from
+    /// [`sources::default_search_path`] (if any) is reported in `config`.
+    /// The assembled configuration is then checked with [`Validate`]. All
+    /// problems found are reported together in [`ConfigError::Invalid`].
+    pub fn try_build(
+        self,
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<MagicBlockParams, ConfigError> {
+        self.try_build_with_warnings(args).map(|(params, _)| params)
+    }
+
+    /// Loads and validates the configuration without using it, returning
+    /// every problem found. Sources that can't be read are reported too.
+    /// Nothing is created, see [`MagicBlockParams::prepare_storage`].
+    pub fn validate_sources(mut self, args: impl IntoIterator<Item = OsString>) -> ConfigReport {
+        self.dry_run = true;
+        match self.try_build_with_warnings(args) {
+            Ok((_, warnings)) => ConfigReport {
+                errors: Vec::new(),
+                warnings,
+            },
+            Err(ConfigError::Invalid(report)) => report,
+            Err(ConfigError::Figment(error)) => ConfigReport::from(*error),
+        }
+    }
+
+    /// Like [`ConfigBuilder::try_build`], also returning the non-fatal
+    /// problems found along the way, such as deprecated keys.
+    pub fn try_build_with_warnings(
+        self,
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<(MagicBlockParams, Vec<ConfigWarning>), ConfigError> {
+        let (params, report) = self.load(args)?;
+        let Some(params) = params.filter(|_| report.is_ok()) else {
+            return Err(ConfigError::Invalid(report));
+        };
+        Ok((params, report.warnings))
+    }
+
+    /// Loads and checks the configuration like [`ConfigBuilder::try_build`],
+    /// then probes the host and the remote cluster for common problems, see
+    /// [`Diagnosis`]. Problems are reported rather than returned as errors.
+    /// Like [`ConfigBuilder::validate_sources`], nothing is created.
+    pub fn doctor(
+        mut self,
+        args: impl IntoIterator<Item = OsString>,
+        timeout: Duration,
+    ) -> Diagnosis {
+        self.dry_run = true;
+        let (params, report) = match self.load(args) {
+            Ok(loaded) => loaded,
+            Err(ConfigError::Invalid(report)) => (None, report),
+            Err(ConfigError::Figment(error)) => (None, ConfigReport::from(*error)),
+        };
+        doctor::diagnose(params.as_ref(), &report, timeout)
+    }
+
+    /// Parses `args`, merges every layer and checks the result, returning
+    /// the configuration, if it could be extracted, and every problem found.
+    fn load(
+        self,
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<(Option<MagicBlockParams>, ConfigReport), ConfigError> {
+        let strict_config = self.strict_config;
+        let skip_preflight = self.skip_preflight;
+        let dry_run = self.dry_run;
+        let (cli, figment) = self.assemble(args)?;
+
+        let mut report = ConfigReport {
+            errors: Vec::new(),
+            warnings: aliases::deprecation_warnings(&figment),
+        };
+        let strict_config = strict_config || cli.strict_config;
+        let unknown = strict::unknown_keys(&figment)?;
+        if strict_config {
+            report.errors.extend(unknown.into_iter().map(Into::into));
+        } else {
+            let unknown = unknown.into_iter().map(ConfigWarning::UnknownKey);
+            report.warnings.extend(unknown);
+        }
+        match version::check(&figment) {
+            Ok(warning) => report.warnings.extend(warning),
+            Err(error) => report.errors.push(error),
+        }
+        let provenance = provenance::collect(&figment);
+        let params = report::extract_all(figment, &mut report.errors).map(|mut params| {
+            params.provenance = provenance;
+            params.profile = cli.profile;
+            params.config_timeout = cli.config_timeout;
+            params.strict_config = strict_config;
+            params.preflight = cli.preflight;
+            params.no_env = cli.no_env;
+            params.verbose = cli.verbose;
+            params.quiet = cli.quiet;
+            let steps = i16::from(cli.verbose) - i16::from(cli.quiet);
+            params.log.level = params.log.level.adjusted(steps);
+            params.expand_bind_addresses();
+            params.apply_lifecycle_defaults();
+            params.resolve_remote_aliases();
+            params.resolve_relative_paths();
+            report.errors.extend(params.validate());
+            if params.preflight && !skip_preflight {
+                report.errors.extend(params.check_ports());
+            }
+            if report.is_ok() && !dry_run {
+                report.errors.extend(params.prepare_storage());
+            }
+            params
+        });
+        Ok((params, report))
+    }
+
+    /// Explains where the effective value of the dotted `key` comes from,
+    /// without extracting or validating the rest of the configuration.
+    pub fn explain(
+        self,
+        args: impl IntoIterator<Item = OsString>,
+        key: &str,
+    ) -> Result<Explanation, ConfigError> {
+        let (_, figment) = self.assemble(args)?;
+        Explanation::of(&figment, key).ok_or_else(|| {
+            ConfigError::Invalid(ConfigReport {
+                errors: vec![ValidationError::new(key, "not set by any layer")],
+                warnings: Vec::new(),
+            })
+        })
+    }
+
+    /// Explains every key set by any layer, like [`ConfigBuilder::explain`]
+    /// does for a single one.
+    pub fn provenance(
+        self,
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<Vec<Explanation>, ConfigError> {
+        let (_, figment) = self.assemble(args)?;
+        Ok(Explanation::all(&figment)?)
+    }
+
+    /// Parses `args` and merges every layer, returning the parsed arguments
+    /// and the merged layers.
+    fn assemble(
+        self,
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<(MagicBlockParams, Figment), ConfigError> {
+        let args: Vec<OsString> = args.into_iter().collect();
+        // Known before parsing, as parsing is what reads the environment.
+        let no_env = self.no_env
+            || args
+                .iter()
+                .skip(1)
+                .take_while(|arg| *arg != "--")
+                .any(|arg| arg == "--no-env");
+        let mut command = MagicBlockParams::command();
+        if no_env {
+            command = command.mut_args(|arg| arg.env(None));
+        }
+        let (mut cli, matches) = parse_command_line(command, args);
+        // Only `MBV_CONFIG` is a list, as `--config` can be repeated instead
+        // and its paths and URLs may contain colons.
+        if matches.value_source("config") == Some(ValueSource::EnvVariable) {
+            cli.config = cli
+                .config
+                .iter()
+                .flat_map(|list| sources::split_config_list(list))
+                .map(ExpandedPath::from)
+                .collect();
+        }
+        if cli.config.is_empty() {
+            cli.config
+                .extend(sources::find_default_config().map(ExpandedPath::from));
+        }
+        let options = FileOptions {
+            stdin: self.stdin,
+            ..FileOptions::from(&cli)
+        };
+
+        let mut figment = secrets::exposed(|| {
+            Figment::new().merge(Named(
+                provenance::BUILT_IN_DEFAULTS,
+                Serialized::defaults(&cli),
+            ))
+        });
+        if let Some(toml) = &self.embedded_defaults {
+            let embedded = Toml::string(toml).profile(Profile::Default);
+            figment = aliases::merge_aliased(figment, Named("embedded defaults", embedded));
+        }
+        let explicit = Serialized::defaults(explicit_args(&cli, &matches)?);
+        figment = figment.merge(Named("command-line arguments", explicit));
+        for path in &cli.config {
+            figment = sources::merge_config_file(figment, path, &options)?;
+        }
+        for provider in self.providers {
+            figment = aliases::merge_aliased(figment, BoxedProvider(provider));
+        }
+        if !cli.overrides.is_empty() {
+            let overrides = KeyOverrides(cli.overrides.clone());
+            figment = aliases::merge_aliased(figment, overrides);
+        }
+        if no_env {
+            cli.no_env = true;
+        } else {
+            if let Some(path) = &cli.env_file {
+                figment = figment.merge(Shorthands(DotEnv::read(path)?));
+            }
+            let env = sources::env_provider();
+            figment = figment.merge(Shorthands(Named(sources::ENVIRONMENT_LAYER, env)));
+        }
+        if !self.runtime_overrides.is_empty() {
+            let overrides = Serialized::defaults(self.runtime_overrides);
+            figment = figment.merge(Named("runtime patches", overrides));
+        }
+        Ok((cli, figment))
+    }
+}
+
+/// Adapts a boxed provider to `Figment::merge`, which needs a sized type.
+struct BoxedProvider(Box<dyn Provider>);
+
+impl Provider for BoxedProvider {
+    fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        self.0.data()
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.0.profile()
+    }
+}
+
+/// Gives a layer a name for error messages and [`Explanation`]s, in place of
+/// the provider's type name.
+struct Named<P>(&'static str, P);
+
+impl<P: Provider> Provider for Named<P> {
+    fn metadata(&self) -> Metadata {
+        Metadata::named(self.0)
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        self.1.data()
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.1.profile()
+    }
+}
+
+/// Parses `args` with `command`, exiting on invalid arguments. `--metrics off`
+/// can't be expressed with the derived parser, which only knows addresses,
+/// so it is parsed here and turns the endpoint off like `--no-metrics`.
+/// Likewise, the derived parser keeps only the first of repeated `--remote`
+/// flags, so their remotes are joined here in the order given.
+pub(crate) fn parse_command_line(
+    command: clap::Command,
+    args: Vec<OsString>,
+) -> (MagicBlockParams, ArgMatches) {
+    let command = command
+        .mut_arg("metrics", |arg| {
+            arg.value_parser(parse_endpoint::<BindAddress>)
+        })
+        .mut_arg("keypair", |arg| arg.value_parser(KeypairParser));
+    let matches = command.get_matches_from(args);
+    let mut derived = matches.clone();
+    let metrics = derived.remove_one::<Option<BindAddress>>("metrics");
+    let mut cli = MagicBlockParams::from_arg_matches(&derived).unwrap_or_else(|e| e.exit());
+    match metrics {
+        Some(Some(address)) => cli.metrics.listen = Some(address),
+        Some(None) => cli.no_metrics = true,
+        None => {}
+    }
+    if let Some(clusters) = matches.get_many::<RemoteCluster>("remote") {
+        if clusters.len() > 1 {
+            cli.remote = clusters
+                .flat_map(|cluster| cluster.remotes().iter().cloned())
+                .collect();
+        }
+    }
+    (cli, matches)
+}
+
+/// Parses `--keypair` without resolving a reference, which is only carried
+/// over into the sources and resolved once the configuration is extracted.
+/// Unlike clap's own errors, its errors leave out the (secret) value.
+#[derive(Clone)]
+struct KeypairParser;
+
+impl TypedValueParser for KeypairParser {
+    type Value = SecretRef<Secret<SerdeKeypair>>;
+
+    fn parse_ref(
+        &self,
+        command: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let invalid = |message: &dyn std::fmt::Display| {
+            let arg = arg.map_or_else(|| "...".to_owned(), ToString::to_string);
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!("invalid value for '{arg}': {message}\n"),
+            )
+            .with_cmd(command)
+        };
+        let s = value.to_str().ok_or_else(|| invalid(&"not valid UTF-8"))?;
+        SecretRef::parse_unresolved(s, || Secret::new(SerdeKeypair(Keypair::new())))
+            .map_err(|e: SecretError| invalid(&e))
+    }
+}
+
+/// Returns the values of `cli` that were given explicitly, either on the
+/// command line or through a clap environment fallback, leaving out clap's
+/// default values.
+fn explicit_args(cli: &MagicBlockParams, matches: &ArgMatches) -> figment::Result<Dict> {
+    let all = secrets::exposed(|| Figment::from(Serialized::defaults(cli)).data())?;
+    let all = all.get(&Profile::Default).cloned().unwrap_or_default();
+    let mut explicit = Figment::new();
+    for id in matches.ids() {
+        let id = id.as_str();
+        if matches!(
+            matches.value_source(id),
+            None | Some(ValueSource::DefaultValue)
+        ) {
+            continue;
+        }
+        if let Some((path, value)) = find_key(&all, &id.replace('_', "-"), "") {
+            explicit = explicit.merge(Serialized::default(&path, value));
+        }
+    }
+    // Negative flags aren't configuration keys themselves, but unset or turn
+    // off the key they negate.
+    if cli.no_metrics {
+        explicit = explicit.merge(Serialized::default("metrics.listen", None::<BindAddress>));
+    }
+    if cli.no_ledger_reset {
+        explicit = explicit.merge(Serialized::default("ledger.reset", false));
+    }
+    let explicit = explicit.data()?;
+    Ok(explicit.get(&Profile::Default).cloned().unwrap_or_default())
+}
+
+/// Finds `key` in `dict` or any nested dictionary, returning its dotted path
+/// and value. Flattened CLI arguments live in nested sections, and a flag
+/// named after a section, such as `--metrics`, sets the key it is shorthand
+/// for, see [`consts::SHORTHAND_KEYS`].
+pub(crate) fn find_key(dict: &Dict, key: &str, prefix: &str) -> Option<(String, Value)> {
+    if let Some(value) = dict.get(key) {
+        let path = format!("{prefix}{key}");
+        let shorthand = consts::SHORTHAND_KEYS
+            .iter()
+            .find(|(section, _)| *section == path);
+        return match shorthand {
+            Some((_, field)) => {
+                let value = value.as_dict()?.get(*field)?;
+                Some((format!("{path}.{field}"), value.clone()))
+            }
+            None => Some((path, value.clone())),
+        };
+    }
+    dict.iter().find_map(|(name, value)| {
+        let nested = value.as_dict()?;
+        find_key(nested, key, &format!("{prefix}{name}."))
+    })
+}
@@ -1,115 +1,441 @@
-use crate::consts;
-use crate::types::SerdeKeypair;
-use clap::Parser;
-use consts::{DEFAULT_BASE_FEE_STR, DEFAULT_VALIDATOR_KEYPAIR};
+use crate::types::{
+    BindAddress, BoundedU64, ByteSize, CommitmentLevel, ConnectionOptions, HumanDuration, Lamports,
+    Percent, Secret, SecretRef, SerdeKeypair, SerdePubkey, TlsIdentity,
+};
+use crate::{consts, secrets};
+use clap::{ArgAction, Parser};
+use consts::{
+    DEFAULT_BASE_FEE_STR, DEFAULT_BLOCKS_PER_PARTITION_STR, DEFAULT_BLOCK_TIME_STR,
+    DEFAULT_COMPUTE_UNIT_PRICE_STR, DEFAULT_VALIDATOR_KEYPAIR,
+};
 use isocountry::CountryCode;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::serde_as;
-use std::time::Duration;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    str::FromStr,
+};
 use url::Url;
 
+/// Base fee in lamports, at most [`consts::MAX_BASE_FEE`].
+pub type BaseFee = Lamports<{ consts::MAX_BASE_FEE }>;
+/// Compute unit price in micro-lamports, at most [`consts::MAX_COMPUTE_UNIT_PRICE`].
+pub type ComputeUnitPrice = BoundedU64<0, { consts::MAX_COMPUTE_UNIT_PRICE }>;
+/// Accounts database size in bytes.
+pub type DatabaseSize = ByteSize<{ consts::MIN_DATABASE_SIZE }, { consts::MAX_DATABASE_SIZE }>;
+/// Accounts index size in bytes.
+pub type IndexSize = ByteSize<{ consts::MIN_INDEX_SIZE }, { consts::MAX_INDEX_SIZE }>;
+
 //==============================================================================
 // 2. CLI-Exposed & File-Exposed Configuration Sections
 //==============================================================================
 
 /// Configuration for the validator behavior.
-#[derive(Parser, Deserialize, Serialize, Debug)]
+#[derive(Parser, Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(default, rename_all = "kebab-case")]
 #[clap(rename_all = "kebab-case")]
 pub struct ValidatorConfig {
-    /// Base fee in lamports for transactions.
+    /// Base fee in lamports for transactions, or an amount such as
+    /// `0.000005 SOL`.
     #[arg(long, env = "MBV_VALIDATOR_BASEFEE", default_value = DEFAULT_BASE_FEE_STR)]
-    pub basefee: u64,
+    pub basefee: BaseFee,
 
-    /// The validator's identity keypair, encoded in Base58.
+    /// The validator's identity keypair, encoded in Base58 or hex or as a
+    /// JSON array of bytes, or a reference such as
+    /// `aws-ssm:/magicblock/prod/keypair` or `file:/etc/magicblock/id.json`,
+    /// a Solana JSON keypair file. With `prompt`, it is read from the
+    /// terminal without echo, and with `prompt:fd=3` from file descriptor 3.
     #[arg(long, short, env = "MBV_VALIDATOR_KEYPAIR", default_value = DEFAULT_VALIDATOR_KEYPAIR)]
-    pub keypair: SerdeKeypair,
+    pub keypair: SecretRef<Secret<SerdeKeypair>>,
+
+    /// A remote signer holding the identity instead, so that the private key
+    /// never lives on the validator host. Takes precedence over `keypair`.
+    #[arg(skip)]
+    pub signer: Option<RemoteSigner>,
+
+    /// The keypair paying the fees of commit transactions, instead of the
+    /// identity.
+    #[arg(skip)]
+    #[serde(deserialize_with = "deserialize_commit_fee_payer")]
+    pub commit_fee_payer: Option<RoleKeypair>,
+
+    /// The keypair with authority to claim the validator's fees, instead of
+    /// the identity.
+    #[arg(skip)]
+    #[serde(deserialize_with = "deserialize_fee_claim_authority")]
+    pub fee_claim_authority: Option<RoleKeypair>,
+}
+
+impl ValidatorConfig {
+    /// Where the validator's identity comes from: the remote `signer` if
+    /// one is set, the local `keypair` otherwise.
+    pub fn identity(&self) -> IdentitySource {
+        match &self.signer {
+            Some(signer) => IdentitySource::Remote(signer.clone()),
+            None => IdentitySource::Local(self.keypair.expose_secret().clone()),
+        }
+    }
+
+    /// Where the keypair of `role` comes from: its own keypair or signer if
+    /// one is set, the identity otherwise.
+    pub fn keypair_for(&self, role: KeyRole) -> IdentitySource {
+        let keypair = match role {
+            KeyRole::Identity => None,
+            KeyRole::CommitFeePayer => self.commit_fee_payer.as_ref(),
+            KeyRole::FeeClaimAuthority => self.fee_claim_authority.as_ref(),
+        };
+        keypair
+            .and_then(RoleKeypair::source)
+            .unwrap_or_else(|| self.identity())
+    }
+}
+
+/// Deserializes the keypair of `role`, asking for it by the role's name if
+/// it is `prompt`, rather than as the identity.
+fn deserialize_role<'de, D: Deserializer<'de>>(
+    role: KeyRole,
+    deserializer: D,
+) -> Result<Option<RoleKeypair>, D::Error> {
+    secrets::prompting_for(role.prompt_label(), || {
+        Option::<RoleKeypair>::deserialize(deserializer)
+    })
+}
+
+fn deserialize_commit_fee_payer<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<RoleKeypair>, D::Error> {
+    deserialize_role(KeyRole::CommitFeePayer, deserializer)
+}
+
+fn deserialize_fee_claim_authority<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<RoleKeypair>, D::Error> {
+    deserialize_role(KeyRole::FeeClaimAuthority, deserializer)
+}
+
+/// A role the validator signs transactions in, see
+/// [`ValidatorConfig::keypair_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyRole {
+    /// The validator's identity.
+    Identity,
+    /// The payer of the fees of commit transactions.
+    CommitFeePayer,
+    /// The authority claiming the validator's fees.
+    FeeClaimAuthority,
+}
+
+impl KeyRole {
+    /// What a keypair of this role set to `prompt` is asked for as.
+    fn prompt_label(self) -> &'static str {
+        match self {
+            Self::Identity => secrets::IDENTITY_PROMPT_LABEL,
+            Self::CommitFeePayer => "Commit fee payer keypair (Base58)",
+            Self::FeeClaimAuthority => "Fee claim authority keypair (Base58)",
+        }
+    }
+}
+
+/// The keypair of a role other than the identity, given like the identity:
+/// as a keypair or a reference to one, or as a remote signer, which takes
+/// precedence.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct RoleKeypair {
+    /// The keypair, encoded in Base58, or a reference to one, e.g. with
+    /// `file:` or `env:`.
+    pub keypair: Option<SecretRef<Secret<SerdeKeypair>>>,
+    /// A remote signer holding the keypair instead.
+    pub signer: Option<RemoteSigner>,
+}
+
+impl RoleKeypair {
+    /// Where the keypair comes from, or `None` if neither `keypair` nor
+    /// `signer` is set.
+    pub fn source(&self) -> Option<IdentitySource> {
+        match (&self.signer, &self.keypair) {
+            (Some(signer), _) => Some(IdentitySource::Remote(signer.clone())),
+            (None, Some(keypair)) => Some(IdentitySource::Local(keypair.expose_secret().clone())),
+            (None, None) => None,
+        }
+    }
 }
 
 impl Default for ValidatorConfig {
     fn default() -> Self {
         Self {
-            basefee: consts::DEFAULT_BASE_FEE,
-            keypair: SerdeKeypair(solana_keypair::Keypair::from_base58_string(
-                consts::DEFAULT_VALIDATOR_KEYPAIR,
-            )),
+            basefee: BaseFee::new(consts::DEFAULT_BASE_FEE)
+                .expect("Default base fee should be in range"),
+            keypair: SecretRef::new(Secret::new(SerdeKeypair(
+                solana_keypair::Keypair::from_base58_string(consts::DEFAULT_VALIDATOR_KEYPAIR),
+            ))),
+            signer: None,
+            commit_fee_payer: None,
+            fee_claim_authority: None,
         }
     }
 }
 
-//==============================================================================
-// 3. File-Only Configuration Sections
-//==============================================================================
+/// A remote signer holding the validator's identity, or the keypair of
+/// another role.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteSigner {
+    /// URL of the signer, e.g. `https://signer.internal:8443`.
+    pub url: Url,
+    /// Bearer token to authenticate with the signer, or a secret store reference.
+    pub token: SecretRef<Secret<String>>,
+    /// Public key of the keypair the signer holds.
+    pub pubkey: SerdePubkey,
+    /// How to connect to the signer.
+    #[serde(default)]
+    pub connection: ConnectionOptions,
+}
+
+/// Where the validator's identity, or the keypair of another role, comes
+/// from, see [`ValidatorConfig::identity`] and [`ValidatorConfig::keypair_for`].
+#[derive(Clone)]
+pub enum IdentitySource {
+    /// A keypair held by the validator itself.
+    Local(SerdeKeypair),
+    /// A remote signer holding the keypair.
+    Remote(RemoteSigner),
+}
+
+impl IdentitySource {
+    /// The public key of the keypair.
+    pub fn pubkey(&self) -> solana_pubkey::Pubkey {
+        match self {
+            Self::Local(keypair) => crate::keygen::pubkey(&keypair.0),
+            Self::Remote(signer) => signer.pubkey.0,
+        }
+    }
+}
+
+/// Never shows the local keypair.
+impl fmt::Debug for IdentitySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(_) => write!(f, "Local({})", consts::REDACTED),
+            Self::Remote(signer) => f.debug_tuple("Remote").field(signer).finish(),
+        }
+    }
+}
 
 /// Defines the strategy for committing transactions to the ledger.
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Parser, Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
+#[clap(rename_all = "kebab-case")]
 pub struct CommitStrategy {
     /// Compute unit price in micro-lamports for commit transactions.
-    pub compute_unit_price: u64,
+    #[arg(
+        long,
+        env = "MBV_COMMIT_COMPUTE_UNIT_PRICE",
+        default_value = DEFAULT_COMPUTE_UNIT_PRICE_STR
+    )]
+    pub compute_unit_price: ComputeUnitPrice,
+    /// Name of the remote in `[remotes]` to send commit transactions to,
+    /// instead of the primary `remote`.
+    #[arg(skip)]
+    pub remote: Option<String>,
 }
 
 impl Default for CommitStrategy {
     fn default() -> Self {
         Self {
-            compute_unit_price: 1_000_000,
+            compute_unit_price: ComputeUnitPrice::new(consts::DEFAULT_COMPUTE_UNIT_PRICE)
+                .expect("Default compute unit price should be in range"),
+            remote: None,
         }
     }
 }
 
-/// Configuration for on-chain operations and validator identity.
-#[serde_as]
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub struct ChainOperationConfig {
-    /// Validator's two-letter country code (e.g., "US").
-    pub country_code: CountryCode,
-    /// Validator's fully qualified domain name (FQDN).
-    pub fqdn: Url,
-    /// How often to claim fees from the chain
-    #[serde(with = "humantime")]
-    pub claim_fees_frequency: Duration,
-}
-
 /// Configuration for the ledger database.
 #[serde_as]
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Parser, Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
+#[clap(rename_all = "kebab-case")]
 pub struct LedgerConfig {
+    /// Number of blocks stored in each ledger partition.
+    #[arg(
+        long,
+        env = "MBV_LEDGER_BLOCKS_PER_PARTITION",
+        default_value = DEFAULT_BLOCKS_PER_PARTITION_STR
+    )]
     pub blocks_per_partition: usize,
     /// Target time per blocks
-    #[serde(with = "humantime")]
-    pub block_time: Duration,
+    #[arg(
+        long = "ledger-block-time",
+        alias = "block-time",
+        env = "MBV_LEDGER_BLOCK_TIME",
+        default_value = DEFAULT_BLOCK_TIME_STR
+    )]
+    pub block_time: HumanDuration,
+    /// Whether to reset the ledger on startup.
+    #[arg(
+        long = "ledger-reset",
+        env = "MBV_LEDGER_RESET",
+        value_name = "BOOL",
+        default_value_t = true,
+        action = ArgAction::Set
+    )]
     pub reset: bool,
 }
 
 impl Default for LedgerConfig {
     fn default() -> Self {
         Self {
-            blocks_per_partition: 1024 * 1024,
-            block_time: Duration::from_millis(400),
+            blocks_per_partition: consts::DEFAULT_BLOCKS_PER_PARTITION,
+            block_time: HumanDuration(consts::DEFAULT_BLOCK_TIME),
             reset: true,
         }
     }
 }
 
+/// Configuration for the metrics endpoint. `metrics = "127.0.0.1:9000"`,
+/// like `--metrics`, is shorthand for `metrics.listen`.
+#[derive(Parser, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+#[clap(rename_all = "kebab-case")]
+pub struct MetricsConfig {
+    /// Listen address for the metrics endpoint. If disabled, this is not set.
+    /// `off`, `disabled` or `none` turn it off, e.g. one enabled by a lower
+    /// layer.
+    #[arg(
+        id = "metrics",
+        long = "metrics",
+        short = 'm',
+        value_name = "METRICS",
+        env = "MBV_METRICS"
+    )]
+    #[serde(deserialize_with = "crate::types::deserialize_endpoint")]
+    pub listen: Option<BindAddress>,
+    /// HTTP path the metrics are served at.
+    #[arg(skip = String::from(consts::DEFAULT_METRICS_PATH))]
+    pub path: String,
+    /// Labels added to every metric, e.g. `cluster = "devnet"`.
+    #[arg(skip)]
+    pub labels: BTreeMap<String, String>,
+    /// Bucket boundaries by histogram name, in place of its built-in ones,
+    /// e.g. `rpc-request-duration = [0.005, 0.01, 0.1, 1.0]`.
+    #[arg(skip)]
+    pub buckets: BTreeMap<String, Vec<f64>>,
+    /// Whether each subsystem records metrics, e.g. `accounts-db = false`.
+    /// Subsystems not listed record them.
+    #[arg(skip)]
+    pub subsystems: BTreeMap<String, bool>,
+    /// Prometheus push gateway to push the metrics to as well.
+    #[arg(skip)]
+    pub push_gateway: PushGatewayConfig,
+    /// Serve the metrics over TLS with this identity. Formerly
+    /// `[metrics-tls]`.
+    #[arg(skip)]
+    pub tls: Option<TlsIdentity>,
+}
+
+impl MetricsConfig {
+    /// Whether the `subsystem` records metrics, see `subsystems`.
+    pub fn is_enabled(&self, subsystem: &str) -> bool {
+        self.subsystems.get(subsystem).copied().unwrap_or(true)
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            listen: None,
+            path: consts::DEFAULT_METRICS_PATH.to_owned(),
+            labels: BTreeMap::new(),
+            buckets: BTreeMap::new(),
+            subsystems: BTreeMap::new(),
+            push_gateway: PushGatewayConfig::default(),
+            tls: None,
+        }
+    }
+}
+
+/// A Prometheus push gateway, for short-lived or firewalled nodes that
+/// can't be scraped.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct PushGatewayConfig {
+    /// URL of the push gateway, e.g. `http://pushgateway:9091`. Nothing is
+    /// pushed if not set.
+    pub url: Option<Url>,
+    /// How often the metrics are pushed.
+    pub interval: HumanDuration,
+}
+
+impl Default for PushGatewayConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            interval: HumanDuration(consts::DEFAULT_PUSH_GATEWAY_INTERVAL),
+        }
+    }
+}
+
+//==============================================================================
+// 3. File-Only Configuration Sections
+//==============================================================================
+
+/// Configuration for on-chain operations and validator identity.
+#[serde_as]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct ChainOperationConfig {
+    /// Validator's two-letter country code (e.g., "US").
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub country_code: CountryCode,
+    /// Validator's fully qualified domain name (FQDN).
+    pub fqdn: Url,
+    /// How often to claim fees from the chain
+    pub claim_fees_frequency: HumanDuration,
+}
+
 /// Configuration specific to ChainLink oracle integration.
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct ChainLinkConfig {
+    /// Whether to prepare address lookup tables.
     pub prepare_lookup_tables: bool,
-    pub auto_airdrop_lamports: u64,
+    /// Lamports to airdrop automatically, or an amount such as `1 SOL`.
+    pub auto_airdrop_lamports: Lamports,
+    /// Commitment at which accounts are cloned from the base chain. At least
+    /// `confirmed` in the `ephemeral` lifecycle.
+    pub commitment: CommitmentLevel,
+    /// Maximum number of monitored accounts.
     pub max_monitored_accounts: usize,
+    /// Name of the remote in `[remotes]` to clone accounts from, instead of
+    /// the primary `remote`.
+    pub remote: Option<String>,
 }
 
 /// Configuration for the accounts database.
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct AccountsDbConfig {
-    pub database_size: usize,
+    /// Accounts database size, in bytes or with a unit, e.g. `512MiB`.
+    pub database_size: DatabaseSize,
+    /// Block size of the database in bytes: 128, 256 or 512.
     pub block_size: BlockSize,
-    pub index_size: usize,
+    /// Accounts index size, in bytes or with a unit, smaller than
+    /// `database_size`.
+    pub index_size: IndexSize,
+    /// Number of snapshots to keep.
     pub max_snapshots: u16,
+    /// Number of blocks between snapshots, at most `blocks_per_partition`.
     pub snapshot_frequency: u64,
 }
 
@@ -117,20 +443,302 @@ impl Default for AccountsDbConfig {
     fn default() -> Self {
         Self {
             block_size: BlockSize::Block256,
-            database_size: 100 * 1024 * 1024,
-            index_size: 1024 * 1024,
+            database_size: DatabaseSize::new(consts::DEFAULT_DATABASE_SIZE)
+                .expect("Default database size should be in range"),
+            index_size: IndexSize::new(consts::DEFAULT_INDEX_SIZE)
+                .expect("Default index size should be in range"),
             max_snapshots: 4,
             snapshot_frequency: 1024,
         }
     }
 }
 
-/// Block size for the accounts DB.
-#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy)]
+/// Configuration for outbound network connections.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct NetworkConfig {
+    /// Proxy to reach the remote clusters through.
+    pub proxy: ProxyConfig,
+}
+
+/// A proxy for connections to remote clusters, kept to this configuration
+/// rather than the `HTTP_PROXY` family of variables, which other parts of
+/// the process would pick up too.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct ProxyConfig {
+    /// URL of the proxy, with scheme `http`, `https`, `socks5` or
+    /// `socks5h`. Remotes are connected to directly if not set. A password
+    /// in the URL is redacted when the configuration is printed.
+    #[serde(serialize_with = "secrets::serialize_url")]
+    pub url: Option<Url>,
+    /// Hosts to connect to directly, including their subdomains, e.g.
+    /// `localhost` or `.internal`. `*` bypasses the proxy for every host.
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Whether connections to `host` skip the proxy, as `host` or one of
+    /// its parent domains is in `no-proxy`.
+    pub fn bypasses(&self, host: &str) -> bool {
+        let host = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase();
+        self.no_proxy.iter().any(|entry| {
+            let domain = entry.trim_start_matches("*.").trim_start_matches('.');
+            let domain = domain.to_ascii_lowercase();
+            entry == "*"
+                || host == domain
+                || host
+                    .strip_suffix(&domain)
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+        })
+    }
+}
+
+/// Configuration for the admin HTTP service, served with the `admin` feature.
+/// It only speaks plain HTTP, so TLS must be terminated in front of it, e.g.
+/// by a reverse proxy, for the token not to cross the network in the clear.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct AdminConfig {
+    /// Listen address of the admin service.
+    pub listen: BindAddress,
+    /// Bearer token every request must carry, or a secret store reference.
+    pub token: SecretRef<Secret<String>>,
+}
+
+/// Configuration for logging, for binaries to set up their logger with.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct LogConfig {
+    /// Level of the modules without a filter of their own, raised by `-v`
+    /// and lowered by `-q`.
+    pub level: LogLevel,
+    /// Levels of individual modules, e.g. `magicblock_ledger = "debug"`.
+    pub filters: BTreeMap<String, LogLevel>,
+    /// Format of the log lines.
+    pub format: LogFormat,
+}
+
+impl LogConfig {
+    /// The filter, in the `RUST_LOG` syntax understood by `env_logger` and
+    /// `tracing_subscriber::EnvFilter`, e.g. `info,magicblock_ledger=debug`.
+    pub fn filter(&self) -> String {
+        let mut filter = self.level.to_string();
+        for (module, level) in &self.filters {
+            filter.push_str(&format!(",{module}={level}"));
+        }
+        filter
+    }
+}
+
+/// Severity of log messages, from none at all to every message.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    const ALL: [Self; 6] = [
+        Self::Off,
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    /// The level `steps` more verbose than this one, or less verbose if
+    /// `steps` is negative, stopping at `off` and `trace`.
+    pub fn adjusted(self, steps: i16) -> Self {
+        let index = (self as i16 + steps).clamp(0, Self::ALL.len() as i16 - 1);
+        Self::ALL[index as usize]
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        })
+    }
+}
+
+/// Format of log lines.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// Human-readable lines with every field.
+    #[default]
+    Full,
+    /// Shorter human-readable lines.
+    Compact,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+/// Configuration for exporting traces over OTLP, for binaries to set up
+/// their OpenTelemetry pipeline with.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct TracingConfig {
+    /// URL of the OTLP collector, e.g. `http://127.0.0.1:4317`. Traces are
+    /// not exported if not set.
+    pub endpoint: Option<Url>,
+    /// Protocol spoken with the collector.
+    pub protocol: OtlpProtocol,
+    /// Headers sent with every export, e.g. an API key of a hosted
+    /// collector. Each value can be a secret reference.
+    pub headers: BTreeMap<String, SecretRef<Secret<String>>>,
+    /// Share of traces to sample, e.g. `10%` or `0.1`.
+    pub sampling_ratio: Percent,
+    /// Attributes of the resource the traces come from, e.g.
+    /// `"service.name" = "magicblock-validator"`.
+    pub resource_attributes: BTreeMap<String, String>,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            protocol: OtlpProtocol::default(),
+            headers: BTreeMap::new(),
+            sampling_ratio: Percent::new(consts::DEFAULT_TRACING_SAMPLING_RATIO)
+                .expect("Default sampling ratio should be in range"),
+            resource_attributes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Protocol of the OTLP exporter, named as in `OTEL_EXPORTER_OTLP_PROTOCOL`.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum OtlpProtocol {
+    /// Protobuf over gRPC, usually on port 4317.
+    #[default]
+    #[serde(rename = "grpc")]
+    Grpc,
+    /// Protobuf over HTTP, usually on port 4318.
+    #[serde(rename = "http/protobuf")]
+    HttpProtobuf,
+    /// JSON over HTTP, usually on port 4318.
+    #[serde(rename = "http/json")]
+    HttpJson,
+}
+
+/// Block size for the accounts DB, in bytes. Written as the number, e.g.
+/// `block-size = 256`, and also accepted as a string or by its variant name
+/// (`block256`) for older files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum BlockSize {
     Block128 = 128,
     #[default]
     Block256 = 256,
     Block512 = 512,
 }
+
+/// An error raised when parsing a [`BlockSize`] that is not one of the
+/// supported sizes.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid block size `{0}`, expected one of 128, 256 or 512")]
+pub struct BlockSizeError(String);
+
+impl BlockSize {
+    const ALL: [Self; 3] = [Self::Block128, Self::Block256, Self::Block512];
+
+    /// The block size in bytes.
+    pub fn bytes(self) -> u64 {
+        self as u64
+    }
+}
+
+impl TryFrom<u64> for BlockSize {
+    type Error = BlockSizeError;
+    fn try_from(bytes: u64) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|size| size.bytes() == bytes)
+            .ok_or_else(|| BlockSizeError(bytes.to_string()))
+    }
+}
+
+impl FromStr for BlockSize {
+    type Err = BlockSizeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let number = trimmed
+            .get(..5)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("block"))
+            .map_or(trimmed, |_| &trimmed[5..]);
+        number
+            .parse::<u64>()
+            .ok()
+            .and_then(|bytes| Self::try_from(bytes).ok())
+            .ok_or_else(|| BlockSizeError(s.to_owned()))
+    }
+}
+
+impl Display for BlockSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bytes())
+    }
+}
+
+impl Serialize for BlockSize {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.bytes())
+    }
+}
+
+/// Accepts the size as a number or a string parsed like
+/// [`BlockSize::from_str`].
+impl<'de> Deserialize<'de> for BlockSize {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = BlockSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a block size of 128, 256 or 512")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                BlockSize::try_from(v).map_err(E::custom)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                let bytes =
+                    u64::try_from(v).map_err(|_| E::custom(BlockSizeError(v.to_string())))?;
+                self.visit_u64(bytes)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
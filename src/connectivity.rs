@@ -0,0 +1,514 @@
+//! Connectivity checks against the configured remote cluster, so that a
+//! misconfigured remote is found before startup rather than minutes into it.
+
+use crate::{
+    remote::{Remote, RemoteAuth, RemoteCluster},
+    MagicBlockParams,
+};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{de::DeserializeOwned, Deserialize};
+use solana_pubkey::Pubkey;
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::{self, Display},
+    io::{self, Read, Write},
+    net::{IpAddr, TcpStream},
+    time::{Duration, Instant, SystemTime},
+};
+use tungstenite::{
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+};
+use url::{Host, Url};
+
+/// Port of SOCKS proxies whose URL doesn't give one.
+const SOCKS_PORT: u16 = 1080;
+/// Largest response head accepted from an HTTP proxy.
+const MAX_PROXY_RESPONSE: usize = 8 * 1024;
+
+/// Outcome of checking a single remote endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointCheck {
+    /// The endpoint, with aliases resolved.
+    pub url: Url,
+    /// Round-trip time of the first request, or of the WebSocket handshake.
+    pub latency: Duration,
+    /// What the endpoint reported, or why it failed the check.
+    pub status: Result<EndpointStatus, String>,
+}
+
+/// What a reachable, healthy endpoint reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndpointStatus {
+    /// An HTTP endpoint that answered `getVersion`, `getHealth` and
+    /// `getGenesisHash`, matching the `expected-genesis` hash if one is
+    /// pinned.
+    Http {
+        /// Version of the node software, e.g. `2.1.0`.
+        version: String,
+        /// Genesis hash of the cluster the node belongs to.
+        genesis_hash: String,
+    },
+    /// A WebSocket endpoint that completed the handshake.
+    WebSocket,
+    /// An endpoint that wasn't checked, for the given reason, e.g. as it is
+    /// behind a proxy the checks can't connect through.
+    Skipped(String),
+}
+
+/// Outcome of checking every endpoint of the remote cluster.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteCheck {
+    pub endpoints: Vec<EndpointCheck>,
+}
+
+impl RemoteCheck {
+    /// Whether every endpoint passed and all of them belong to the same
+    /// cluster.
+    pub fn is_ok(&self) -> bool {
+        self.endpoints
+            .iter()
+            .all(|endpoint| endpoint.status.is_ok())
+            && self.genesis_hashes().len() <= 1
+    }
+
+    /// The distinct genesis hashes reported by the HTTP endpoints.
+    fn genesis_hashes(&self) -> BTreeSet<&str> {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| match &endpoint.status {
+                Ok(EndpointStatus::Http { genesis_hash, .. }) => Some(genesis_hash.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Display for EndpointCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let latency = self.latency.as_millis();
+        match &self.status {
+            Ok(EndpointStatus::Http {
+                version,
+                genesis_hash,
+            }) => write!(
+                f,
+                "ok    {} ({latency}ms): version {version}, genesis hash {genesis_hash}",
+                self.url
+            ),
+            Ok(EndpointStatus::WebSocket) => {
+                write!(f, "ok    {} ({latency}ms): handshake completed", self.url)
+            }
+            Ok(EndpointStatus::Skipped(reason)) => write!(f, "skip  {}: {reason}", self.url),
+            Err(error) => write!(f, "error {}: {error}", self.url),
+        }
+    }
+}
+
+impl Display for RemoteCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        for endpoint in &self.endpoints {
+            write!(f, "{separator}{endpoint}")?;
+            separator = "\n";
+        }
+        let hashes = self.genesis_hashes();
+        if hashes.len() > 1 {
+            let hashes: Vec<_> = hashes.into_iter().collect();
+            write!(
+                f,
+                "{separator}error: the endpoints belong to different clusters, with genesis hashes {}",
+                hashes.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl MagicBlockParams {
+    /// Checks every endpoint of the configured `remote`: HTTP endpoints must
+    /// answer `getVersion`, `getHealth` and `getGenesisHash`, with the
+    /// `expected-genesis` hash if one is pinned; WebSocket endpoints must
+    /// complete the handshake. Each endpoint is given `timeout` to respond,
+    /// and is connected to through its proxy, see
+    /// [`MagicBlockParams::proxy_for`]. Endpoints behind an `https` proxy
+    /// are skipped.
+    pub fn check_remote(&self, timeout: Duration) -> RemoteCheck {
+        let endpoints = endpoints(&self.remote)
+            .into_iter()
+            .flat_map(|(http, ws, remote)| {
+                let proxy = self.proxy_for(remote);
+                let agent = match agent(proxy, timeout) {
+                    Ok(agent) => agent,
+                    Err(reason) => return [skipped(http, &reason), skipped(ws, &reason)],
+                };
+                [
+                    check_http(&agent, http, remote),
+                    check_websocket(ws, remote.auth(), proxy, timeout),
+                ]
+            })
+            .collect();
+        RemoteCheck { endpoints }
+    }
+}
+
+/// An agent for requests to a remote, connecting through `proxy` if set.
+/// Fails with the reason if the agent can't connect through `proxy`, as
+/// for `https` proxies.
+pub(crate) fn agent(proxy: Option<&Url>, timeout: Duration) -> Result<ureq::Agent, String> {
+    let agent = ureq::AgentBuilder::new().timeout(timeout);
+    let Some(proxy) = proxy else {
+        return Ok(agent.build());
+    };
+    let scheme = match proxy.scheme() {
+        "http" => "http",
+        // The agent has the proxy resolve the host either way.
+        "socks5" | "socks5h" => "socks5",
+        scheme => return Err(format!("not checked through `{scheme}` proxies")),
+    };
+    let host = proxy.host_str().ok_or("the proxy URL has no host")?;
+    let port = proxy.port_or_known_default().unwrap_or(SOCKS_PORT);
+    let credentials = match proxy.password() {
+        Some(password) => format!("{}:{password}@", proxy.username()),
+        None if !proxy.username().is_empty() => format!("{}@", proxy.username()),
+        None => String::new(),
+    };
+    let proxy = ureq::Proxy::new(format!("{scheme}://{credentials}{host}:{port}"))
+        .map_err(|e| format!("invalid proxy: {}", e.kind()))?;
+    Ok(agent.proxy(proxy).build())
+}
+
+/// The check of an endpoint that was skipped for `reason`.
+fn skipped(url: Url, reason: &str) -> EndpointCheck {
+    EndpointCheck {
+        url,
+        latency: Duration::ZERO,
+        status: Ok(EndpointStatus::Skipped(reason.to_owned())),
+    }
+}
+
+/// The HTTP and WebSocket URLs of every remote in `cluster`, along with the
+/// remote itself.
+pub(crate) fn endpoints(cluster: &RemoteCluster) -> Vec<(Url, Url, &Remote)> {
+    cluster
+        .remotes()
+        .iter()
+        // gRPC remotes speak neither JSON-RPC nor WebSockets.
+        .filter_map(|remote| Some((remote.http_url()?, remote.ws_url()?, remote)))
+        .collect()
+}
+
+fn check_http(agent: &ureq::Agent, url: Url, remote: &Remote) -> EndpointCheck {
+    let auth = remote.auth();
+    let start = Instant::now();
+    let version = call::<Version>(agent, &url, auth, "getVersion");
+    let latency = start.elapsed();
+    let status = version.and_then(|version| {
+        call::<String>(agent, &url, auth, "getHealth")?;
+        let genesis_hash: String = call(agent, &url, auth, "getGenesisHash")?;
+        remote
+            .verify_genesis(&genesis_hash)
+            .map_err(|e| e.to_string())?;
+        Ok(EndpointStatus::Http {
+            version: version.solana_core,
+            genesis_hash,
+        })
+    });
+    EndpointCheck {
+        url,
+        latency,
+        status,
+    }
+}
+
+fn check_websocket(
+    url: Url,
+    auth: &RemoteAuth,
+    proxy: Option<&Url>,
+    timeout: Duration,
+) -> EndpointCheck {
+    let start = Instant::now();
+    let status = handshake(&url, auth, proxy, timeout).map(|()| EndpointStatus::WebSocket);
+    EndpointCheck {
+        url,
+        latency: start.elapsed(),
+        status,
+    }
+}
+
+/// Connects to `url`, through `proxy` if set, and completes the WebSocket
+/// handshake, then closes the connection.
+fn handshake(
+    url: &Url,
+    auth: &RemoteAuth,
+    proxy: Option<&Url>,
+    timeout: Duration,
+) -> Result<(), String> {
+    let stream = match proxy {
+        None => connect(url, timeout)?,
+        Some(proxy) if proxy.scheme() == "http" => http_tunnel(proxy, url, timeout)?,
+        Some(proxy) => socks5_tunnel(proxy, url, timeout)?,
+    };
+    let mut request = auth
+        .url(url)
+        .as_str()
+        .into_client_request()
+        .map_err(|e| format!("handshake failed: {e}"))?;
+    for (name, value) in auth.headers() {
+        let name = HeaderName::try_from(name).map_err(|e| format!("invalid header: {e}"))?;
+        let value = HeaderValue::try_from(value.as_str())
+            .map_err(|_| format!("invalid `{name}` header"))?;
+        request.headers_mut().insert(name, value);
+    }
+    let (mut socket, _) =
+        tungstenite::client_tls(request, stream).map_err(|e| format!("handshake failed: {e}"))?;
+    let _ = socket.close(None);
+    Ok(())
+}
+
+/// Connects to the host of `url`, with `timeout` for connecting and for
+/// every read and write.
+fn connect(url: &Url, timeout: Duration) -> Result<TcpStream, String> {
+    let address = url
+        .socket_addrs(|| Some(SOCKS_PORT))
+        .map_err(|e| format!("cannot resolve the host: {e}"))?
+        .into_iter()
+        .next()
+        .ok_or("cannot resolve the host")?;
+    let stream = TcpStream::connect_timeout(&address, timeout)
+        .map_err(|e| format!("cannot connect: {e}"))?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|()| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| format!("cannot connect: {e}"))?;
+    Ok(stream)
+}
+
+/// Opens a tunnel to the host of `url` through the HTTP `proxy`, with
+/// `CONNECT`.
+fn http_tunnel(proxy: &Url, url: &Url, timeout: Duration) -> Result<TcpStream, String> {
+    let failed = |e: io::Error| format!("proxy failed: {e}");
+    let mut stream = connect(proxy, timeout).map_err(|e| format!("proxy failed: {e}"))?;
+    let host = url.host_str().ok_or("the URL has no host")?;
+    let port = url.port_or_known_default().ok_or("the URL has no port")?;
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if !proxy.username().is_empty() {
+        let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or(""));
+        let credentials = BASE64_STANDARD.encode(credentials);
+        request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).map_err(failed)?;
+
+    // Read byte by byte, so that nothing past the head is consumed.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > MAX_PROXY_RESPONSE {
+            return Err("proxy failed: response too large".into());
+        }
+        let mut byte = [0];
+        stream.read_exact(&mut byte).map_err(failed)?;
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or_default();
+    match status_line.split(' ').nth(1) {
+        Some("200") => Ok(stream),
+        _ => Err(format!("proxy refused the tunnel: {status_line}")),
+    }
+}
+
+/// Opens a connection to the host of `url` through the SOCKS5 `proxy`. With
+/// `socks5h`, the proxy resolves the host, and with `socks5` it is resolved
+/// here.
+fn socks5_tunnel(proxy: &Url, url: &Url, timeout: Duration) -> Result<TcpStream, String> {
+    let failed = |e: io::Error| format!("proxy failed: {e}");
+    let mut stream = connect(proxy, timeout).map_err(|e| format!("proxy failed: {e}"))?;
+    let credentials =
+        (!proxy.username().is_empty()).then(|| (proxy.username(), proxy.password().unwrap_or("")));
+    // No authentication, or username and password.
+    let method = if credentials.is_some() { 2 } else { 0 };
+    stream.write_all(&[5, 1, method]).map_err(failed)?;
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).map_err(failed)?;
+    if reply != [5, method] {
+        return Err("proxy refused the authentication method".into());
+    }
+    if let Some((username, password)) = credentials {
+        let mut request = vec![1];
+        for field in [username, password] {
+            let len = u8::try_from(field.len()).map_err(|_| "proxy credentials are too long")?;
+            request.push(len);
+            request.extend_from_slice(field.as_bytes());
+        }
+        stream.write_all(&request).map_err(failed)?;
+        stream.read_exact(&mut reply).map_err(failed)?;
+        if reply[1] != 0 {
+            return Err("proxy rejected the credentials".into());
+        }
+    }
+
+    let port = url.port_or_known_default().ok_or("the URL has no port")?;
+    let ip = match url.host() {
+        Some(Host::Ipv4(ip)) => Some(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => Some(IpAddr::V6(ip)),
+        Some(Host::Domain(_)) if proxy.scheme() == "socks5h" => None,
+        Some(Host::Domain(_)) => Some(
+            url.socket_addrs(|| None)
+                .map_err(|e| format!("cannot resolve the host: {e}"))?
+                .first()
+                .ok_or("cannot resolve the host")?
+                .ip(),
+        ),
+        None => return Err("the URL has no host".into()),
+    };
+    // Connect, to an IPv4 address, a domain name or an IPv6 address.
+    let mut request = vec![5, 1, 0];
+    match (ip, url.host_str()) {
+        (Some(IpAddr::V4(ip)), _) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        (Some(IpAddr::V6(ip)), _) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        (None, Some(domain)) => {
+            let len = u8::try_from(domain.len()).map_err(|_| "the host name is too long")?;
+            request.extend_from_slice(&[3, len]);
+            request.extend_from_slice(domain.as_bytes());
+        }
+        (None, None) => return Err("the URL has no host".into()),
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).map_err(failed)?;
+
+    let mut head = [0; 4];
+    stream.read_exact(&mut head).map_err(failed)?;
+    if head[1] != 0 {
+        return Err(format!("proxy cannot connect, with reply code {}", head[1]));
+    }
+    // Skips the address the proxy connected from, and its port.
+    let len = match head[3] {
+        1 => 4,
+        4 => 16,
+        3 => {
+            let mut len = [0];
+            stream.read_exact(&mut len).map_err(failed)?;
+            usize::from(len[0])
+        }
+        _ => return Err("proxy sent an invalid reply".into()),
+    };
+    let mut address = vec![0; len + 2];
+    stream.read_exact(&mut address).map_err(failed)?;
+    Ok(stream)
+}
+
+/// A JSON-RPC response, carrying either a result or an error.
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+/// The result of `getVersion`.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Version {
+    solana_core: String,
+}
+
+/// The balance, in lamports, of `pubkey` according to the node at `url`.
+pub(crate) fn balance(
+    agent: &ureq::Agent,
+    url: &Url,
+    auth: &RemoteAuth,
+    pubkey: &Pubkey,
+) -> Result<u64, String> {
+    #[derive(Deserialize)]
+    struct Balance {
+        value: u64,
+    }
+
+    let params = format!(r#"["{pubkey}"]"#);
+    call_with::<Balance>(agent, url, auth, "getBalance", &params).map(|balance| balance.value)
+}
+
+/// The clock of the node at `url`, from the `Date` header of its answer to
+/// `getHealth`. Accurate to a second.
+pub(crate) fn server_time(
+    agent: &ureq::Agent,
+    url: &Url,
+    auth: &RemoteAuth,
+) -> Result<SystemTime, String> {
+    let response = send(agent, url, auth, "getHealth", "[]")?;
+    let date = response
+        .header("Date")
+        .ok_or("getHealth response has no `Date` header")?;
+    httpdate::parse_http_date(date).map_err(|e| format!("invalid `Date` header {date:?}: {e}"))
+}
+
+/// Calls the parameterless JSON-RPC `method` on `url`.
+fn call<T: DeserializeOwned>(
+    agent: &ureq::Agent,
+    url: &Url,
+    auth: &RemoteAuth,
+    method: &str,
+) -> Result<T, String> {
+    call_with(agent, url, auth, method, "[]")
+}
+
+/// Calls the JSON-RPC `method` on `url` with `params`, a JSON array.
+fn call_with<T: DeserializeOwned>(
+    agent: &ureq::Agent,
+    url: &Url,
+    auth: &RemoteAuth,
+    method: &str,
+    params: &str,
+) -> Result<T, String> {
+    let response: RpcResponse<T> = send(agent, url, auth, method, params)?
+        .into_json()
+        .map_err(|e| format!("{method} returned an invalid response: {e}"))?;
+    match response {
+        RpcResponse {
+            result: Some(result),
+            ..
+        } => Ok(result),
+        RpcResponse {
+            error: Some(error), ..
+        } => Err(format!("{method} failed: {}", error.message)),
+        _ => Err(format!("{method} returned no result")),
+    }
+}
+
+/// Sends a JSON-RPC request for `method` with `params` to `url`, with the
+/// credentials in `auth`.
+fn send(
+    agent: &ureq::Agent,
+    url: &Url,
+    auth: &RemoteAuth,
+    method: &str,
+    params: &str,
+) -> Result<ureq::Response, String> {
+    let body = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":{params}}}"#);
+    let request = agent
+        .post(auth.url(url).as_str())
+        .set("Content-Type", "application/json");
+    auth.headers()
+        .fold(request, |request, (name, value)| request.set(name, &value))
+        .send_string(&body)
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("{method} failed with HTTP status {code}"),
+            // Leaves out the URL, which is already part of the report.
+            ureq::Error::Transport(e) => match e.source() {
+                Some(cause) => format!("{method} failed: {cause}"),
+                None => format!("{method} failed: {}", e.kind()),
+            },
+        })
+}
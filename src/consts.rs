@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 // CLI Default Values
 pub const DEFAULT_REMOTE: &str = "devnet";
 pub const DEFAULT_LIFECYCLE: &str = "programs-replica";
@@ -9,12 +11,164 @@ pub const DEFAULT_VALIDATOR_KEYPAIR: &str =
 pub const DEFAULT_BASE_FEE: u64 = 100;
 pub const DEFAULT_BASE_FEE_STR: &str = "100";
 pub const DEFAULT_COMPUTE_UNIT_PRICE: u64 = 1_000_000;
+pub const DEFAULT_COMPUTE_UNIT_PRICE_STR: &str = "1000000";
+pub const DEFAULT_BLOCKS_PER_PARTITION: usize = 1024 * 1024;
+pub const DEFAULT_BLOCKS_PER_PARTITION_STR: &str = "1048576";
+pub const DEFAULT_BLOCK_TIME: Duration = Duration::from_millis(400);
+pub const DEFAULT_BLOCK_TIME_STR: &str = "400ms";
+pub const DEFAULT_DATABASE_SIZE: u64 = 100 * 1024 * 1024;
+pub const DEFAULT_INDEX_SIZE: u64 = 1024 * 1024;
+
+// Allowed Ranges
+/// Lamports in one SOL.
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+/// 1 SOL, in lamports.
+pub const MAX_BASE_FEE: u64 = LAMPORTS_PER_SOL;
+/// 100 lamports per compute unit, in micro-lamports.
+pub const MAX_COMPUTE_UNIT_PRICE: u64 = 100_000_000;
+pub const MIN_DATABASE_SIZE: u64 = 1024 * 1024;
+pub const MAX_DATABASE_SIZE: u64 = 1024 * 1024 * 1024 * 1024;
+pub const MIN_INDEX_SIZE: u64 = 1024;
+pub const MAX_INDEX_SIZE: u64 = 64 * 1024 * 1024 * 1024;
 
 // Remote URL Aliases
 pub const MAINNET_URL: &str = "https://api.mainnet-beta.solana.com";
 pub const DEVNET_URL: &str = "https://api.devnet.solana.com";
 pub const TESTNET_URL: &str = "https://api.testnet.solana.com";
 pub const LOCALHOST_URL: &str = "http://127.0.0.1:8899";
+pub const BUILTIN_REMOTE_ALIASES: &[&str] = &["mainnet", "devnet", "testnet", "localhost", "dev"];
+/// Scheme of a URL standing for a `[remote-aliases]` name until it is
+/// resolved.
+pub const REMOTE_ALIAS_SCHEME: &str = "alias";
+
+// Remote Failover
+/// Consecutive failed requests after which the primary remote is failed over.
+pub const DEFAULT_FAILOVER_ERROR_THRESHOLD: u32 = 3;
+pub const DEFAULT_FAILOVER_COOLDOWN: Duration = Duration::from_secs(30);
+
+// Connection Options
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_KEEPALIVE: Duration = Duration::from_secs(60);
+pub const DEFAULT_POOL_SIZE: u32 = 16;
+pub const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+// Remote Health Checks
+pub const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Consecutive failed health checks after which a remote is unhealthy.
+pub const DEFAULT_UNHEALTHY_THRESHOLD: u32 = 3;
+
+// Metrics
+/// Path the metrics are served at unless `metrics.path` is set.
+pub const DEFAULT_METRICS_PATH: &str = "/metrics";
+pub const DEFAULT_PUSH_GATEWAY_INTERVAL: Duration = Duration::from_secs(15);
+
+// Tracing
+/// Share of traces sampled unless `tracing.sampling-ratio` is set: all of them.
+pub const DEFAULT_TRACING_SAMPLING_RATIO: f64 = 1.0;
+
+// Default Config File Search Path
+pub const LOCAL_CONFIG_FILE: &str = "magicblock.toml";
+/// Relative to `$XDG_CONFIG_HOME` (or `~/.config`).
+pub const USER_CONFIG_FILE: &str = "magicblock/config.toml";
+pub const SYSTEM_CONFIG_FILE: &str = "/etc/magicblock/config.toml";
+/// A `--config` value reading the configuration document from stdin.
+pub const STDIN_CONFIG: &str = "-";
+pub const DEFAULT_CONFIG_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_CONFIG_FETCH_TIMEOUT_STR: &str = "10s";
+/// How long `check-remote` waits for each remote endpoint.
+pub const DEFAULT_REMOTE_CHECK_TIMEOUT_STR: &str = "5s";
+
+// Configuration Format Versions
+/// The latest `config-version` this crate understands.
+pub const CONFIG_VERSION: u32 = 2;
+pub const CONFIG_VERSION_KEY: &str = "config-version";
+/// What changed in each `config-version`, used to guide upgrades.
+pub const CONFIG_VERSION_CHANGES: &[(u32, &str)] = &[
+    (1, "initial layered configuration format"),
+    (2, "`validator.base-fee` renamed to `validator.basefee`"),
+];
+
+// Secrets
+/// Keys holding secrets, which are never shown in diffs. `*` stands for any
+/// one key or list index, e.g. a header name.
+pub const SECRET_KEYS: &[&str] = &[
+    "validator.keypair",
+    "validator.signer.token",
+    "validator.commit-fee-payer.keypair",
+    "validator.commit-fee-payer.signer.token",
+    "validator.fee-claim-authority.keypair",
+    "validator.fee-claim-authority.signer.token",
+    "admin.token",
+    "tracing.headers.*",
+];
+/// Keys a remote is set at, as in [`SECRET_KEYS`]: alone, in a list, as
+/// the `default` of a cluster with roles, by name or by lifecycle.
+pub const REMOTE_KEYS: &[&str] = &[
+    "remote",
+    "remote.*",
+    "remote.default",
+    "remote.default.*",
+    "remotes.*",
+    "lifecycle-defaults.*",
+    "lifecycle-defaults.*.*",
+    "lifecycle-defaults.*.default",
+    "lifecycle-defaults.*.default.*",
+];
+/// Keys of a remote holding its credentials.
+pub const REMOTE_SECRET_KEYS: &[&str] = &["token", "api-key", "x-token", "headers.*"];
+/// Keys holding URLs whose password, if they have one, is a secret, as in
+/// [`SECRET_KEYS`]. The rest of the URL is shown.
+pub const CREDENTIAL_URL_KEYS: &[&str] = &["network.proxy.url"];
+/// Keys of a remote holding a URL with a password, as in
+/// [`CREDENTIAL_URL_KEYS`].
+pub const REMOTE_CREDENTIAL_URL_KEYS: &[&str] = &["proxy"];
+/// Placeholder shown in place of a secret.
+pub const REDACTED: &str = "***REDACTED***";
+/// Values that turn off an optional endpoint, such as `metrics`.
+pub const DISABLED_ENDPOINT: &[&str] = &["off", "disabled", "none"];
 
 // Figment Configuration
 pub const ENV_VAR_PREFIX: &str = "MBV_";
+/// Top-level table holding the per-profile overrides of a configuration file.
+pub const PROFILES_KEY: &str = "profile";
+/// Top-level key listing the files a configuration file includes.
+pub const INCLUDE_KEY: &str = "include";
+
+/// Keys that were renamed, as `(deprecated, replacement)` pairs. Deprecated
+/// keys still load, but emit a [`crate::warning::ConfigWarning`].
+pub const DEPRECATED_KEYS: &[(&str, &str)] = &[
+    ("validator.base-fee", "validator.basefee"),
+    ("metrics-tls", "metrics.tls"),
+];
+
+/// Sections that can be set to a single value, standing for one of their
+/// keys, as `(section, key)` pairs, e.g. `metrics = "127.0.0.1:9000"` for
+/// `metrics.listen`.
+pub const SHORTHAND_KEYS: &[(&str, &str)] = &[("metrics", "listen")];
+
+/// Top-level options that only control how the sources are loaded, left out
+/// of printed configurations.
+pub const LOADER_KEYS: &[&str] = &[
+    "config",
+    "config-format",
+    "config-refresh-interval",
+    "env-file",
+];
+
+/// Options that only control how the sources are loaded, whose environment
+/// variables are read by clap only and kept out of the environment layer.
+pub const LOADER_ENV_KEYS: &[&str] = &[
+    "config",
+    "config_format",
+    "config_timeout",
+    "config_refresh_interval",
+    "config_token",
+    "age_identity",
+    "env_file",
+    "profile",
+    "strict_config",
+    "preflight",
+];
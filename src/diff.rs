@@ -0,0 +1,146 @@
+//! Key-by-key comparison of two configurations, for reviewing changes before
+//! rolling them out.
+
+use crate::{
+    error::ConfigError,
+    secrets,
+    sources::{self, FileOptions},
+    MagicBlockParams,
+};
+use figment::{providers::Serialized, Figment};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    path::Path,
+};
+use toml::{Table, Value};
+
+/// A key that differs between two configurations. Values are rendered as
+/// TOML, with secrets redacted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyChange {
+    /// The key is only set in the new configuration.
+    Added { key: String, value: String },
+    /// The key is only set in the old configuration.
+    Removed { key: String, value: String },
+    /// The key is set in both, to different values.
+    Changed {
+        key: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl KeyChange {
+    /// Dotted path of the key.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Added { key, .. } | Self::Removed { key, .. } | Self::Changed { key, .. } => key,
+        }
+    }
+}
+
+impl Display for KeyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { key, value } => write!(f, "+ {key} = {value}"),
+            Self::Removed { key, value } => write!(f, "- {key} = {value}"),
+            Self::Changed { key, old, new } => write!(f, "~ {key}: {old} -> {new}"),
+        }
+    }
+}
+
+/// Every key that differs between two configurations, sorted by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    pub changes: Vec<KeyChange>,
+}
+
+impl ConfigDiff {
+    /// Whether the configurations are identical.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        for change in &self.changes {
+            write!(f, "{separator}{change}")?;
+            separator = "\n";
+        }
+        Ok(())
+    }
+}
+
+impl MagicBlockParams {
+    /// Compares `self`, the old configuration, with `other`, the new one.
+    pub fn diff(&self, other: &Self) -> ConfigDiff {
+        let old = leaf_values(self);
+        let new = leaf_values(other);
+        let mut changes = Vec::new();
+        for (key, old_value) in &old {
+            match new.get(key) {
+                None => changes.push(KeyChange::Removed {
+                    key: key.clone(),
+                    value: render(key, old_value),
+                }),
+                Some(new_value) if new_value != old_value => changes.push(KeyChange::Changed {
+                    key: key.clone(),
+                    old: render(key, old_value),
+                    new: render(key, new_value),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (key, value) in &new {
+            if !old.contains_key(key) {
+                changes.push(KeyChange::Added {
+                    key: key.clone(),
+                    value: render(key, value),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.key().cmp(b.key()));
+        ConfigDiff { changes }
+    }
+
+    /// Loads a single configuration file over the built-in defaults, leaving
+    /// out the command line and the environment.
+    pub fn from_file(path: &Path, options: &FileOptions) -> Result<Self, ConfigError> {
+        let defaults = secrets::exposed(|| Figment::from(Serialized::defaults(Self::default())));
+        let figment = sources::merge_config_file(defaults, path, options)?;
+        Ok(figment.extract()?)
+    }
+}
+
+/// Every value set in `params`, by dotted key. Secrets are included as they
+/// are, to be compared, and only redacted when rendered.
+pub(crate) fn leaf_values(params: &MagicBlockParams) -> BTreeMap<String, Value> {
+    fn collect(table: Table, prefix: &str, out: &mut BTreeMap<String, Value>) {
+        for (key, value) in table {
+            let key = format!("{prefix}{key}");
+            match value {
+                Value::Table(section) => collect(section, &format!("{key}."), out),
+                value => {
+                    out.insert(key, value);
+                }
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    if let Ok(Value::Table(table)) = secrets::exposed(|| Value::try_from(params)) {
+        collect(table, "", &mut out);
+    }
+    out
+}
+
+/// Renders `value`, set at `key`, as TOML, with the secrets in it replaced by
+/// a placeholder.
+fn render(key: &str, value: &Value) -> String {
+    let mut value = value.clone();
+    secrets::redact_toml(key, &mut value);
+    value.to_string()
+}
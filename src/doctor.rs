@@ -0,0 +1,309 @@
+//! Diagnosis of common misconfigurations, combining the configuration checks
+//! with probes of the host and of the remote cluster, so that problems that
+//! would otherwise surface minutes into startup are caught in seconds.
+
+use crate::{report::ConfigReport, validate, MagicBlockParams};
+use std::{
+    fmt::{self, Display},
+    path::Path,
+    time::Duration,
+};
+
+/// Clock skew from the remote cluster above which a warning is given.
+#[cfg(feature = "check-remote")]
+const CLOCK_SKEW_WARNING: Duration = Duration::from_secs(5);
+/// Clock skew from the remote cluster above which the check fails.
+#[cfg(feature = "check-remote")]
+const CLOCK_SKEW_FAILURE: Duration = Duration::from_secs(60);
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Something that may be intended, but often isn't.
+    Warn,
+    /// Something that will keep the validator from working.
+    Fail,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pass => "pass",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+        })
+    }
+}
+
+/// The outcome of one check, e.g. that the `listen` address is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// What was checked, e.g. `ports`.
+    pub check: &'static str,
+    pub status: Status,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(check: &'static str, status: Status, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.status, self.check, self.message)
+    }
+}
+
+/// Every finding of the `doctor` subcommand, see [`ConfigBuilder::doctor`].
+///
+/// [`ConfigBuilder::doctor`]: crate::ConfigBuilder::doctor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub findings: Vec<Finding>,
+}
+
+impl Diagnosis {
+    /// Whether no check failed. Warnings are allowed.
+    pub fn is_ok(&self) -> bool {
+        self.count(Status::Fail) == 0
+    }
+
+    /// The number of findings with `status`.
+    pub fn count(&self, status: Status) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.status == status)
+            .count()
+    }
+}
+
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for finding in &self.findings {
+            writeln!(f, "{finding}")?;
+        }
+        write!(
+            f,
+            "{} passed, {} warnings, {} failed",
+            self.count(Status::Pass),
+            self.count(Status::Warn),
+            self.count(Status::Fail)
+        )
+    }
+}
+
+/// Turns the configuration `report` into findings, then probes the host and
+/// the remote cluster if the configuration could be loaded.
+pub(crate) fn diagnose(
+    params: Option<&MagicBlockParams>,
+    report: &ConfigReport,
+    timeout: Duration,
+) -> Diagnosis {
+    let mut findings: Vec<_> = report
+        .errors
+        .iter()
+        .map(|error| Finding::new("configuration", Status::Fail, error.to_string()))
+        .chain(
+            report
+                .warnings
+                .iter()
+                .map(|warning| Finding::new("configuration", Status::Warn, warning.to_string())),
+        )
+        .collect();
+    if report.is_ok() {
+        findings.insert(0, Finding::new("configuration", Status::Pass, "valid"));
+    }
+    let Some(params) = params else {
+        findings.push(Finding::new(
+            "probes",
+            Status::Warn,
+            "skipped, the configuration could not be loaded",
+        ));
+        return Diagnosis { findings };
+    };
+
+    let storage_failed = report.errors.iter().any(|error| error.key == "storage");
+    if let (Some(storage), false) = (&params.storage, storage_failed) {
+        findings.push(self::storage(storage));
+    }
+    // With `--preflight`, the ports were already checked while loading.
+    if !params.preflight {
+        findings.extend(ports(params));
+    }
+    findings.extend(remote(params, timeout));
+    Diagnosis { findings }
+}
+
+/// Reports the `storage` directory the configuration checks passed for. A
+/// missing one is only created at startup, so the closest existing directory
+/// it would be created in is checked instead.
+fn storage(storage: &Path) -> Finding {
+    if storage.is_dir() {
+        let message = format!("{} is a writable directory", storage.display());
+        return Finding::new("storage", Status::Pass, message);
+    }
+    let Some(parent) = storage.ancestors().skip(1).find(|path| path.exists()) else {
+        let message = format!("{} will be created", storage.display());
+        return Finding::new("storage", Status::Pass, message);
+    };
+    let checked = match parent.is_dir() {
+        true => validate::check_writable_dir(parent),
+        false => Err("not a directory".into()),
+    };
+    match checked {
+        Ok(()) => {
+            let message = format!(
+                "{} will be created in {}, which is writable",
+                storage.display(),
+                parent.display()
+            );
+            Finding::new("storage", Status::Pass, message)
+        }
+        Err(error) => {
+            let message = format!(
+                "{} cannot be created, {}: {error}",
+                storage.display(),
+                parent.display()
+            );
+            Finding::new("storage", Status::Fail, message)
+        }
+    }
+}
+
+fn ports(params: &MagicBlockParams) -> Vec<Finding> {
+    let errors = params.check_ports();
+    if errors.is_empty() {
+        let addresses: Vec<String> = params
+            .listen
+            .iter()
+            .chain(&params.metrics.listen)
+            .map(ToString::to_string)
+            .collect();
+        let message = match addresses.split_last() {
+            Some((last, [])) => format!("{last} is free"),
+            Some((last, rest)) => format!("{} and {last} are free", rest.join(", ")),
+            None => "no addresses to bind".to_owned(),
+        };
+        return vec![Finding::new("ports", Status::Pass, message)];
+    }
+    errors
+        .into_iter()
+        .map(|error| Finding::new("ports", Status::Fail, error.to_string()))
+        .collect()
+}
+
+#[cfg(feature = "check-remote")]
+fn remote(params: &MagicBlockParams, timeout: Duration) -> Vec<Finding> {
+    use crate::{
+        config::KeyRole,
+        connectivity::{self, EndpointStatus},
+        LifecycleMode,
+    };
+    use std::time::SystemTime;
+
+    let check = params.check_remote(timeout);
+    let mut findings: Vec<_> = check
+        .endpoints
+        .iter()
+        .map(|endpoint| {
+            let (url, latency) = (&endpoint.url, endpoint.latency.as_millis());
+            match &endpoint.status {
+                Ok(EndpointStatus::Http { version, .. }) => {
+                    let message = format!("{url} answered in {latency}ms, version {version}");
+                    Finding::new("remote", Status::Pass, message)
+                }
+                Ok(EndpointStatus::WebSocket) => {
+                    let message = format!("{url} completed the handshake in {latency}ms");
+                    Finding::new("remote", Status::Pass, message)
+                }
+                Ok(EndpointStatus::Skipped(reason)) => {
+                    Finding::new("remote", Status::Warn, format!("{url}: {reason}"))
+                }
+                Err(error) => Finding::new("remote", Status::Fail, format!("{url}: {error}")),
+            }
+        })
+        .collect();
+    if !check.is_ok() && check.endpoints.iter().all(|e| e.status.is_ok()) {
+        findings.push(Finding::new(
+            "remote",
+            Status::Fail,
+            "the endpoints belong to different clusters",
+        ));
+    }
+
+    let Some((url, _, remote)) = connectivity::endpoints(&params.remote).into_iter().next() else {
+        return findings;
+    };
+    let auth = remote.auth();
+    let agent = match connectivity::agent(params.proxy_for(remote), timeout) {
+        Ok(agent) => agent,
+        Err(reason) => {
+            for check in ["clock", "keypair"] {
+                findings.push(Finding::new(check, Status::Warn, reason.clone()));
+            }
+            return findings;
+        }
+    };
+    findings.push(match connectivity::server_time(&agent, &url, auth) {
+        Ok(remote) => {
+            let now = SystemTime::now();
+            let skew = now
+                .duration_since(remote)
+                .or_else(|_| remote.duration_since(now))
+                .unwrap_or_default();
+            let status = if skew > CLOCK_SKEW_FAILURE {
+                Status::Fail
+            } else if skew > CLOCK_SKEW_WARNING {
+                Status::Warn
+            } else {
+                Status::Pass
+            };
+            let message = format!("{}s off from {url}", skew.as_secs());
+            Finding::new("clock", status, message)
+        }
+        Err(error) => Finding::new("clock", Status::Warn, format!("not checked, {error}")),
+    });
+
+    // Commits are paid for by the commit fee payer, the identity unless set.
+    let pubkey = params
+        .validator
+        .keypair_for(KeyRole::CommitFeePayer)
+        .pubkey();
+    findings.push(match connectivity::balance(&agent, &url, auth, &pubkey) {
+        Ok(0) => {
+            // Only ephemeral validators pay for transactions on the remote.
+            let status = if params.lifecycle == LifecycleMode::Ephemeral {
+                Status::Fail
+            } else {
+                Status::Warn
+            };
+            Finding::new(
+                "keypair",
+                status,
+                format!("{pubkey} is not funded on {url}"),
+            )
+        }
+        Ok(lamports) => {
+            let message = format!("{pubkey} holds {lamports} lamports on {url}");
+            Finding::new("keypair", Status::Pass, message)
+        }
+        Err(error) => Finding::new("keypair", Status::Warn, format!("not checked, {error}")),
+    });
+    findings
+}
+
+#[cfg(not(feature = "check-remote"))]
+fn remote(_: &MagicBlockParams, _: Duration) -> Vec<Finding> {
+    vec![Finding::new(
+        "remote",
+        Status::Warn,
+        "not checked, built without the `check-remote` feature",
+    )]
+}
@@ -0,0 +1,127 @@
+//! Transparent decryption of SOPS- and age-encrypted configuration files, so
+//! that encrypted configurations can be committed and deployed without a
+//! separate decrypt step. Files are decrypted through the `sops` and `age`
+//! CLIs.
+
+use crate::sources::ConfigFormat;
+use figment::{
+    value::{Dict, Map},
+    Metadata, Profile, Provider,
+};
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// How a configuration file is encrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    /// Values encrypted by SOPS, with the keys left in the clear.
+    Sops,
+    /// The whole file encrypted by age, in binary or armored form.
+    Age,
+}
+
+impl Display for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sops => "SOPS",
+            Self::Age => "age",
+        })
+    }
+}
+
+impl Encryption {
+    /// Detects whether the file at `path`, in `format`, is encrypted: by its
+    /// name, e.g. `config.sops.yaml` or `config.toml.age`, or else by its
+    /// content, i.e. an age header or SOPS metadata.
+    pub fn detect(path: &Path, format: ConfigFormat) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if name.ends_with(".age") {
+            return Some(Self::Age);
+        }
+        if name.contains(".sops.") {
+            return Some(Self::Sops);
+        }
+        let content = std::fs::read(path).ok()?;
+        if content.starts_with(b"age-encryption.org/v1")
+            || content.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----")
+        {
+            return Some(Self::Age);
+        }
+        let content = std::str::from_utf8(&content).ok()?;
+        let has_metadata = content.contains("sops")
+            && format
+                .figment_from_str(content)
+                .find_value("sops.mac")
+                .is_ok();
+        has_metadata.then_some(Self::Sops)
+    }
+
+    /// Decrypts the file at `path` with the age `identity` file, if given.
+    /// SOPS falls back to its own key discovery without one.
+    pub fn decrypt(self, path: &Path, identity: Option<&Path>) -> Result<String, String> {
+        let mut command = match self {
+            Self::Sops => {
+                let mut command = Command::new("sops");
+                if let Some(identity) = identity {
+                    command.env("SOPS_AGE_KEY_FILE", identity);
+                }
+                command.arg("--decrypt");
+                command
+            }
+            Self::Age => {
+                let identity = identity.ok_or_else(|| {
+                    format!(
+                        "cannot decrypt {}: no age identity, set `--age-identity` or MBV_AGE_IDENTITY",
+                        path.display()
+                    )
+                })?;
+                let mut command = Command::new("age");
+                command.arg("--decrypt").arg("--identity").arg(identity);
+                command
+            }
+        };
+        let program = command.get_program().to_string_lossy().into_owned();
+        let output = command
+            .arg(path)
+            .output()
+            .map_err(|e| format!("failed to run {program} to decrypt {}: {e}", path.display()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "{program} failed to decrypt {}: {}",
+                path.display(),
+                stderr.trim()
+            ));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|_| format!("{} decrypts to non UTF-8 content", path.display()))
+    }
+}
+
+/// A decrypted configuration file, attributed to the encrypted file so that
+/// diagnostics name it.
+pub(crate) struct Decrypted {
+    pub path: PathBuf,
+    pub format: ConfigFormat,
+    pub encryption: Encryption,
+    pub content: String,
+}
+
+impl Provider for Decrypted {
+    fn metadata(&self) -> Metadata {
+        let format = match self.format {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Json => "JSON",
+        };
+        let name = format!("{}-encrypted {format} file", self.encryption);
+        Metadata::from(name, figment::Source::File(self.path.clone()))
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        self.format.figment_from_str(&self.content).data()
+    }
+}
@@ -0,0 +1,31 @@
+use crate::report::ConfigReport;
+
+/// An error raised while assembling the configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// Reading or parsing a source failed.
+    #[error(transparent)]
+    Figment(Box<figment::Error>),
+    /// The configuration has one or more invalid or, in strict mode, unknown
+    /// keys. The report lists all of them.
+    #[error("{0}")]
+    Invalid(ConfigReport),
+}
+
+/// An error raised while serializing the configuration, see
+/// [`MagicBlockParams::to_format`](crate::MagicBlockParams::to_format).
+#[derive(Debug, thiserror::Error)]
+pub enum SerializeError {
+    #[error(transparent)]
+    Toml(#[from] toml::ser::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+impl From<figment::Error> for ConfigError {
+    fn from(error: figment::Error) -> Self {
+        Self::Figment(Box::new(error))
+    }
+}
@@ -0,0 +1,72 @@
+//! Explanations of where configuration values come from, answering "which
+//! value actually won" without reading through every layer.
+
+use crate::{consts, secrets, sources, template};
+use figment::{
+    value::{Dict, Value},
+    Figment,
+};
+use std::fmt::{self, Display};
+
+/// The effective value of a key, along with the layer that supplied it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// Dotted path of the key, e.g. `validator.basefee`.
+    pub key: String,
+    /// The effective value, or `None` if the key is left unset. Inline
+    /// secrets read `***REDACTED***`.
+    pub value: Option<Value>,
+    /// The layer that supplied the value, e.g. ``TOML file `config.toml` ``.
+    pub origin: Option<String>,
+    /// The key's description, if it is documented.
+    pub doc: Option<&'static str>,
+}
+
+impl Explanation {
+    /// Explains `key` in the merged `figment`, or returns `None` if no layer
+    /// sets it. Inline secrets are redacted.
+    pub fn of(figment: &Figment, key: &str) -> Option<Self> {
+        let value = secrets::redact(key, figment.find_value(key).ok()?);
+        Some(Self {
+            key: key.to_owned(),
+            value: (!matches!(value, Value::Empty(..))).then_some(value),
+            origin: sources::key_origin(figment, key),
+            doc: template::doc(key),
+        })
+    }
+
+    /// Explains every key set in the merged `figment`, except for the
+    /// options that only control loading and the variables holding a seed
+    /// phrase, in key order.
+    pub fn all(figment: &Figment) -> figment::Result<Vec<Self>> {
+        let merged: Dict = figment.extract()?;
+        let seed_phrases = secrets::seed_phrase_keys(figment);
+        Ok(sources::leaf_keys(&merged)
+            .iter()
+            .filter(|key| {
+                let root = key.split('.').next().unwrap_or_default();
+                !consts::LOADER_KEYS.contains(&root) && !seed_phrases.contains(key)
+            })
+            .filter_map(|key| Self::of(figment, key))
+            .collect())
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => match toml::Value::try_from(value) {
+                Ok(value) => write!(f, "{} = {value}", self.key)?,
+                Err(_) => write!(f, "{} = {value:?}", self.key)?,
+            },
+            None => write!(f, "{} is not set", self.key)?,
+        }
+        if let Some(origin) = &self.origin {
+            write!(f, "\n  set by: {origin}")?;
+        }
+        if let Some(doc) = self.doc {
+            write!(f, "\n  {doc}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+//! Generation of validator identities, written in the Solana CLI's keypair
+//! format so that no `solana-keygen` and base58 conversion is needed.
+
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use std::{io, path::Path};
+
+/// Generates a fresh keypair and writes it to `path` as a JSON array of
+/// bytes, like `solana-keygen new`. On Unix, a new file is only readable by
+/// its owner.
+pub fn generate_keypair_file(path: &Path) -> io::Result<Keypair> {
+    let keypair = Keypair::new();
+    solana_keypair::write_keypair_file(&keypair, path)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(keypair)
+}
+
+/// The public key of `keypair`, its last 32 bytes.
+pub fn pubkey(keypair: &Keypair) -> Pubkey {
+    let bytes: [u8; 32] = keypair.to_bytes()[32..]
+        .try_into()
+        .expect("Keypairs end with a 32 byte public key");
+    Pubkey::from(bytes)
+}
+
+/// A configuration file snippet setting `validator.keypair` to `keypair`.
+pub fn keypair_snippet(keypair: &Keypair) -> String {
+    format!(
+        "[validator]\nkeypair = \"{}\"\n",
+        keypair.to_base58_string()
+    )
+}
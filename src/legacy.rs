@@ -0,0 +1,187 @@
+//! Migration from the pre-layered validator configuration format, whose
+//! sections and key names differ from the current ones.
+
+use crate::{
+    consts, error::ConfigError, report::ConfigReport, secrets, validate::ValidationError,
+    MagicBlockParams,
+};
+use figment::{providers::Serialized, Figment};
+use toml::{Table, Value};
+
+/// Legacy keys with a direct equivalent, as `(legacy, current)`.
+const RENAMED_KEYS: &[(&str, &str)] = &[
+    ("accounts.lifecycle", "lifecycle"),
+    (
+        "accounts.commit.compute_unit_price",
+        "commit.compute-unit-price",
+    ),
+    ("accounts.db.db-size", "accounts-db.database-size"),
+    ("accounts.db.block-size", "accounts-db.block-size"),
+    ("accounts.db.index-map-size", "accounts-db.index-size"),
+    ("accounts.db.max-snapshots", "accounts-db.max-snapshots"),
+    (
+        "accounts.db.snapshot-frequency",
+        "accounts-db.snapshot-frequency",
+    ),
+    (
+        "accounts.max-monitored-accounts",
+        "chainlink.max-monitored-accounts",
+    ),
+    ("validator.base_fees", "validator.basefee"),
+    ("validator.fqdn", "chain-operation.fqdn"),
+    ("validator.country_code", "chain-operation.country-code"),
+    ("ledger.reset", "ledger.reset"),
+];
+
+/// Legacy keys without an equivalent, dropped when migrating.
+const DROPPED_KEYS: &[&str] = &[
+    "accounts.commit.frequency_millis",
+    "validator.sigverify",
+    "ledger.path",
+    "ledger.size",
+];
+
+/// Legacy keys combined into or converted to a current key, handled by
+/// [`migrate_legacy`] itself.
+const CONVERTED_KEYS: &[&str] = &[
+    "accounts.remote",
+    "accounts.remote.url",
+    "accounts.remote.ws-url",
+    "rpc.addr",
+    "rpc.port",
+    "metrics.enabled",
+    "metrics.addr",
+    "metrics.port",
+    "validator.millis_per_slot",
+    "validator.claim_fees_interval_secs",
+];
+
+/// Address the legacy RPC and metrics services bound to when only a port
+/// was given.
+const LEGACY_ADDR: &str = "0.0.0.0";
+/// Port of the legacy RPC service when only an address was given.
+const LEGACY_RPC_PORT: i64 = 8899;
+
+/// Translates a configuration in the legacy TOML format to the current one.
+///
+/// Keys without an equivalent, such as `validator.sigverify`, are dropped.
+/// Keys the legacy format didn't have are reported as errors, so nothing is
+/// lost silently. The result is not validated, as legacy configurations may
+/// lack settings the current lifecycle requirements ask for.
+pub fn migrate_legacy(content: &str) -> Result<MagicBlockParams, ConfigError> {
+    let legacy: Table = content
+        .parse()
+        .map_err(|e| figment::Error::from(format!("invalid legacy configuration: {e}")))?;
+    let mut keys = Vec::new();
+    leaf_keys(&legacy, "", &mut keys);
+
+    let is_known = |key: &str| {
+        DROPPED_KEYS.contains(&key)
+            || CONVERTED_KEYS.contains(&key)
+            || RENAMED_KEYS.iter().any(|(old, _)| *old == key)
+    };
+    let unknown: Vec<_> = keys
+        .iter()
+        .filter(|key| !is_known(key))
+        .map(|key| ValidationError::new(key.as_str(), "unknown legacy key"))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ConfigError::Invalid(ConfigReport {
+            errors: unknown,
+            warnings: Vec::new(),
+        }));
+    }
+
+    let mut migrated = Vec::new();
+    let mut set = |key: &str, value: Value| migrated.push((key.to_owned(), value));
+    for (old, new) in RENAMED_KEYS {
+        if let Some(value) = find(&legacy, old) {
+            set(new, value.clone());
+        }
+    }
+    match find(&legacy, "accounts.remote") {
+        Some(Value::Table(remote)) => match (remote.get("url"), remote.get("ws-url")) {
+            (Some(http), Some(ws)) => set(
+                "remote",
+                Value::Table(Table::from_iter([
+                    ("http".to_owned(), http.clone()),
+                    ("ws".to_owned(), ws.clone()),
+                ])),
+            ),
+            (Some(url), None) => set("remote", url.clone()),
+            (None, Some(_)) => {
+                let message = "set without `accounts.remote.url`, which the remote needs as well";
+                return Err(ConfigError::Invalid(ConfigReport {
+                    errors: vec![ValidationError::new("accounts.remote.ws-url", message)],
+                    warnings: Vec::new(),
+                }));
+            }
+            (None, None) => {}
+        },
+        Some(remote) => set("remote", remote.clone()),
+        None => {}
+    }
+    if let Some(listen) = address(&legacy, "rpc", Some(LEGACY_RPC_PORT)) {
+        set("listen", listen);
+    }
+    let metrics_enabled = find(&legacy, "metrics.enabled").and_then(Value::as_bool);
+    if metrics_enabled != Some(false) {
+        if let Some(metrics) = address(&legacy, "metrics", None) {
+            set("metrics.listen", metrics);
+        }
+    }
+    if let Some(millis) = find(&legacy, "validator.millis_per_slot") {
+        set("ledger.block-time", Value::String(format!("{millis}ms")));
+    }
+    if let Some(secs) = find(&legacy, "validator.claim_fees_interval_secs") {
+        let frequency = Value::String(format!("{secs}s"));
+        set("chain-operation.claim-fees-frequency", frequency);
+    }
+
+    let defaults =
+        secrets::exposed(|| Figment::from(Serialized::defaults(MagicBlockParams::default())));
+    let migrated = migrated
+        .into_iter()
+        .fold(defaults, |figment, (key, value)| {
+            figment.merge(Serialized::default(&key, value))
+        });
+    let mut params: MagicBlockParams = migrated.extract()?;
+    params.config_version = Some(consts::CONFIG_VERSION);
+    Ok(params)
+}
+
+/// The `addr:port` of a legacy `section`, if either is set.
+fn address(legacy: &Table, section: &str, default_port: Option<i64>) -> Option<Value> {
+    let addr = find(legacy, &format!("{section}.addr")).and_then(Value::as_str);
+    let port = find(legacy, &format!("{section}.port")).and_then(Value::as_integer);
+    if addr.is_none() && port.is_none() {
+        return None;
+    }
+    let port = port.or(default_port)?;
+    let addr = addr.unwrap_or(LEGACY_ADDR);
+    Some(Value::String(format!("{addr}:{port}")))
+}
+
+/// Looks up the dotted `key` in `table`.
+fn find<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (section, rest) = match key.split_once('.') {
+        Some((section, rest)) => (section, Some(rest)),
+        None => (key, None),
+    };
+    let value = table.get(section)?;
+    match rest {
+        Some(rest) => find(value.as_table()?, rest),
+        None => Some(value),
+    }
+}
+
+/// Collects the dotted paths of every value in `table`.
+fn leaf_keys(table: &Table, prefix: &str, out: &mut Vec<String>) {
+    for (key, value) in table {
+        let key = format!("{prefix}{key}");
+        match value {
+            Value::Table(section) => leaf_keys(section, &format!("{key}."), out),
+            _ => out.push(key),
+        }
+    }
+}
@@ -3,26 +3,75 @@
 //! This library uses `figment`, `serde`, and `clap` to assemble a configuration
 //! from multiple sources with a clear order of precedence.
 
-use clap::{Parser, ValueEnum};
-use figment::{
-    providers::{Env, Format, Serialized, Toml},
-    Figment, Profile,
-};
+// Loading errors wrap `figment::Error`s, which are large but only ever
+// produced once, at startup.
+#![allow(clippy::result_large_err)]
+
+use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
+use figment::Provider;
 use serde::{Deserialize, Serialize};
-use std::{ffi::OsString, path::PathBuf};
+use serde_with::{formats::PreferOne, serde_as, OneOrMany};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+#[cfg(feature = "admin")]
+pub mod admin;
+pub mod aliases;
+pub mod builder;
 pub mod config;
+#[cfg(feature = "check-remote")]
+pub mod connectivity;
 pub mod consts;
+pub mod diff;
+pub mod doctor;
+pub mod encrypted;
+pub mod error;
+pub mod explain;
+pub mod keygen;
+pub mod legacy;
+pub mod preflight;
+pub mod provenance;
+pub mod reload;
 pub mod remote;
+pub mod report;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod secrets;
+pub mod sources;
+pub mod strict;
+pub mod template;
 pub mod types;
+pub mod validate;
+pub mod version;
+pub mod warning;
+
+pub use crate::{
+    builder::ConfigBuilder,
+    diff::ConfigDiff,
+    error::{ConfigError, SerializeError},
+    explain::Explanation,
+    reload::ConfigHandle,
+    report::ConfigReport,
+    validate::Validate,
+    warning::ConfigWarning,
+};
+
+use url::Url;
 
 use crate::{
     config::{
-        AccountsDbConfig, ChainLinkConfig, ChainOperationConfig, CommitStrategy, LedgerConfig,
-        ValidatorConfig,
+        AccountsDbConfig, AdminConfig, ChainLinkConfig, ChainOperationConfig, CommitStrategy,
+        LedgerConfig, LogConfig, MetricsConfig, NetworkConfig, TracingConfig, ValidatorConfig,
     },
-    remote::RemoteCluster,
-    types::BindAddress,
+    provenance::Source,
+    remote::{FailoverConfig, Remote, RemoteCluster, RemoteProxy, RemotePurpose},
+    sources::{ConfigFormat, KeyOverride},
+    types::{BindAddress, ExpandedPath, TlsIdentity},
 };
 
 //==============================================================================
@@ -30,16 +79,117 @@ use crate::{
 //==============================================================================
 
 /// Top-level configuration, assembled from multiple sources.
-#[derive(Parser, Deserialize, Serialize, Debug, Default)]
+#[serde_as]
+#[derive(Parser, Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(default, rename_all = "kebab-case")]
 #[command(author, version, about)]
 pub struct MagicBlockParams {
-    /// Path to the TOML configuration file.
+    /// Paths or HTTP(S) URLs of configuration files (TOML, YAML or JSON), merged
+    /// in order so that later files override earlier ones. Can be repeated, or
+    /// colon-separated in `MBV_CONFIG`, and `~` and environment variables are
+    /// expanded. If omitted, the first file found on the default search path
+    /// is used.
     #[arg(long, short, global = true, env = "MBV_CONFIG")]
-    pub config: Option<PathBuf>,
+    pub config: Vec<ExpandedPath>,
+
+    /// Format of the configuration files. Inferred from each file's extension if not set.
+    #[arg(long, value_enum, global = true, env = "MBV_CONFIG_FORMAT")]
+    pub config_format: Option<ConfigFormat>,
+
+    /// Timeout for fetching configuration files from HTTP(S) URLs.
+    #[arg(
+        long,
+        global = true,
+        env = "MBV_CONFIG_TIMEOUT",
+        default_value = consts::DEFAULT_CONFIG_FETCH_TIMEOUT_STR,
+        value_parser = humantime::re::humantime::parse_duration,
+    )]
+    #[serde(skip)]
+    pub config_timeout: Duration,
+
+    /// Re-fetch the configuration sources this often and apply the changes,
+    /// see `ConfigHandle::refresh_periodically`. Disabled if not set.
+    #[arg(
+        long,
+        global = true,
+        env = "MBV_CONFIG_REFRESH_INTERVAL",
+        value_parser = humantime::re::humantime::parse_duration,
+    )]
+    #[serde(with = "humantime")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub config_refresh_interval: Option<Duration>,
+
+    /// Bearer token used to authenticate when fetching configuration files.
+    #[arg(long, global = true, env = "MBV_CONFIG_TOKEN", hide_env_values = true)]
+    #[serde(skip)]
+    pub config_token: Option<String>,
+
+    /// age identity file used to decrypt SOPS- and age-encrypted
+    /// configuration files.
+    #[arg(long, global = true, env = "MBV_AGE_IDENTITY")]
+    #[serde(skip)]
+    pub age_identity: Option<PathBuf>,
+
+    /// Active configuration profile. Each file's `[profile.<name>]` table for
+    /// this profile is applied on top of the rest of that file.
+    #[arg(long, global = true, env = "MBV_PROFILE")]
+    #[serde(skip)]
+    pub profile: Option<String>,
+
+    /// Path to a `.env` file whose `MBV_*` entries are layered just below the
+    /// process environment. None is read unless given.
+    #[arg(long, global = true, env = "MBV_ENV_FILE")]
+    pub env_file: Option<PathBuf>,
+
+    /// Override any configuration key, including file-only ones, e.g.
+    /// `--set ledger.block-time=50ms`. Can be repeated. Applied
+    /// above the configuration files, below the environment.
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    #[serde(skip)]
+    pub overrides: Vec<KeyOverride>,
+
+    /// Reject configuration keys that don't map to any field, instead of
+    /// silently ignoring them.
+    #[arg(long, global = true, env = "MBV_STRICT_CONFIG")]
+    #[serde(skip)]
+    pub strict_config: bool,
+
+    /// Check that the `listen` and `metrics` addresses can be bound before
+    /// anything starts, failing fast if they are already in use.
+    #[arg(long, global = true, env = "MBV_PREFLIGHT")]
+    #[serde(skip)]
+    pub preflight: bool,
 
-    /// Remote Solana cluster URL or a predefined alias (e.g., "mainnet").
-    #[arg(long, short, default_value = consts::DEFAULT_REMOTE, env = "MBV_REMOTE")]
+    /// Ignore `MBV_*` environment variables, including those backing the
+    /// options above and the `--env-file`, e.g. to rule out a stray variable
+    /// on the host.
+    #[arg(long, global = true)]
+    #[serde(skip)]
+    pub no_env: bool,
+
+    /// Log more: each `-v` raises `log.level` by one step, e.g. `-vv` from
+    /// `info` to `trace`.
+    #[arg(short, long, action = ArgAction::Count, global = true, conflicts_with = "quiet")]
+    #[serde(skip)]
+    pub verbose: u8,
+
+    /// Log less: each `-q` lowers `log.level` by one step, e.g. `-qq` from
+    /// `info` to `error`.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    #[serde(skip)]
+    pub quiet: u8,
+
+    /// Version of the configuration format the sources were written for. Older
+    /// versions load with a warning, newer ones are rejected.
+    #[clap(skip)]
+    pub config_version: Option<u32>,
+
+    /// Remote Solana cluster URL, a predefined alias (e.g., "mainnet") or one
+    /// of `remote-aliases`. Several can be given separated by commas, e.g.
+    /// `mainnet,https://backup.example.com`, or by repeating the flag; earlier
+    /// ones take priority.
+    #[arg(long, short, action = ArgAction::Append, default_value = consts::DEFAULT_REMOTE, env = "MBV_REMOTE")]
     pub remote: RemoteCluster,
 
     /// The application's operational mode.
@@ -47,50 +197,582 @@ pub struct MagicBlockParams {
     pub lifecycle: LifecycleMode,
 
     /// Root directory for application storage (e.g., accounts, ledger).
+    /// `~` and environment variables are expanded, and a relative path set
+    /// in a configuration file is relative to that file's directory.
     #[arg(long, env = "MBV_STORAGE")]
-    pub storage: Option<PathBuf>,
+    pub storage: Option<ExpandedPath>,
+
+    /// Create the `storage` directory, including its parents, if it doesn't exist yet.
+    #[arg(long, env = "MBV_CREATE_STORAGE_DIRS")]
+    pub create_storage_dirs: bool,
+
+    /// Minimum free space, in bytes, required on the volume holding `storage`.
+    #[arg(long, env = "MBV_STORAGE_MIN_FREE_SPACE")]
+    pub storage_min_free_space: Option<u64>,
+
+    /// Bind addresses given as a port alone, e.g. `--listen :9000`, to every
+    /// interface (`0.0.0.0`) instead of the loopback one (`127.0.0.1`).
+    #[arg(long, env = "MBV_BIND_ALL")]
+    pub bind_all: bool,
 
-    /// Primary listen address for the main RPC service.
+    /// Listen addresses for the main RPC service. Can be repeated, e.g. to
+    /// bind both IPv4 and IPv6. A port alone, e.g. `:9000`, binds the default
+    /// interface, see `bind-all`, and `unix:/run/magicblock/rpc.sock` a Unix
+    /// domain socket.
     #[arg(long, short, default_value = consts::DEFAULT_RPC_ADDR, env = "MBV_LISTEN")]
-    pub listen: BindAddress,
+    #[serde_as(as = "OneOrMany<_, PreferOne>")]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(
+            with = "serde_with::Schema<Vec<BindAddress>, OneOrMany<serde_with::Same, PreferOne>>"
+        )
+    )]
+    pub listen: Vec<BindAddress>,
+
+    /// The metrics endpoint, with its listen address flattened to the top
+    /// level as `--metrics`.
+    #[clap(flatten)]
+    #[cfg_attr(feature = "schema", schemars(schema_with = "schema::metrics_schema"))]
+    pub metrics: MetricsConfig,
+
+    /// Disable the metrics endpoint, e.g. one enabled by the embedded
+    /// defaults. The last of `--metrics` and `--no-metrics` wins.
+    #[arg(long, overrides_with = "metrics")]
+    #[serde(skip)]
+    pub no_metrics: bool,
+
+    /// Remotes declared once by name, e.g. `[remotes.helius]`, for sections
+    /// such as `chainlink` and `commit` to refer to.
+    #[clap(skip)]
+    pub remotes: BTreeMap<String, Remote>,
+
+    /// URLs by alias, e.g. `helius = "https://mainnet.helius-rpc.com"`, usable
+    /// wherever a remote URL is given, like the built-in `mainnet`.
+    #[clap(skip)]
+    pub remote_aliases: BTreeMap<String, Url>,
 
-    /// Listen address for the metrics endpoint. If disabled, this is not set.
-    #[arg(long, short, env = "MBV_METRICS")]
-    pub metrics: Option<BindAddress>,
+    /// Remotes by lifecycle, e.g. `replica = "mainnet"`, used in place of
+    /// the built-in default when no layer sets `remote`.
+    #[clap(skip)]
+    pub lifecycle_defaults: BTreeMap<LifecycleMode, RemoteCluster>,
 
     /// Validator-specific arguments, flattened to the top level.
     #[clap(flatten)]
     pub validator: ValidatorConfig,
 
-    // --- File-Only Configuration ---
-    #[clap(skip)]
+    /// Commit arguments, flattened to the top level.
+    #[clap(flatten)]
     pub commit: CommitStrategy,
+
+    /// Ledger arguments, flattened to the top level.
+    #[clap(flatten)]
+    pub ledger: LedgerConfig,
+
+    /// Keep the ledger on startup instead of resetting it, like
+    /// `--ledger-reset false`. The last of the two wins.
+    #[arg(long, overrides_with = "reset")]
+    #[serde(skip)]
+    pub no_ledger_reset: bool,
+
+    // --- File-Only Configuration ---
     #[clap(skip)]
     pub accounts_db: AccountsDbConfig,
     #[clap(skip)]
-    pub ledger: LedgerConfig,
-    #[clap(skip)]
     pub chainlink: ChainLinkConfig,
+    /// When to fail over between the `primary` and `fallback` remotes.
+    #[clap(skip)]
+    pub failover: FailoverConfig,
+    /// Settings for outbound connections, such as a proxy to reach the
+    /// remotes through.
+    #[clap(skip)]
+    pub network: NetworkConfig,
+    #[clap(skip)]
+    pub log: LogConfig,
+    #[clap(skip)]
+    pub tracing: TracingConfig,
     #[clap(skip)]
     pub chain_operation: Option<ChainOperationConfig>,
+    #[clap(skip)]
+    pub admin: Option<AdminConfig>,
+    /// Serve the `listen` endpoints over TLS with this identity.
+    #[clap(skip)]
+    pub tls: Option<TlsIdentity>,
+
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
+    /// Where every value was set, see [`MagicBlockParams::provenance`].
+    #[clap(skip)]
+    #[serde(skip)]
+    provenance: BTreeMap<String, Source>,
+}
+
+/// Actions other than running with the assembled configuration.
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Load and validate the configuration, print a report and exit non-zero
+    /// if it is invalid, without starting anything.
+    Validate,
+    /// Print the effective configuration, with every layer merged, and exit.
+    PrintConfig {
+        /// Output format, e.g. `json` to feed the result to `jq`.
+        #[arg(long, value_enum, default_value = "toml")]
+        format: ConfigFormat,
+        /// Comment every key with where its value was set, e.g.
+        /// `# from /etc/magicblock/config.toml:12`. TOML only.
+        #[arg(long)]
+        sources: bool,
+    },
+    /// Print a JSON Schema of the configuration file and exit.
+    #[cfg(feature = "schema")]
+    Schema,
+    /// Print the effective value of a key, the layer that set it and its
+    /// description.
+    Explain {
+        /// Dotted path of the key, e.g. `validator.basefee`.
+        key: String,
+    },
+    /// Compare two configuration files key by key, with secrets redacted.
+    /// Exits with 1 if they differ.
+    Diff {
+        /// The current configuration file.
+        old: PathBuf,
+        /// The configuration file to compare it with.
+        new: PathBuf,
+    },
+    /// Check that every endpoint of the configured `remote` is reachable and
+    /// healthy, reporting latencies and the cluster's genesis hash. Exits
+    /// with 1 if any check fails.
+    #[cfg(feature = "check-remote")]
+    CheckRemote {
+        /// How long to wait for each endpoint.
+        #[arg(
+            long,
+            default_value = consts::DEFAULT_REMOTE_CHECK_TIMEOUT_STR,
+            value_parser = humantime::re::humantime::parse_duration,
+        )]
+        timeout: Duration,
+    },
+    /// Translate a configuration file in the legacy, pre-layered format to
+    /// the current one.
+    Migrate {
+        /// The legacy configuration file.
+        path: PathBuf,
+        /// Where to write the migrated configuration, instead of printing it.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Print a Markdown reference of every configuration key, with its type,
+    /// default, environment variable and flag, and exit.
+    #[cfg(feature = "schema")]
+    Docs {
+        /// Where to write the reference, instead of printing it.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Write a configuration file listing every key, documented, with its
+    /// default value.
+    Init {
+        /// Where to write the configuration file.
+        #[arg(default_value = "config.toml")]
+        path: PathBuf,
+        /// Overwrite the file if it already exists.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Run the configuration checks along with probes of the host and of the
+    /// remote cluster, and print a pass/warn/fail summary. Exits with 1 if
+    /// any check fails.
+    Doctor {
+        /// How long to wait for each remote endpoint.
+        #[arg(
+            long,
+            default_value = consts::DEFAULT_REMOTE_CHECK_TIMEOUT_STR,
+            value_parser = humantime::re::humantime::parse_duration,
+        )]
+        timeout: Duration,
+    },
+    /// Generate a validator identity keypair and write it in the Solana CLI's
+    /// JSON format.
+    Keygen {
+        /// Where to write the keypair.
+        #[arg(default_value = "validator-keypair.json")]
+        path: PathBuf,
+        /// Overwrite the file if it already exists.
+        #[arg(long)]
+        force: bool,
+        /// Also print a configuration snippet setting `validator.keypair`.
+        #[arg(long)]
+        snippet: bool,
+    },
+    /// Print a completion script for the given shell, covering every option.
+    Completions {
+        /// Shell to generate the completion script for.
+        shell: clap_complete::Shell,
+    },
+}
+
+impl Default for MagicBlockParams {
+    fn default() -> Self {
+        Self {
+            config: Vec::new(),
+            config_format: None,
+            config_timeout: Duration::default(),
+            config_refresh_interval: None,
+            config_token: None,
+            age_identity: None,
+            profile: None,
+            env_file: None,
+            overrides: Vec::new(),
+            strict_config: false,
+            preflight: false,
+            no_env: false,
+            verbose: 0,
+            quiet: 0,
+            config_version: None,
+            remote: RemoteCluster::default(),
+            lifecycle: LifecycleMode::default(),
+            storage: None,
+            create_storage_dirs: false,
+            storage_min_free_space: None,
+            bind_all: false,
+            listen: vec![BindAddress::default()],
+            metrics: MetricsConfig::default(),
+            no_metrics: false,
+            remotes: BTreeMap::new(),
+            remote_aliases: BTreeMap::new(),
+            lifecycle_defaults: BTreeMap::new(),
+            validator: ValidatorConfig::default(),
+            commit: CommitStrategy::default(),
+            ledger: LedgerConfig::default(),
+            no_ledger_reset: false,
+            accounts_db: AccountsDbConfig::default(),
+            chainlink: ChainLinkConfig::default(),
+            failover: FailoverConfig::default(),
+            network: NetworkConfig::default(),
+            log: LogConfig::default(),
+            tracing: TracingConfig::default(),
+            chain_operation: None,
+            admin: None,
+            tls: None,
+            command: None,
+            provenance: BTreeMap::new(),
+        }
+    }
 }
 
 impl MagicBlockParams {
     /// Assembles the final configuration from all sources.
-    /// The precedence is: TOML File > Environment Variables > CLI Arguments > Defaults
-    pub fn try_new(args: impl Iterator<Item = OsString>) -> figment::Result<Self> {
-        let cli = Self::parse_from(args);
-        let mut figment = Figment::new().merge(Serialized::defaults(&cli));
-        if let Some(path) = &cli.config {
-            figment = figment.merge(Toml::file(path).profile(Profile::Default));
+    /// The precedence is: Environment Variables > TOML File > CLI Arguments > Defaults
+    ///
+    /// When no configuration file is given, the one picked from
+    /// [`sources::default_search_path`] (if any) is reported in `config`.
+    ///
+    /// The result is checked with [`Validate`], and every semantic problem
+    /// found is reported at once in [`ConfigError::Invalid`].
+    pub fn try_new(args: impl Iterator<Item = OsString>) -> Result<Self, ConfigError> {
+        Self::builder().try_build(args)
+    }
+
+    /// Like [`MagicBlockParams::try_new`], also returning the non-fatal
+    /// problems found along the way, such as deprecated keys.
+    pub fn try_new_with_warnings(
+        args: impl Iterator<Item = OsString>,
+    ) -> Result<(Self, Vec<ConfigWarning>), ConfigError> {
+        Self::builder().try_build_with_warnings(args)
+    }
+
+    /// Like [`MagicBlockParams::try_new`], ignoring environment variables as
+    /// if `--no-env` was given, so tests don't pick up the host's `MBV_*`
+    /// variables.
+    pub fn try_new_without_env(args: impl Iterator<Item = OsString>) -> Result<Self, ConfigError> {
+        Self::builder().with_no_env(true).try_build(args)
+    }
+
+    /// Parses the command line alone, like [`Parser::parse_from`], also
+    /// accepting `--metrics off`. Exits on invalid arguments.
+    pub fn parse_args(args: impl IntoIterator<Item = OsString>) -> Self {
+        builder::parse_command_line(Self::command(), args.into_iter().collect()).0
+    }
+
+    /// Loads every source and validates the result without starting
+    /// anything, e.g. to lint configuration files in CI. See
+    /// [`ConfigBuilder::validate_sources`].
+    pub fn validate_sources(args: impl Iterator<Item = OsString>) -> ConfigReport {
+        Self::builder().validate_sources(args)
+    }
+
+    /// Diagnoses common misconfigurations, giving each remote endpoint
+    /// `timeout` to respond. See [`ConfigBuilder::doctor`].
+    pub fn doctor(args: impl Iterator<Item = OsString>, timeout: Duration) -> doctor::Diagnosis {
+        Self::builder().doctor(args, timeout)
+    }
+
+    /// Explains where the effective value of the dotted `key` comes from.
+    /// See [`ConfigBuilder::explain`].
+    pub fn explain(
+        args: impl Iterator<Item = OsString>,
+        key: &str,
+    ) -> Result<Explanation, ConfigError> {
+        Self::builder().explain(args, key)
+    }
+
+    /// Like [`MagicBlockParams::try_new`], with additional configuration
+    /// sources layered on top of the configuration files, see
+    /// [`ConfigBuilder::with_provider`].
+    pub fn try_new_with_providers(
+        args: impl Iterator<Item = OsString>,
+        providers: Vec<Box<dyn Provider>>,
+    ) -> Result<Self, ConfigError> {
+        providers
+            .into_iter()
+            .fold(Self::builder(), ConfigBuilder::with_boxed_provider)
+            .try_build(args)
+    }
+
+    /// Serializes the configuration back to TOML, e.g. to see which value
+    /// won across layers. Secrets are written as their references, or as
+    /// `***REDACTED***` if given inline, and options that only control
+    /// loading, such as `config`, are left out.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(&self.to_table()?)
+    }
+
+    /// Like [`Self::to_toml`], in any of the configuration file formats.
+    /// Keys are written in the same order whatever the format, so that
+    /// dumps can be compared.
+    pub fn to_format(&self, format: ConfigFormat) -> Result<String, SerializeError> {
+        let table = self.to_table()?;
+        Ok(match format {
+            ConfigFormat::Toml => toml::to_string_pretty(&table)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(&table)? + "\n",
+            ConfigFormat::Yaml => serde_yaml::to_string(&table)?,
+        })
+    }
+
+    /// The configuration as a table, in field order, without the options
+    /// that only control loading.
+    fn to_table(&self) -> Result<toml::Table, toml::ser::Error> {
+        let mut table = toml::Table::try_from(self)?;
+        table.retain(|key, _| !consts::LOADER_KEYS.contains(&key));
+        Ok(table)
+    }
+
+    /// A SHA-256 hash of the configuration, to tell which revision a node
+    /// is running and to detect drift across a fleet. Only the keys and
+    /// values count: neither their order nor their sources do. Secrets and
+    /// the options that only control loading are left out.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        // Sorted by key, with one line per value.
+        for (key, mut value) in diff::leaf_values(self) {
+            let root = key.split('.').next().unwrap_or_default();
+            if secrets::is_secret_key(&key) || consts::LOADER_KEYS.contains(&root) {
+                continue;
+            }
+            secrets::redact_toml(&key, &mut value);
+            hasher.update(format!("{key} = {value}\n"));
         }
-        figment = figment.merge(Env::prefixed("MBV_").split("_").profile(Profile::Default));
-        figment.extract()
+        hasher.finalize().into()
+    }
+
+    /// Like [`Self::to_toml`], but writes inline secrets as they are, for
+    /// writing configuration files that can be loaded back. Keep the output
+    /// out of logs.
+    pub fn to_toml_exposing_secrets(&self) -> Result<String, toml::ser::Error> {
+        secrets::exposed(|| self.to_toml())
+    }
+
+    /// Translates a configuration in the legacy, pre-layered TOML format to
+    /// the current one. See [`legacy::migrate_legacy`].
+    pub fn migrate_legacy(content: &str) -> Result<Self, ConfigError> {
+        legacy::migrate_legacy(content)
+    }
+
+    /// Returns a [`ConfigBuilder`] to customize how the configuration is assembled.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Where the value of every key was set, by dotted key, e.g. that
+    /// `validator.basefee` comes from line 3 of `config.toml`. Loader
+    /// options are left out. Empty unless the configuration was assembled
+    /// by a [`ConfigBuilder`].
+    pub fn provenance(&self) -> &BTreeMap<String, Source> {
+        &self.provenance
+    }
+
+    /// The remote named `name` in `[remotes]`, or the primary `remote` when no
+    /// name is given, e.g. `params.remote_for(params.chainlink.remote.as_deref())`.
+    pub fn remote_for(&self, name: Option<&str>) -> Option<&Remote> {
+        match name {
+            Some(name) => self.remotes.get(name),
+            None => self.remote.primary(),
+        }
+    }
+
+    /// The proxy to connect to `remote` through: the remote's own `proxy`,
+    /// or else `[network.proxy]` unless the remote's host is in `no-proxy`.
+    /// `None` means connecting directly.
+    pub fn proxy_for<'a>(&'a self, remote: &'a Remote) -> Option<&'a Url> {
+        match remote.proxy() {
+            Some(RemoteProxy::Direct) => None,
+            Some(RemoteProxy::Via(url)) => Some(url),
+            None => {
+                let proxy = &self.network.proxy;
+                let bypassed = remote.host().is_some_and(|host| proxy.bypasses(host));
+                proxy.url.as_ref().filter(|_| !bypassed)
+            }
+        }
+    }
+
+    /// The remote to use for `purpose`: the one named by its section, such
+    /// as `chainlink.remote` for cloning, or else by `[remote.roles]`, or
+    /// else the primary `remote`. Subscriptions go to the first gRPC remote,
+    /// if any, before the primary.
+    pub fn remote_for_purpose(&self, purpose: RemotePurpose) -> Option<&Remote> {
+        let roles = self.remote.roles();
+        let name = match purpose {
+            RemotePurpose::Clone => self.chainlink.remote.as_ref().or(roles.clone.as_ref()),
+            RemotePurpose::Commit => self.commit.remote.as_ref().or(roles.commit.as_ref()),
+            RemotePurpose::Subscribe => roles.subscribe.as_ref(),
+        };
+        match name {
+            Some(name) => self.remotes.get(name),
+            None if purpose == RemotePurpose::Subscribe => {
+                self.remote.grpc().next().or_else(|| self.remote.primary())
+            }
+            None => self.remote.primary(),
+        }
+    }
+
+    /// Every address to bind, by dotted key: `listen`, or `listen.N` when
+    /// several are given, then `metrics.listen` and `admin.listen` if set.
+    pub(crate) fn bind_addresses(&self) -> Vec<(String, &BindAddress)> {
+        let listen = self.listen.iter().enumerate().map(|(i, address)| {
+            let key = match self.listen.len() {
+                1 => "listen".to_owned(),
+                _ => format!("listen.{i}"),
+            };
+            (key, address)
+        });
+        let metrics = self
+            .metrics
+            .listen
+            .iter()
+            .map(|address| ("metrics.listen".to_owned(), address));
+        let admin = self
+            .admin
+            .iter()
+            .map(|admin| ("admin.listen".to_owned(), &admin.listen));
+        listen.chain(metrics).chain(admin).collect()
+    }
+
+    /// Picks the interface of every address given as a port alone,
+    /// following `bind-all`.
+    pub(crate) fn expand_bind_addresses(&mut self) {
+        let admin = self.admin.as_mut().map(|admin| &mut admin.listen);
+        let addresses = self
+            .listen
+            .iter_mut()
+            .chain(self.metrics.listen.as_mut())
+            .chain(admin);
+        for address in addresses {
+            address.expand(self.bind_all);
+        }
+    }
+
+    /// Uses the `[lifecycle-defaults]` remote of the lifecycle, if any, when
+    /// no layer but the built-in defaults sets `remote`, so that switching
+    /// modes switches clusters too. Its source is reported for `remote`.
+    pub(crate) fn apply_lifecycle_defaults(&mut self) {
+        let remote_set = self.provenance.iter().any(|(key, source)| {
+            (key == "remote" || key.starts_with("remote."))
+                && source.layer != provenance::BUILT_IN_DEFAULTS
+        });
+        let remote = self.lifecycle_defaults.get(&self.lifecycle);
+        let Some(remote) = remote.filter(|_| !remote_set) else {
+            return;
+        };
+        self.remote = remote.clone();
+        let key = format!("lifecycle-defaults.{}", self.lifecycle.name());
+        let table = format!("{key}.");
+        let source = self
+            .provenance
+            .iter()
+            .find(|(k, _)| **k == key || k.starts_with(&table))
+            .map(|(_, source)| source.clone());
+        if let Some(source) = source {
+            self.provenance.insert("remote".to_owned(), source);
+        }
+    }
+
+    /// Replaces every remote URL given as a `[remote-aliases]` name with the
+    /// aliased URL. Unknown names are left for validation to report.
+    pub(crate) fn resolve_remote_aliases(&mut self) {
+        let lifecycle_defaults = self
+            .lifecycle_defaults
+            .values_mut()
+            .flat_map(|cluster| cluster.remotes_mut().iter_mut());
+        let urls = self
+            .remote
+            .remotes_mut()
+            .iter_mut()
+            .chain(self.remotes.values_mut())
+            .chain(lifecycle_defaults)
+            .flat_map(Remote::urls_mut);
+        for url in urls {
+            if let Some(aliased) = url.alias().and_then(|name| self.remote_aliases.get(name)) {
+                url.url = aliased.clone();
+            }
+        }
+    }
+
+    /// Resolves relative paths set in a configuration file against that
+    /// file's directory. Paths from the command line and the environment
+    /// stay relative to the working directory.
+    pub(crate) fn resolve_relative_paths(&mut self) {
+        let provenance = std::mem::take(&mut self.provenance);
+        let file_dir = |key: &str| {
+            let source = provenance.get(key)?;
+            let file = Path::new(source.file.as_deref()?);
+            if source.variable.is_some() || !file.is_file() {
+                return None;
+            }
+            file.parent().map(Path::to_path_buf)
+        };
+        for (key, path) in self.paths_mut() {
+            if let Some(dir) = file_dir(&key) {
+                path.resolve_against(&dir);
+            }
+        }
+        self.provenance = provenance;
+    }
+
+    /// Every path of the configuration, keyed by dotted key.
+    fn paths_mut(&mut self) -> Vec<(String, &mut ExpandedPath)> {
+        let tls = [
+            ("tls", self.tls.as_mut()),
+            ("metrics.tls", self.metrics.tls.as_mut()),
+        ];
+        let tls = tls.into_iter().flat_map(|(section, identity)| {
+            let paths = identity.into_iter().flat_map(TlsIdentity::paths_mut);
+            paths.map(move |(name, path)| (format!("{section}.{name}"), path))
+        });
+        let storage = self
+            .storage
+            .as_mut()
+            .map(|path| ("storage".to_owned(), path));
+        storage.into_iter().chain(tls).collect()
     }
 }
 
 /// Defines the operational mode of the application.
-#[derive(ValueEnum, Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[derive(
+    ValueEnum, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 #[clap(rename_all = "kebab-case")]
 pub enum LifecycleMode {
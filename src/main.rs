@@ -1,8 +1,255 @@
-use std::env::args_os;
+use std::{env::args_os, ffi::OsString, path::Path, process::ExitCode};
 
-use magicblock_config::MagicBlockParams;
+use clap::CommandFactory;
+use magicblock_config::{
+    keygen,
+    sources::{ConfigFormat, FileOptions},
+    Command, MagicBlockParams,
+};
 
-fn main() {
-    let params = MagicBlockParams::try_new(args_os()).unwrap();
-    println!("{params:?}")
+fn main() -> ExitCode {
+    let args: Vec<OsString> = args_os().collect();
+    let cli = MagicBlockParams::parse_args(args.clone());
+    match cli.command {
+        Some(Command::Validate) => return validate(args),
+        Some(Command::PrintConfig { format, sources }) => {
+            return print_config(args, format, sources)
+        }
+        #[cfg(feature = "schema")]
+        Some(Command::Schema) => {
+            let schema = MagicBlockParams::json_schema();
+            let json = serde_json::to_string_pretty(&schema).expect("Schemas always serialize");
+            println!("{json}");
+            return ExitCode::SUCCESS;
+        }
+        Some(Command::Explain { key }) => return explain(args, &key),
+        Some(Command::Diff { ref old, ref new }) => return diff(&cli, old, new),
+        #[cfg(feature = "check-remote")]
+        Some(Command::CheckRemote { timeout }) => return check_remote(args, timeout),
+        Some(Command::Migrate { path, output }) => return migrate(&path, output.as_deref()),
+        Some(Command::Init { path, force }) => return init(&path, force),
+        #[cfg(feature = "schema")]
+        Some(Command::Docs { output }) => return docs(output.as_deref()),
+        Some(Command::Doctor { timeout }) => {
+            let diagnosis = MagicBlockParams::doctor(args.into_iter(), timeout);
+            println!("{diagnosis}");
+            return if diagnosis.is_ok() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            };
+        }
+        Some(Command::Keygen {
+            path,
+            force,
+            snippet,
+        }) => return keygen(&path, force, snippet),
+        Some(Command::Completions { shell }) => {
+            let mut command = MagicBlockParams::command();
+            let name = command.get_name().to_owned();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+            return ExitCode::SUCCESS;
+        }
+        None => {}
+    }
+
+    let (params, warnings) = MagicBlockParams::try_new_with_warnings(args.into_iter()).unwrap();
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    for path in &params.config {
+        eprintln!("Using configuration file {}", path.display());
+    }
+    println!("{params:?}");
+    ExitCode::SUCCESS
+}
+
+/// Runs the `validate` subcommand.
+fn validate(args: Vec<OsString>) -> ExitCode {
+    let report = MagicBlockParams::validate_sources(args.into_iter());
+    if !report.warnings.is_empty() || !report.is_ok() {
+        eprintln!("{report}");
+    }
+    if report.is_ok() {
+        println!("Configuration is valid");
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Runs the `check-remote` subcommand.
+#[cfg(feature = "check-remote")]
+fn check_remote(args: Vec<OsString>, timeout: std::time::Duration) -> ExitCode {
+    let (params, warnings) = match MagicBlockParams::try_new_with_warnings(args.into_iter()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    let check = params.check_remote(timeout);
+    println!("{check}");
+    if check.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Runs the `print-config` subcommand.
+fn print_config(args: Vec<OsString>, format: ConfigFormat, sources: bool) -> ExitCode {
+    if sources && format != ConfigFormat::Toml {
+        eprintln!("--sources is only supported with --format toml");
+        return ExitCode::FAILURE;
+    }
+    let (params, warnings) = match MagicBlockParams::try_new_with_warnings(args.into_iter()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    let output = if sources {
+        params.to_toml_with_sources().map_err(Into::into)
+    } else {
+        params.to_format(format)
+    };
+    match output {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("failed to serialize the configuration: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the `explain` subcommand.
+fn explain(args: Vec<OsString>, key: &str) -> ExitCode {
+    match MagicBlockParams::explain(args.into_iter(), key) {
+        Ok(explanation) => {
+            println!("{explanation}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the `diff` subcommand, exiting like `diff(1)`: 0 if the files are
+/// the same, 1 if they differ and 2 if either can't be loaded.
+fn diff(cli: &MagicBlockParams, old: &Path, new: &Path) -> ExitCode {
+    let options = FileOptions::from(cli);
+    let load = |path: &Path| {
+        MagicBlockParams::from_file(path, &options)
+            .map_err(|e| eprintln!("failed to load {}: {e}", path.display()))
+    };
+    let (Ok(old), Ok(new)) = (load(old), load(new)) else {
+        return ExitCode::from(2);
+    };
+    let diff = old.diff(&new);
+    if diff.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    println!("{diff}");
+    ExitCode::FAILURE
+}
+
+/// Runs the `migrate` subcommand.
+fn migrate(path: &Path, output: Option<&Path>) -> ExitCode {
+    let migrated = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))
+        .and_then(|content| MagicBlockParams::migrate_legacy(&content).map_err(|e| e.to_string()))
+        .and_then(|params| params.to_toml_exposing_secrets().map_err(|e| e.to_string()));
+    let toml = match migrated {
+        Ok(toml) => toml,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match output {
+        Some(output) => {
+            if let Err(e) = std::fs::write(output, toml) {
+                eprintln!("failed to write {}: {e}", output.display());
+                return ExitCode::FAILURE;
+            }
+            eprintln!("Wrote {}", output.display());
+        }
+        None => print!("{toml}"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Runs the `docs` subcommand.
+#[cfg(feature = "schema")]
+fn docs(output: Option<&Path>) -> ExitCode {
+    let reference = MagicBlockParams::reference();
+    match output {
+        Some(output) => {
+            if let Err(e) = std::fs::write(output, reference) {
+                eprintln!("failed to write {}: {e}", output.display());
+                return ExitCode::FAILURE;
+            }
+            eprintln!("Wrote {}", output.display());
+        }
+        None => print!("{reference}"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Runs the `init` subcommand.
+fn init(path: &Path, force: bool) -> ExitCode {
+    if path.exists() && !force {
+        eprintln!(
+            "{} already exists, pass --force to overwrite it",
+            path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    if let Err(e) = std::fs::write(path, MagicBlockParams::template()) {
+        eprintln!("failed to write {}: {e}", path.display());
+        return ExitCode::FAILURE;
+    }
+    println!("Wrote {}", path.display());
+    ExitCode::SUCCESS
+}
+
+/// Runs the `keygen` subcommand. The snippet goes to stdout, so it can be
+/// appended to a configuration file.
+fn keygen(path: &Path, force: bool, snippet: bool) -> ExitCode {
+    if path.exists() && !force {
+        eprintln!(
+            "{} already exists, pass --force to overwrite it",
+            path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    let keypair = match keygen::generate_keypair_file(path) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            eprintln!("failed to write {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!(
+        "Wrote keypair {} to {}",
+        keygen::pubkey(&keypair),
+        path.display()
+    );
+    if snippet {
+        print!("{}", keygen::keypair_snippet(&keypair));
+    }
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,148 @@
+//! Pre-flight checks run before any service starts, so that misconfigured
+//! hosts fail fast with a clear diagnostic.
+
+use crate::{
+    types::{BindAddress, UnixSocket},
+    validate::{self, ValidationError},
+    MagicBlockParams,
+};
+use std::net::SocketAddr;
+
+impl MagicBlockParams {
+    /// Attempts to bind the `listen`, `metrics` and `admin.listen` addresses, releasing them
+    /// right away, and reports every address that can't be bound. Where
+    /// possible, the process already holding the address is named.
+    pub fn check_ports(&self) -> Vec<ValidationError> {
+        self.bind_addresses()
+            .into_iter()
+            .filter_map(|(key, address)| {
+                let message = match address {
+                    BindAddress::Unix(socket) => check_unix_socket(socket)?,
+                    address => check_tcp_address(address)?,
+                };
+                Some(ValidationError::new(key, message))
+            })
+            .collect()
+    }
+
+    /// Creates the `storage` directory, including its parents, if it is
+    /// missing and `create_storage_dirs` is enabled, and checks that it can
+    /// be written to. Run once the configuration is valid, as [`Validate`]
+    /// itself only looks.
+    ///
+    /// [`Validate`]: crate::Validate
+    pub fn prepare_storage(&self) -> Vec<ValidationError> {
+        let Some(storage) = self.storage.as_ref().filter(|_| self.create_storage_dirs) else {
+            return Vec::new();
+        };
+        let created = std::fs::create_dir_all(storage)
+            .map_err(|e| format!("failed to create directory: {e}"))
+            .and_then(|()| validate::check_writable_dir(storage));
+        match created {
+            Ok(()) => Vec::new(),
+            Err(message) => {
+                let message = format!("{}: {message}", storage.display());
+                vec![ValidationError::new("storage", message)]
+            }
+        }
+    }
+}
+
+/// Describes why `address` can't be bound, if it can't.
+fn check_tcp_address(address: &BindAddress) -> Option<String> {
+    let error = address.bind_tcp().err()?;
+    let address = address.socket_addr()?;
+    Some(match listener_process(address) {
+        Some(owner) if error.kind() == std::io::ErrorKind::AddrInUse => {
+            format!("{address} is already in use by {owner}")
+        }
+        _ => format!("cannot bind {address}: {error}"),
+    })
+}
+
+/// Describes why `socket` can't be bound, if it can't. A socket file nothing
+/// listens on is left alone, as [`UnixSocket::bind`] replaces it.
+#[cfg(unix)]
+fn check_unix_socket(socket: &UnixSocket) -> Option<String> {
+    use std::os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    };
+
+    let path = socket.path.display();
+    match std::fs::symlink_metadata(&socket.path) {
+        Ok(metadata) if !metadata.file_type().is_socket() => {
+            Some(format!("{path} exists and is not a socket"))
+        }
+        Ok(_) => UnixStream::connect(&socket.path)
+            .is_ok()
+            .then(|| format!("{path} is already in use")),
+        Err(_) => match UnixListener::bind(&socket.path) {
+            Ok(_) => {
+                let _ = std::fs::remove_file(&socket.path);
+                None
+            }
+            Err(error) => Some(format!("cannot bind {path}: {error}")),
+        },
+    }
+}
+
+/// Unix domain sockets are reported as unsupported by [`Validate`].
+///
+/// [`Validate`]: crate::Validate
+#[cfg(not(unix))]
+fn check_unix_socket(_socket: &UnixSocket) -> Option<String> {
+    None
+}
+
+/// Names the process listening on the port of `address`, as `name (pid N)`.
+/// Only processes visible to the current user can be found.
+#[cfg(target_os = "linux")]
+fn listener_process(address: SocketAddr) -> Option<String> {
+    use std::fs;
+
+    let socket = format!("socket:[{}]", listening_socket_inode(address.port())?);
+    fs::read_dir("/proc").ok()?.flatten().find_map(|process| {
+        let pid: u32 = process.file_name().to_str()?.parse().ok()?;
+        let mut fds = fs::read_dir(process.path().join("fd")).ok()?.flatten();
+        let owns_socket = fds.any(|fd| {
+            fs::read_link(fd.path()).is_ok_and(|target| target.as_os_str() == socket.as_str())
+        });
+        if !owns_socket {
+            return None;
+        }
+        let name = fs::read_to_string(process.path().join("comm")).unwrap_or_default();
+        Some(format!("{} (pid {pid})", name.trim()))
+    })
+}
+
+/// Finds the inode of the TCP socket listening on `port` in the kernel's
+/// socket tables.
+#[cfg(target_os = "linux")]
+fn listening_socket_inode(port: u16) -> Option<String> {
+    /// Socket state of a listening TCP socket in `/proc/net/tcp`.
+    const TCP_LISTEN: &str = "0A";
+
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(content) = std::fs::read_to_string(table) else {
+            continue;
+        };
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some((_, local_port)) = fields.get(1).and_then(|local| local.rsplit_once(':'))
+            else {
+                continue;
+            };
+            if u16::from_str_radix(local_port, 16) == Ok(port) && fields.get(3) == Some(&TCP_LISTEN)
+            {
+                return fields.get(9).map(|inode| inode.to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listener_process(_address: SocketAddr) -> Option<String> {
+    None
+}
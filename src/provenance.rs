@@ -0,0 +1,261 @@
+//! Tracking of where every effective value comes from, down to the file and
+//! line or the environment variable that set it.
+
+use crate::{consts, sources, MagicBlockParams};
+use figment::{value::Dict, Figment, Metadata};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+/// Where the effective value of a key was set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    /// The layer that set the value, e.g. `TOML file` or `built-in defaults`.
+    pub layer: String,
+    /// The file the value was read from, if it came from one, e.g. a
+    /// configuration file, a URL or the `.env` file.
+    pub file: Option<String>,
+    /// The line of `file` that sets the value, counting from 1, if known.
+    /// Only TOML and `.env` files are searched.
+    pub line: Option<usize>,
+    /// The environment variable that set the value, for the environment and
+    /// `.env` layers.
+    pub variable: Option<String>,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.layer)?;
+        if let Some(variable) = &self.variable {
+            write!(f, " `{variable}`")?;
+            if self.file.is_some() {
+                f.write_str(" in")?;
+            }
+        }
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, " `{file}:{line}`"),
+            (Some(file), None) => write!(f, " `{file}`"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Source {
+    /// A short note on where the value was set, for comments in dumps, e.g.
+    /// `from env MBV_VALIDATOR_BASEFEE` or `default`.
+    fn annotation(&self) -> String {
+        match (&self.variable, &self.file, self.line) {
+            (Some(variable), Some(file), Some(line)) => {
+                format!("from env {variable} ({file}:{line})")
+            }
+            (Some(variable), _, _) => format!("from env {variable}"),
+            (None, Some(file), Some(line)) => format!("from {file}:{line}"),
+            (None, Some(file), None) => format!("from {file}"),
+            (None, None, _) if self.layer == BUILT_IN_DEFAULTS => "default".to_owned(),
+            (None, None, _) => format!("from {}", self.layer),
+        }
+    }
+}
+
+impl MagicBlockParams {
+    /// Like [`MagicBlockParams::to_toml`], with a comment next to every key
+    /// saying where its value was set, see [`MagicBlockParams::provenance`].
+    pub fn to_toml_with_sources(&self) -> Result<String, toml::ser::Error> {
+        let toml = self.to_toml()?;
+        // The serializer's own output always parses.
+        let mut document: toml_edit::DocumentMut = toml.parse().expect("valid TOML");
+        for (key, source) in &self.provenance {
+            // Arrays of tables have no single line to comment.
+            if let Some(value) = value_mut(document.as_item_mut(), key) {
+                let comment = format!(" # {}", source.annotation());
+                value.decor_mut().set_suffix(comment);
+            }
+        }
+        Ok(document.to_string())
+    }
+}
+
+/// The value of the dotted `key` in `item`, if it is a plain value.
+fn value_mut<'a>(item: &'a mut toml_edit::Item, key: &str) -> Option<&'a mut toml_edit::Value> {
+    key.split('.')
+        .try_fold(item, |item, name| item.get_mut(name))?
+        .as_value_mut()
+}
+
+/// Name of the layer of built-in defaults.
+pub(crate) const BUILT_IN_DEFAULTS: &str = "built-in defaults";
+
+/// The source of every key set in the merged `figment`, by dotted key.
+pub(crate) fn collect(figment: &Figment) -> BTreeMap<String, Source> {
+    let Ok(merged) = figment.extract::<Dict>() else {
+        return BTreeMap::new();
+    };
+    let mut locator = Locator::default();
+    sources::leaf_keys(&merged)
+        .into_iter()
+        .filter(|key| {
+            let root = key.split('.').next().unwrap_or_default();
+            !consts::LOADER_KEYS.contains(&root)
+        })
+        .filter_map(|key| Some((key.clone(), locator.of(figment, &key)?)))
+        .collect()
+}
+
+/// The source of the dotted `key` in the merged `figment`, or `None` if no
+/// layer sets it.
+pub(crate) fn of(figment: &Figment, key: &str) -> Option<Source> {
+    Locator::default().of(figment, key)
+}
+
+/// The source of the dotted `key`, set by the layer with `metadata`.
+pub(crate) fn from_metadata(metadata: &Metadata, key: &str) -> Source {
+    Locator::default().in_metadata(metadata, key)
+}
+
+/// Finds where keys are set within their layer, reading each file and the
+/// environment once, however many keys are looked up.
+#[derive(Default)]
+pub(crate) struct Locator {
+    /// The line setting each key, by TOML file.
+    toml: HashMap<PathBuf, HashMap<String, usize>>,
+    /// The variable setting each key and its line, by `.env` file.
+    dotenv: HashMap<PathBuf, BTreeMap<String, (String, Option<usize>)>>,
+    /// The variable setting each key in the environment.
+    env: Option<BTreeMap<String, String>>,
+}
+
+impl Locator {
+    /// The source of the dotted `key` in the merged `figment`, or `None` if
+    /// no layer sets it.
+    pub(crate) fn of(&mut self, figment: &Figment, key: &str) -> Option<Source> {
+        figment
+            .find_metadata(key)
+            .map(|metadata| self.in_metadata(metadata, key))
+    }
+
+    /// The source of the dotted `key`, set by the layer with `metadata`.
+    pub(crate) fn in_metadata(&mut self, metadata: &Metadata, key: &str) -> Source {
+        let file = metadata.source.as_ref().and_then(|source| match source {
+            figment::Source::File(path) => Some(path.display().to_string()),
+            figment::Source::Custom(name) => Some(name.clone()),
+            _ => None,
+        });
+        let mut source = Source {
+            layer: metadata.name.to_string(),
+            file,
+            line: None,
+            variable: None,
+        };
+        if is_environment(metadata) {
+            let env = self.env.get_or_insert_with(|| {
+                std::env::vars()
+                    .filter_map(|(var, _)| Some((sources::env_key_path(&var)?, var)))
+                    .collect()
+            });
+            source.variable = setting(env, key).cloned();
+        } else if let Some(path) = source.file.as_deref().map(PathBuf::from) {
+            if metadata.name == sources::DOTENV_LAYER {
+                let vars = self
+                    .dotenv
+                    .entry(path)
+                    .or_insert_with_key(|path| dotenv_vars(path));
+                if let Some((var, line)) = setting(vars, key) {
+                    source.variable = Some(var.clone());
+                    source.line = *line;
+                }
+            } else if metadata.name.starts_with("TOML") {
+                let lines = self
+                    .toml
+                    .entry(path)
+                    .or_insert_with_key(|path| toml_lines(path));
+                source.line = lines.get(key).copied().or_else(|| {
+                    // Set with a shorthand, e.g. `metrics = ...` for `metrics.listen`.
+                    let (section, _) = consts::SHORTHAND_KEYS
+                        .iter()
+                        .find(|(section, field)| key == format!("{section}.{field}"))?;
+                    lines.get(*section).copied()
+                });
+            }
+        }
+        source
+    }
+}
+
+/// Whether `metadata` is that of the environment layer.
+fn is_environment(metadata: &Metadata) -> bool {
+    metadata.name == sources::ENVIRONMENT_LAYER
+}
+
+/// The entry of `by_key` setting the dotted `key`, or the last one setting a
+/// key nested in it, as when an unknown section is reported by its name
+/// alone.
+fn setting<'a, T>(by_key: &'a BTreeMap<String, T>, key: &str) -> Option<&'a T> {
+    by_key.get(key).or_else(|| {
+        let nested = format!("{key}.");
+        by_key
+            .range(nested.clone()..)
+            .take_while(|(path, _)| path.starts_with(&nested))
+            .map(|(_, value)| value)
+            .last()
+    })
+}
+
+/// The line of the TOML file at `path` that sets each dotted key, tables
+/// included. Arrays of tables have no single line and are left out.
+fn toml_lines(path: &Path) -> HashMap<String, usize> {
+    fn collect(
+        content: &str,
+        table: &dyn toml_edit::TableLike,
+        prefix: &str,
+        lines: &mut HashMap<String, usize>,
+    ) {
+        for (name, item) in table.iter() {
+            let key = format!("{prefix}{name}");
+            let Some((name, _)) = table.get_key_value(name) else {
+                continue;
+            };
+            if let Some(span) = name.span() {
+                lines.insert(key.clone(), content[..span.start].matches('\n').count() + 1);
+            }
+            if let Some(nested) = item.as_table_like() {
+                collect(content, nested, &format!("{key}."), lines);
+            }
+        }
+    }
+
+    let mut lines = HashMap::new();
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return lines;
+    };
+    if let Ok(document) = toml_edit::ImDocument::parse(content.as_str()) {
+        collect(&content, document.as_table(), "", &mut lines);
+    }
+    lines
+}
+
+/// The variable of the `.env` file at `path` that sets each dotted key,
+/// with the line it is assigned on. The last assignment wins, as when the
+/// file is read.
+fn dotenv_vars(path: &Path) -> BTreeMap<String, (String, Option<usize>)> {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let mut lines = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_start();
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        if let Some((var, _)) = line.split_once('=') {
+            lines.insert(var.trim_end(), index + 1);
+        }
+    }
+    dotenvy::from_path_iter(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|(var, _)| {
+            let key = sources::env_key_path(&var)?;
+            let line = lines.get(var.as_str()).copied();
+            Some((key, (var, line)))
+        })
+        .collect()
+}
@@ -0,0 +1,674 @@
+//! Reloading of the configuration while running, e.g. on `SIGHUP` as daemons
+//! conventionally do, without restarting the application.
+
+use crate::{
+    diff::KeyChange,
+    error::ConfigError,
+    explain::Explanation,
+    report::ConfigReport,
+    sources::{self, StdinDocument},
+    validate::ValidationError,
+    ConfigBuilder, MagicBlockParams,
+};
+use figment::{providers::Serialized, value::Dict, Figment, Profile, Provider};
+use std::{
+    ffi::OsString,
+    fmt::{self, Display},
+    hash::{BuildHasher, RandomState},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+/// Longest wait between refreshes while the sources can't be read, as a
+/// multiple of the refresh interval.
+const MAX_REFRESH_BACKOFF: u32 = 16;
+/// Share of the refresh interval by which each wait is randomly shortened
+/// or lengthened, so that a fleet of nodes doesn't fetch in lockstep.
+const REFRESH_JITTER: f64 = 0.1;
+
+/// Copies the value of a key from a reloaded configuration to the active one.
+type Apply = fn(&mut MagicBlockParams, &MagicBlockParams);
+
+/// The keys, or whole sections, that take effect without a restart, with
+/// how to apply them. Changes to any other key require a restart.
+const RELOADABLE: &[(&str, Apply)] = &[
+    ("validator.basefee", |active, new| {
+        active.validator.basefee = new.validator.basefee
+    }),
+    ("commit.compute-unit-price", |active, new| {
+        active.commit.compute_unit_price = new.commit.compute_unit_price
+    }),
+    ("chainlink.auto-airdrop-lamports", |active, new| {
+        active.chainlink.auto_airdrop_lamports = new.chainlink.auto_airdrop_lamports
+    }),
+    ("log", |active, new| active.log = new.log.clone()),
+];
+
+/// Whether a change to the dotted `key` takes effect on reload, rather than
+/// requiring a restart.
+pub fn is_reloadable(key: &str) -> bool {
+    RELOADABLE.iter().any(|(reloadable, _)| {
+        key.strip_prefix(reloadable)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// What a reload changed, see [`ConfigHandle::reload`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadOutcome {
+    /// Generation of the configuration active after the reload.
+    pub generation: u64,
+    /// Changes now in effect.
+    pub applied: Vec<KeyChange>,
+    /// Changes that take effect on the next restart. Until then, the active
+    /// configuration keeps the old values.
+    pub restart_required: Vec<KeyChange>,
+}
+
+impl ReloadOutcome {
+    /// Whether the sources changed at all.
+    pub fn is_unchanged(&self) -> bool {
+        self.applied.is_empty() && self.restart_required.is_empty()
+    }
+}
+
+impl Display for ReloadOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_unchanged() {
+            return f.write_str("no changes");
+        }
+        let mut separator = "";
+        for (title, changes) in [
+            ("applied", &self.applied),
+            ("restart required", &self.restart_required),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            write!(f, "{separator}{title}:")?;
+            for change in changes {
+                write!(f, "\n  {change}")?;
+            }
+            separator = "\n";
+        }
+        Ok(())
+    }
+}
+
+/// A change of the active configuration, see [`ConfigEvent::Changed`].
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    /// Generation of the configuration now active.
+    pub generation: u64,
+    /// The configuration that was active before.
+    pub previous: Arc<MagicBlockParams>,
+    /// The configuration now active.
+    pub current: Arc<MagicBlockParams>,
+    /// The keys that changed, all of them reloadable.
+    pub changes: Vec<KeyChange>,
+}
+
+impl ConfigChange {
+    /// Whether the dotted `key`, or any key of the section it names,
+    /// changed, e.g. `commit.compute-unit-price` or `log`.
+    pub fn changed(&self, key: &str) -> bool {
+        self.changes.iter().any(|change| {
+            change
+                .key()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+}
+
+/// A rejected reload, see [`ConfigEvent::Rejected`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadRejected {
+    /// Generation of the configuration still active.
+    pub generation: u64,
+    /// Every problem found in the sources.
+    pub report: ConfigReport,
+}
+
+/// What the subscribers of a [`ConfigHandle`] are sent.
+#[derive(Debug, Clone)]
+pub enum ConfigEvent {
+    /// A reload applied changes.
+    Changed(ConfigChange),
+    /// A reload was rejected, and the previous configuration is kept.
+    Rejected(ReloadRejected),
+}
+
+/// The active configuration, shared between threads. A reload re-reads
+/// every layer and replaces the configuration as a whole, so readers never
+/// see a mix of old and new values.
+#[derive(Clone)]
+pub struct ConfigHandle {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    /// Makes the builder each load starts from, as builders are consumed.
+    builder: Box<dyn Fn() -> ConfigBuilder + Send + Sync>,
+    /// The arguments the configuration was first loaded with.
+    args: Vec<OsString>,
+    /// The document read from stdin on the first load, if any, as stdin is
+    /// at its end on reloads.
+    stdin: StdinDocument,
+    current: RwLock<Snapshot>,
+    /// Every patch applied so far, merged, which reloads keep applying.
+    patches: Mutex<Dict>,
+    /// Senders of every subscription, dropped once the receiver is.
+    subscribers: Mutex<Vec<Sender<ConfigEvent>>>,
+    /// The generation of the active configuration, for watches to wait on
+    /// with `published`.
+    generation: Mutex<u64>,
+    published: Condvar,
+    /// Publishes every configuration made active, for async watches.
+    #[cfg(feature = "tokio")]
+    latest: tokio::sync::watch::Sender<Arc<MagicBlockParams>>,
+}
+
+/// The active configuration along with its generation.
+struct Snapshot {
+    /// How many reloads applied changes before this configuration became
+    /// active, starting at 0 for the initial load.
+    generation: u64,
+    params: Arc<MagicBlockParams>,
+}
+
+impl ConfigHandle {
+    /// Loads the configuration from `args` with a builder made by `builder`,
+    /// e.g. [`MagicBlockParams::builder`]. Reloads use the same arguments
+    /// and a fresh builder.
+    pub fn new(
+        builder: impl Fn() -> ConfigBuilder + Send + Sync + 'static,
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<Self, ConfigError> {
+        let args: Vec<OsString> = args.into_iter().collect();
+        let stdin = StdinDocument::default();
+        let params = builder()
+            .with_stdin(stdin.clone())
+            .try_build(args.clone())?;
+        let params = Arc::new(params);
+        Ok(Self {
+            shared: Arc::new(Shared {
+                builder: Box::new(builder),
+                args,
+                stdin,
+                current: RwLock::new(Snapshot {
+                    generation: 0,
+                    params: Arc::clone(&params),
+                }),
+                patches: Mutex::default(),
+                subscribers: Mutex::default(),
+                generation: Mutex::default(),
+                published: Condvar::new(),
+                #[cfg(feature = "tokio")]
+                latest: tokio::sync::watch::Sender::new(params),
+            }),
+        })
+    }
+
+    /// The active configuration.
+    pub fn current(&self) -> Arc<MagicBlockParams> {
+        self.snapshot().1
+    }
+
+    /// The generation of the active configuration, which every reload that
+    /// applies changes increments. Consumers that remember the generation
+    /// they last acted on can tell whether they missed a change.
+    pub fn generation(&self) -> u64 {
+        self.snapshot().0
+    }
+
+    /// The active configuration along with its generation, read together.
+    pub fn snapshot(&self) -> (u64, Arc<MagicBlockParams>) {
+        let current = self
+            .shared
+            .current
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        (current.generation, Arc::clone(&current.params))
+    }
+
+    /// Subscribes to the reloads of the configuration: every reload that
+    /// applies a change or is rejected sends a [`ConfigEvent`] to the
+    /// returned receiver, e.g. for a subsystem to wait on in a thread of its
+    /// own.
+    pub fn subscribe(&self) -> Receiver<ConfigEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(sender);
+        receiver
+    }
+
+    /// Watches the active configuration: unlike [`ConfigHandle::subscribe`],
+    /// which queues every event, the returned watch only ever holds the
+    /// latest configuration, so a slow consumer skips the ones it missed
+    /// instead of falling behind.
+    pub fn watch(&self) -> ConfigWatch {
+        ConfigWatch {
+            shared: Arc::clone(&self.shared),
+            seen: self.generation(),
+        }
+    }
+
+    /// Like [`ConfigHandle::watch`], for async code: the returned receiver
+    /// holds the latest active configuration, and its `changed` resolves
+    /// once a reload applies changes, without a thread to block on.
+    #[cfg(feature = "tokio")]
+    pub fn watch_async(&self) -> tokio::sync::watch::Receiver<Arc<MagicBlockParams>> {
+        self.shared.latest.subscribe()
+    }
+
+    /// Re-reads every layer and runs every check of a regular load on the
+    /// result, except for the `--preflight` port checks, as the ports are
+    /// in use by then. If the result is valid, the changed keys that can
+    /// take effect without a restart are applied, see [`is_reloadable`],
+    /// while the other keys keep their old values until the next restart.
+    /// If it is invalid, the previous configuration is kept and the
+    /// problems are returned.
+    pub fn reload(&self) -> Result<ReloadOutcome, ConfigError> {
+        let patches = self
+            .shared
+            .patches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        self.reload_with(&patches)
+    }
+
+    /// Patches the running configuration with `overrides`, by nested or
+    /// dotted key, e.g. `{"commit.compute-unit-price": 5000}`. Patches sit
+    /// above every other layer and are kept across reloads, until the next
+    /// restart. Only reloadable keys can be patched, see [`is_reloadable`].
+    ///
+    /// The patched configuration is checked like a reload. If it is invalid,
+    /// nothing changes and the problems are returned.
+    pub fn patch(&self, overrides: Dict) -> Result<ReloadOutcome, ConfigError> {
+        let mut patch = Figment::new();
+        for (key, value) in overrides {
+            patch = patch.merge(Serialized::default(&key, value));
+        }
+        let patch = patch.data()?.remove(&Profile::Default).unwrap_or_default();
+        let errors: Vec<_> = sources::leaf_keys(&patch)
+            .into_iter()
+            .filter(|key| !is_reloadable(key))
+            .map(|key| ValidationError::new(key, "requires a restart, and can't be patched"))
+            .collect();
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(ConfigReport {
+                errors,
+                warnings: Vec::new(),
+            }));
+        }
+
+        let mut patches = self
+            .shared
+            .patches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let merged = Figment::from(Serialized::defaults(&*patches))
+            .merge(Serialized::defaults(patch))
+            .data()?
+            .remove(&Profile::Default)
+            .unwrap_or_default();
+        let outcome = self.reload_with(&merged)?;
+        *patches = merged;
+        Ok(outcome)
+    }
+
+    /// Explains where every key of the configuration comes from, with the
+    /// patches applied so far. The sources are read anew, so keys changed
+    /// since that require a restart show their new values.
+    pub fn provenance(&self) -> Result<Vec<Explanation>, ConfigError> {
+        let patches = self
+            .shared
+            .patches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        (self.shared.builder)()
+            .with_stdin(self.shared.stdin.clone())
+            .with_runtime_overrides(patches.clone())
+            .provenance(self.shared.args.clone())
+    }
+
+    /// Reloads with `patches` applied on top of the sources.
+    fn reload_with(&self, patches: &Dict) -> Result<ReloadOutcome, ConfigError> {
+        let new = (self.shared.builder)()
+            .with_stdin(self.shared.stdin.clone())
+            .without_preflight()
+            .with_runtime_overrides(patches.clone())
+            .try_build(self.shared.args.clone());
+        let mut current = self
+            .shared
+            .current
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        // Subscribers are notified before the lock is released, so that they
+        // see the events of concurrent reloads in order.
+        let new = match new {
+            Ok(new) => new,
+            Err(error) => {
+                let report = match &error {
+                    ConfigError::Invalid(report) => report.clone(),
+                    ConfigError::Figment(error) => ConfigReport::from((**error).clone()),
+                };
+                self.notify(ConfigEvent::Rejected(ReloadRejected {
+                    generation: current.generation,
+                    report,
+                }));
+                return Err(error);
+            }
+        };
+        let (applied, restart_required): (Vec<_>, Vec<_>) = current
+            .params
+            .diff(&new)
+            .changes
+            .into_iter()
+            .partition(|change| is_reloadable(change.key()));
+        if !applied.is_empty() {
+            let mut active = MagicBlockParams::clone(&current.params);
+            for (_, apply) in RELOADABLE {
+                apply(&mut active, &new);
+            }
+            // Reloadable keys now have the values, and sources, of the reload.
+            active.provenance.retain(|key, _| !is_reloadable(key));
+            let reloaded = new.provenance.into_iter();
+            active
+                .provenance
+                .extend(reloaded.filter(|(key, _)| is_reloadable(key)));
+            let active = Arc::new(active);
+            let previous = std::mem::replace(&mut current.params, Arc::clone(&active));
+            current.generation += 1;
+            *self
+                .shared
+                .generation
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = current.generation;
+            self.shared.published.notify_all();
+            #[cfg(feature = "tokio")]
+            self.shared.latest.send_replace(Arc::clone(&active));
+            self.notify(ConfigEvent::Changed(ConfigChange {
+                generation: current.generation,
+                previous,
+                current: active,
+                changes: applied.clone(),
+            }));
+        }
+        Ok(ReloadOutcome {
+            generation: current.generation,
+            applied,
+            restart_required,
+        })
+    }
+
+    /// Sends `event` to every subscriber, forgetting those that are gone.
+    fn notify(&self, event: ConfigEvent) {
+        let mut subscribers = self
+            .shared
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Reloads the configuration every `config-refresh-interval` from a
+    /// dedicated thread, e.g. to follow files fetched from a URL or a custom
+    /// provider backed by a config service, and calls `on_reload` with the
+    /// outcome of each reload. Waits are randomly varied by up to 10%, and
+    /// double with every consecutive reload whose sources can't be read, up
+    /// to 16 times the interval.
+    ///
+    /// Returns `false` without starting anything if no interval is set. The
+    /// thread stops once every clone of the handle is dropped.
+    pub fn refresh_periodically(
+        &self,
+        on_reload: impl Fn(Result<ReloadOutcome, ConfigError>) + Send + 'static,
+    ) -> std::io::Result<bool> {
+        let Some(interval) = self.current().config_refresh_interval else {
+            return Ok(false);
+        };
+        let shared = Arc::downgrade(&self.shared);
+        std::thread::Builder::new()
+            .name("config-refresh".into())
+            .spawn(move || {
+                let mut failures = 0;
+                loop {
+                    let backoff = 2u32.saturating_pow(failures).min(MAX_REFRESH_BACKOFF);
+                    std::thread::sleep(jittered(interval.saturating_mul(backoff)));
+                    let Some(shared) = shared.upgrade() else {
+                        return;
+                    };
+                    let result = ConfigHandle { shared }.reload();
+                    failures = match result {
+                        Err(ConfigError::Figment(_)) => failures.saturating_add(1),
+                        _ => 0,
+                    };
+                    on_reload(result);
+                }
+            })?;
+        Ok(true)
+    }
+
+    /// Installs a `SIGHUP` handler that reloads the configuration, see
+    /// [`ConfigHandle::reload`]. Reloads run on a dedicated thread, which
+    /// calls `on_reload` with the outcome of each reload, or the problems
+    /// that got it rejected.
+    ///
+    /// Only one handler can be installed per process, and none over a
+    /// `SIGHUP` handler installed by other code, which is kept.
+    #[cfg(unix)]
+    pub fn reload_on_sighup(
+        &self,
+        on_reload: impl Fn(Result<ReloadOutcome, ConfigError>) + Send + 'static,
+    ) -> std::io::Result<()> {
+        let signals = sighup::install()?;
+        let handle = self.clone();
+        std::thread::Builder::new()
+            .name("config-reload".into())
+            .spawn(move || {
+                for () in signals {
+                    on_reload(handle.reload());
+                }
+            })?;
+        Ok(())
+    }
+}
+
+/// The latest active configuration of a [`ConfigHandle`], see
+/// [`ConfigHandle::watch`].
+#[derive(Clone)]
+pub struct ConfigWatch {
+    shared: Arc<Shared>,
+    /// The generation last returned.
+    seen: u64,
+}
+
+impl ConfigWatch {
+    /// The active configuration, which is marked as seen.
+    pub fn borrow_and_update(&mut self) -> Arc<MagicBlockParams> {
+        let (generation, params) = ConfigHandle {
+            shared: Arc::clone(&self.shared),
+        }
+        .snapshot();
+        self.seen = generation;
+        params
+    }
+
+    /// Whether a configuration newer than the last one returned is active.
+    pub fn has_changed(&self) -> bool {
+        *self
+            .shared
+            .generation
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            > self.seen
+    }
+
+    /// Blocks until a configuration newer than the last one returned is
+    /// active, and returns it. If several reloads applied changes in the
+    /// meantime, only the latest configuration is returned.
+    pub fn changed(&mut self) -> Arc<MagicBlockParams> {
+        let generation = self
+            .shared
+            .generation
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let seen = self.seen;
+        // Reloads lock the generation while holding the active configuration.
+        drop(
+            self.shared
+                .published
+                .wait_while(generation, |generation| *generation <= seen)
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+        self.borrow_and_update()
+    }
+
+    /// Like [`ConfigWatch::changed`], giving up after `timeout`.
+    pub fn changed_timeout(&mut self, timeout: Duration) -> Option<Arc<MagicBlockParams>> {
+        let generation = self
+            .shared
+            .generation
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let seen = self.seen;
+        let (generation, result) = self
+            .shared
+            .published
+            .wait_timeout_while(generation, timeout, |generation| *generation <= seen)
+            .unwrap_or_else(|e| e.into_inner());
+        // Reloads lock the generation while holding the active configuration.
+        drop(generation);
+        (!result.timed_out()).then(|| self.borrow_and_update())
+    }
+}
+
+/// Varies `wait` randomly by up to [`REFRESH_JITTER`] either way.
+fn jittered(wait: Duration) -> Duration {
+    // Every `RandomState` is randomly seeded, which is random enough here.
+    let random = RandomState::new().hash_one(()) as f64 / u64::MAX as f64;
+    wait.mul_f64(1.0 + REFRESH_JITTER * (2.0 * random - 1.0))
+}
+
+/// Delivery of `SIGHUP` through a pipe, as signal handlers can do little
+/// more than write to one.
+#[cfg(unix)]
+mod sighup {
+    use std::{
+        fs::File,
+        io::{self, Read},
+        os::fd::FromRawFd,
+        sync::atomic::{AtomicI32, Ordering},
+    };
+
+    /// The write end of the pipe, or -1 before the handler is installed.
+    static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+    #[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+    use libc::__errno as errno_location;
+    #[cfg(any(target_os = "linux", target_os = "emscripten", target_os = "hurd"))]
+    use libc::__errno_location as errno_location;
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    use libc::__error as errno_location;
+
+    extern "C" fn on_signal(_: libc::c_int) {
+        // The interrupted code may be about to read `errno`, which a failed
+        // `write` would overwrite.
+        // SAFETY: the location of `errno` is valid for the whole thread.
+        let errno = unsafe { *errno_location() };
+        let fd = PIPE.load(Ordering::Relaxed);
+        // Signals arriving while the pipe is full are already pending.
+        // SAFETY: `write` is async-signal-safe and the buffer outlives it.
+        let _ = unsafe { libc::write(fd, [0u8].as_ptr().cast(), 1) };
+        // SAFETY: as above.
+        unsafe { *errno_location() = errno };
+    }
+
+    /// Yields once for every `SIGHUP` received, or for several received in
+    /// quick succession.
+    pub(super) struct Signals(File);
+
+    impl Iterator for Signals {
+        type Item = ();
+
+        fn next(&mut self) -> Option<()> {
+            self.0.read_exact(&mut [0]).ok()
+        }
+    }
+
+    /// Creates the pipe and installs the handler, unless this or another
+    /// `SIGHUP` handler is installed already. Nothing is left behind on
+    /// failure.
+    pub(super) fn install() -> io::Result<Signals> {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for both ends of the pipe.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let [read, write] = fds;
+        // SAFETY: `read` is a freshly created descriptor owned by nothing
+        // else, and closed when `signals` is dropped.
+        let signals = Signals(unsafe { File::from_raw_fd(read) });
+        // SAFETY: likewise for `write`, which the handler only uses once it
+        // is published in `PIPE`.
+        let write_end = unsafe { File::from_raw_fd(write) };
+        for fd in fds {
+            // SAFETY: `fd` is open.
+            unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+        }
+        // A full pipe must not block the handler.
+        // SAFETY: `write` is open.
+        unsafe { libc::fcntl(write, libc::F_SETFL, libc::O_NONBLOCK) };
+        if PIPE
+            .compare_exchange(-1, write, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(already_installed());
+        }
+        // SAFETY: `sigaction` is plain data, for which zeroes are valid.
+        let mut previous: libc::sigaction = unsafe { std::mem::zeroed() };
+        // SAFETY: the action is fully initialized and `on_signal` only
+        // makes async-signal-safe calls.
+        let installed = unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(libc::SIGHUP, &action, &mut previous)
+        };
+        let error = if installed != 0 {
+            io::Error::last_os_error()
+        } else if ![libc::SIG_DFL, libc::SIG_IGN].contains(&previous.sa_sigaction) {
+            // Someone else handles `SIGHUP`, whose handler is put back.
+            // SAFETY: `previous` is the action just replaced.
+            unsafe { libc::sigaction(libc::SIGHUP, &previous, std::ptr::null_mut()) };
+            already_installed()
+        } else {
+            // The write end stays open for as long as the handler may run.
+            std::mem::forget(write_end);
+            return Ok(signals);
+        };
+        PIPE.store(-1, Ordering::SeqCst);
+        Err(error)
+    }
+
+    fn already_installed() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "a SIGHUP handler is already installed",
+        )
+    }
+}
@@ -1,22 +1,340 @@
-use crate::consts;
+use crate::types::{ConnectionOptions, HumanDuration, Secret, SecretRef, SerdePubkeyList};
+use crate::{consts, secrets};
 use derive_more::Display;
-use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DisplayFromStr};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{formats::PreferOne, serde_as, DeserializeFromStr, OneOrMany};
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use url::Url;
+use zeroize::Zeroizing;
 
 /// A connection to one or more remote clusters.
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde_as]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case", untagged)]
 pub enum RemoteCluster {
     Single(Remote),
+    /// An array of remotes, or a comma-separated string of URLs and
+    /// aliases, e.g. `mainnet,https://backup.example.com`.
     Multiple(Vec<Remote>),
+    /// The `default` remotes, with some operations sent elsewhere, e.g.
+    /// `[remote.roles]` with `clone = "helius"`.
+    Scoped {
+        #[serde_as(as = "OneOrMany<_, PreferOne>")]
+        #[cfg_attr(
+            feature = "schema",
+            schemars(
+                with = "serde_with::Schema<Vec<Remote>, OneOrMany<serde_with::Same, PreferOne>>"
+            )
+        )]
+        default: Vec<Remote>,
+        #[serde(default)]
+        roles: RemoteRoles,
+    },
+}
+
+impl RemoteCluster {
+    /// Every remote in the cluster, in the order given. Those of `[remotes]`
+    /// that `roles` refer to are left out.
+    pub fn remotes(&self) -> &[Remote] {
+        match self {
+            Self::Single(remote) => std::slice::from_ref(remote),
+            Self::Multiple(remotes)
+            | Self::Scoped {
+                default: remotes, ..
+            } => remotes.as_slice(),
+        }
+    }
+
+    pub(crate) fn remotes_mut(&mut self) -> &mut [Remote] {
+        match self {
+            Self::Single(remote) => std::slice::from_mut(remote),
+            Self::Multiple(remotes)
+            | Self::Scoped {
+                default: remotes, ..
+            } => remotes.as_mut_slice(),
+        }
+    }
+
+    /// The names of the `[remotes]` some operations use instead of the
+    /// cluster, none unless given.
+    pub fn roles(&self) -> &RemoteRoles {
+        static NONE: RemoteRoles = RemoteRoles {
+            clone: None,
+            commit: None,
+            subscribe: None,
+        };
+        match self {
+            Self::Scoped { roles, .. } => roles,
+            Self::Single(_) | Self::Multiple(_) => &NONE,
+        }
+    }
+
+    /// The authoritative remote: the one with `role = "primary"`, or else
+    /// the first JSON-RPC one not marked as a fallback.
+    pub fn primary(&self) -> Option<&Remote> {
+        self.primary_index().map(|i| &self.remotes()[i])
+    }
+
+    /// The JSON-RPC remotes to fail over to, in the order they are tried.
+    pub fn fallbacks(&self) -> impl Iterator<Item = &Remote> {
+        let primary = self.primary_index();
+        let remotes = self.remotes().iter().enumerate();
+        remotes
+            .filter(move |(i, remote)| Some(*i) != primary && !remote.is_grpc())
+            .map(|(_, remote)| remote)
+    }
+
+    /// The gRPC remotes streaming updates, in the order given.
+    pub fn grpc(&self) -> impl Iterator<Item = &Remote> {
+        self.remotes().iter().filter(|remote| remote.is_grpc())
+    }
+
+    fn primary_index(&self) -> Option<usize> {
+        let remotes = self.remotes();
+        remotes
+            .iter()
+            .position(|remote| remote.role() == Some(RemoteRole::Primary))
+            .or_else(|| {
+                remotes.iter().position(|remote| {
+                    !remote.is_grpc() && remote.role() != Some(RemoteRole::Fallback)
+                })
+            })
+    }
+
+    /// Every URL of every remote in the cluster.
+    pub fn urls(&self) -> impl Iterator<Item = &Url> {
+        self.remotes().iter().flat_map(|remote| match remote {
+            Remote::Unified(url) | Remote::Authenticated { url, .. } => vec![&url.url],
+            Remote::Disjointed { http, ws, .. } => vec![&http.url, &ws.url],
+            Remote::Grpc { endpoint, .. } => vec![&endpoint.url],
+        })
+    }
 }
 
+/// A single URL or alias, or several separated by commas, as given on the
+/// command line, e.g. `--remote mainnet,https://backup.example.com`.
 impl FromStr for RemoteCluster {
     type Err = url::ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        AliasedUrl::from_str(s).map(|url| Self::Single(Remote::Unified(url)))
+        Ok(parse_remotes(s)?.into_iter().collect())
+    }
+}
+
+/// Collects remotes in priority order: a single one is a [`RemoteCluster::Single`],
+/// any other number a [`RemoteCluster::Multiple`].
+impl FromIterator<Remote> for RemoteCluster {
+    fn from_iter<I: IntoIterator<Item = Remote>>(iter: I) -> Self {
+        let remotes: Vec<_> = iter.into_iter().collect();
+        match <[Remote; 1]>::try_from(remotes) {
+            Ok([remote]) => Self::Single(remote),
+            Err(remotes) => Self::Multiple(remotes),
+        }
+    }
+}
+
+fn parse_remotes(s: &str) -> Result<Vec<Remote>, url::ParseError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(|url| AliasedUrl::from_str(url).map(Remote::Unified))
+        .collect()
+}
+
+/// Accepts a URL or alias, or several separated by commas, e.g. from
+/// `MBV_REMOTE`, an array of remotes, or a table of a single remote or of
+/// `default` remotes and their `roles`.
+impl<'de> Deserialize<'de> for RemoteCluster {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = RemoteCluster;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a comma-separated string, an array of remotes or a table")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                RemoteList::visit(seq).map(RemoteCluster::Multiple)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let table = RemoteTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                table.into_cluster().map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// The `default` remotes of a cluster: a comma-separated string, an array
+/// of remotes, or a single one.
+#[derive(Debug, Default, PartialEq)]
+struct RemoteList(Vec<Remote>);
+
+impl RemoteList {
+    fn visit<'de, A: de::SeqAccess<'de>>(mut seq: A) -> Result<Vec<Remote>, A::Error> {
+        let mut remotes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(remote) = seq.next_element()? {
+            remotes.push(remote);
+        }
+        Ok(remotes)
+    }
+}
+
+impl<'de> Deserialize<'de> for RemoteList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = RemoteList;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a comma-separated string, an array of remotes or a remote")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                parse_remotes(s).map(RemoteList).map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                RemoteList::visit(seq).map(RemoteList)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let remote = Remote::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(RemoteList(vec![remote]))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Every key a remote or a cluster of remotes can be given as a table with.
+/// The kind of remote is told apart by its keys afterwards, rather than by
+/// trying each in turn, so that a mistyped value is reported along with its
+/// key, and a mistyped key is seen by strict mode.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct RemoteTable {
+    default: Option<RemoteList>,
+    roles: Option<RemoteRoles>,
+    url: Option<AliasedUrl>,
+    http: Option<AliasedUrl>,
+    ws: Option<AliasedUrl>,
+    endpoint: Option<AliasedUrl>,
+    role: Option<RemoteRole>,
+    health: Option<Box<HealthCheck>>,
+    rate_limit: Option<Box<RateLimit>>,
+    connection: Option<Box<ConnectionOptions>>,
+    proxy: Option<Box<RemoteProxy>>,
+    expected_genesis: Option<String>,
+    token: Option<SecretRef<Secret<String>>>,
+    api_key: Option<SecretRef<Secret<String>>>,
+    headers: Option<BTreeMap<String, SecretRef<Secret<String>>>>,
+    x_token: Option<SecretRef<Secret<String>>>,
+    filters: Option<Box<GrpcFilters>>,
+}
+
+impl RemoteTable {
+    /// The cluster the table describes: `default` remotes with `roles`, or
+    /// else a single remote.
+    fn into_cluster(mut self) -> Result<RemoteCluster, String> {
+        let roles = self.roles.take();
+        let Some(RemoteList(default)) = self.default.take() else {
+            return match roles {
+                Some(_) => Err("`roles` requires the `default` remotes".into()),
+                None => self.into_remote().map(RemoteCluster::Single),
+            };
+        };
+        if self != Self::default() {
+            return Err("`default` can't be combined with the keys of a single remote".into());
+        }
+        let roles = roles.unwrap_or_default();
+        Ok(RemoteCluster::Scoped { default, roles })
+    }
+
+    /// The remote the table describes, told apart by its `url`, its `http`
+    /// and `ws` URLs, or its gRPC `endpoint`.
+    fn into_remote(self) -> Result<Remote, String> {
+        if self.default.is_some() || self.roles.is_some() {
+            return Err("`default` and `roles` only apply to the `remote` cluster".into());
+        }
+        let json_rpc_key = [
+            ("role", self.role.is_some()),
+            ("health", self.health.is_some()),
+            ("rate-limit", self.rate_limit.is_some()),
+            ("expected-genesis", self.expected_genesis.is_some()),
+            ("token", self.token.is_some()),
+            ("api-key", self.api_key.is_some()),
+            ("headers", self.headers.is_some()),
+        ]
+        .into_iter()
+        .find_map(|(key, set)| set.then_some(key));
+        let grpc_key = [
+            ("x-token", self.x_token.is_some()),
+            ("filters", self.filters.is_some()),
+        ]
+        .into_iter()
+        .find_map(|(key, set)| set.then_some(key));
+        match (&self.endpoint, json_rpc_key, grpc_key) {
+            (Some(_), Some(key), _) => {
+                return Err(format!("`{key}` doesn't apply to a gRPC `endpoint`"));
+            }
+            (None, _, Some(key)) => {
+                return Err(format!("`{key}` only applies to a gRPC `endpoint`"));
+            }
+            _ => {}
+        }
+        let auth = Box::new(RemoteAuth {
+            token: self.token,
+            api_key: self.api_key,
+            headers: self.headers.unwrap_or_default(),
+        });
+        match (self.url, self.http, self.ws, self.endpoint) {
+            (None, None, None, Some(endpoint)) => Ok(Remote::Grpc {
+                endpoint,
+                x_token: self.x_token,
+                filters: self.filters,
+                connection: self.connection,
+                proxy: self.proxy,
+            }),
+            (Some(url), None, None, None) => Ok(Remote::Authenticated {
+                url,
+                role: self.role,
+                health: self.health,
+                rate_limit: self.rate_limit,
+                connection: self.connection,
+                proxy: self.proxy,
+                expected_genesis: self.expected_genesis,
+                auth,
+            }),
+            (None, Some(http), Some(ws), None) => Ok(Remote::Disjointed {
+                http,
+                ws,
+                role: self.role,
+                health: self.health,
+                rate_limit: self.rate_limit,
+                connection: self.connection,
+                proxy: self.proxy,
+                expected_genesis: self.expected_genesis,
+                auth,
+            }),
+            (None, Some(_), None, None) => Err("`http` requires `ws` as well".into()),
+            (None, None, Some(_), None) => Err("`ws` requires `http` as well".into()),
+            (None, None, None, None) => {
+                Err("missing the remote's `url`, `http` and `ws`, or `endpoint`".into())
+            }
+            _ => Err("only one of `url`, `http` and `ws`, or `endpoint` can be set".into()),
+        }
     }
 }
 
@@ -30,34 +348,639 @@ impl Default for RemoteCluster {
 
 /// A connection to a single remote node.
 #[serde_as]
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case", untagged)]
 pub enum Remote {
     /// A single URL for both HTTP and WebSocket connections.
-    Unified(#[serde_as(as = "DisplayFromStr")] AliasedUrl),
+    Unified(AliasedUrl),
     /// Separate URLs for HTTP and WebSocket connections.
     Disjointed {
-        #[serde_as(as = "DisplayFromStr")]
         http: AliasedUrl,
-        #[serde_as(as = "DisplayFromStr")]
         ws: AliasedUrl,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        role: Option<RemoteRole>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        health: Option<Box<HealthCheck>>,
+        #[serde(
+            default,
+            rename = "rate-limit",
+            skip_serializing_if = "Option::is_none"
+        )]
+        rate_limit: Option<Box<RateLimit>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connection: Option<Box<ConnectionOptions>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy: Option<Box<RemoteProxy>>,
+        /// Genesis hash of the cluster the remote must belong to.
+        #[serde(
+            default,
+            rename = "expected-genesis",
+            skip_serializing_if = "Option::is_none"
+        )]
+        expected_genesis: Option<String>,
+        #[serde(flatten)]
+        auth: Box<RemoteAuth>,
+    },
+    /// A single URL for both HTTP and WebSocket connections, with
+    /// credentials.
+    Authenticated {
+        url: AliasedUrl,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        role: Option<RemoteRole>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        health: Option<Box<HealthCheck>>,
+        #[serde(
+            default,
+            rename = "rate-limit",
+            skip_serializing_if = "Option::is_none"
+        )]
+        rate_limit: Option<Box<RateLimit>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connection: Option<Box<ConnectionOptions>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy: Option<Box<RemoteProxy>>,
+        /// Genesis hash of the cluster the remote must belong to.
+        #[serde(
+            default,
+            rename = "expected-genesis",
+            skip_serializing_if = "Option::is_none"
+        )]
+        expected_genesis: Option<String>,
+        #[serde(flatten)]
+        auth: Box<RemoteAuth>,
     },
+    /// A Yellowstone Geyser gRPC endpoint streaming account and transaction
+    /// updates instead of WebSocket subscriptions. It serves no JSON-RPC, so
+    /// the cluster needs another remote for requests.
+    Grpc {
+        endpoint: AliasedUrl,
+        /// Token sent as the `x-token` header, or a secret store reference.
+        #[serde(default, rename = "x-token", skip_serializing_if = "Option::is_none")]
+        x_token: Option<SecretRef<Secret<String>>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filters: Option<Box<GrpcFilters>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connection: Option<Box<ConnectionOptions>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy: Option<Box<RemoteProxy>>,
+    },
+}
+
+/// Accepts a URL or alias, or a table with the remote's `url`, its `http`
+/// and `ws` URLs, or its gRPC `endpoint`.
+impl<'de> Deserialize<'de> for Remote {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Remote;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a URL, an alias or a table")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map(Remote::Unified).map_err(E::custom)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let table = RemoteTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                table.into_remote().map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl Remote {
+    /// Whether the remote is a gRPC endpoint streaming updates, rather than
+    /// a JSON-RPC node.
+    pub fn is_grpc(&self) -> bool {
+        matches!(self, Self::Grpc { .. })
+    }
+
+    /// The credentials sent to the remote, empty unless given.
+    pub fn auth(&self) -> &RemoteAuth {
+        static NONE: RemoteAuth = RemoteAuth {
+            token: None,
+            api_key: None,
+            headers: BTreeMap::new(),
+        };
+        match self {
+            Self::Unified(_) | Self::Grpc { .. } => &NONE,
+            Self::Disjointed { auth, .. } | Self::Authenticated { auth, .. } => auth,
+        }
+    }
+
+    /// The role given to the remote, if any. A remote given as a URL alone
+    /// has none.
+    pub fn role(&self) -> Option<RemoteRole> {
+        match self {
+            Self::Unified(_) | Self::Grpc { .. } => None,
+            Self::Disjointed { role, .. } | Self::Authenticated { role, .. } => *role,
+        }
+    }
+
+    /// The limits the remote's provider sets, none unless given.
+    pub fn rate_limit(&self) -> RateLimit {
+        match self {
+            Self::Unified(_) | Self::Grpc { .. } => None,
+            Self::Disjointed { rate_limit, .. } | Self::Authenticated { rate_limit, .. } => {
+                rate_limit.as_deref().copied()
+            }
+        }
+        .unwrap_or_default()
+    }
+
+    /// The genesis hash the remote is pinned to with `expected-genesis`, if
+    /// any.
+    pub fn expected_genesis(&self) -> Option<&str> {
+        match self {
+            Self::Unified(_) | Self::Grpc { .. } => None,
+            Self::Disjointed {
+                expected_genesis, ..
+            }
+            | Self::Authenticated {
+                expected_genesis, ..
+            } => expected_genesis.as_deref(),
+        }
+    }
+
+    /// Checks `genesis_hash`, as the remote answered `getGenesisHash`,
+    /// against the one it is pinned to, so that a mistyped URL doesn't go
+    /// unnoticed until accounts are cloned from the wrong cluster. Any hash
+    /// passes for a remote that isn't pinned.
+    pub fn verify_genesis(&self, genesis_hash: &str) -> Result<(), GenesisMismatch> {
+        match self.expected_genesis() {
+            Some(expected) if expected != genesis_hash => Err(GenesisMismatch {
+                expected: expected.to_owned(),
+                actual: genesis_hash.to_owned(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// How clients connect to the remote, the defaults unless given.
+    pub fn connection(&self) -> ConnectionOptions {
+        match self {
+            Self::Unified(_) => None,
+            Self::Disjointed { connection, .. }
+            | Self::Authenticated { connection, .. }
+            | Self::Grpc { connection, .. } => connection.as_deref().copied(),
+        }
+        .unwrap_or_default()
+    }
+
+    /// The proxy the remote is reached through in place of
+    /// `[network.proxy]`, if it sets one, see
+    /// [`MagicBlockParams::proxy_for`](crate::MagicBlockParams::proxy_for).
+    pub fn proxy(&self) -> Option<&RemoteProxy> {
+        match self {
+            Self::Unified(_) => None,
+            Self::Disjointed { proxy, .. }
+            | Self::Authenticated { proxy, .. }
+            | Self::Grpc { proxy, .. } => proxy.as_deref(),
+        }
+    }
+
+    /// The host requests to the remote go to.
+    pub(crate) fn host(&self) -> Option<&str> {
+        match self {
+            Self::Unified(url) | Self::Authenticated { url, .. } => url.url.host_str(),
+            Self::Disjointed { http, .. } => http.url.host_str(),
+            Self::Grpc { endpoint, .. } => endpoint.url.host_str(),
+        }
+    }
+
+    /// The URL to send HTTP requests to, if the remote serves JSON-RPC. A
+    /// single `ws` or `wss` URL is mapped to `http` or `https`, see
+    /// [`Remote::ws_url`].
+    pub fn http_url(&self) -> Option<Url> {
+        match self {
+            Self::Unified(url) | Self::Authenticated { url, .. } => {
+                Some(switch_scheme(&url.url, false))
+            }
+            Self::Disjointed { http, .. } => Some(http.url.clone()),
+            Self::Grpc { .. } => None,
+        }
+    }
+
+    /// The URL to open WebSocket subscriptions on. For a single `http` or
+    /// `https` URL, this is the matching `ws` or `wss` URL. A local node
+    /// serves it on the next port, e.g. `ws://127.0.0.1:8900` for
+    /// `http://127.0.0.1:8899`, any other host on the same port.
+    pub fn ws_url(&self) -> Option<Url> {
+        match self {
+            Self::Unified(url) | Self::Authenticated { url, .. } => {
+                Some(switch_scheme(&url.url, true))
+            }
+            Self::Disjointed { ws, .. } => Some(ws.url.clone()),
+            Self::Grpc { .. } => None,
+        }
+    }
+
+    /// Every URL of the remote, to resolve `[remote-aliases]` names in.
+    pub(crate) fn urls_mut(&mut self) -> Vec<&mut AliasedUrl> {
+        match self {
+            Self::Unified(url) | Self::Authenticated { url, .. } => vec![url],
+            Self::Disjointed { http, ws, .. } => vec![http, ws],
+            Self::Grpc { endpoint, .. } => vec![endpoint],
+        }
+    }
+
+    /// How the remote's health is checked, the defaults unless given.
+    pub fn health(&self) -> HealthCheck {
+        match self {
+            Self::Unified(_) | Self::Grpc { .. } => None,
+            Self::Disjointed { health, .. } | Self::Authenticated { health, .. } => {
+                health.as_deref().copied()
+            }
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// How a single remote is reached, in place of `[network.proxy]`: through
+/// another proxy given by URL, or directly with `none`.
+#[derive(Clone, Debug, PartialEq, Eq, Display, DeserializeFromStr)]
+pub enum RemoteProxy {
+    /// Connect directly, even if `[network.proxy]` is set.
+    #[display("none")]
+    Direct,
+    /// Connect through the proxy at this URL.
+    #[display("{_0}")]
+    Via(Url),
+}
+
+// Redacts the password of the proxy, unless secrets are exposed.
+impl Serialize for RemoteProxy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Via(url) if !secrets::is_exposed() => {
+                serializer.collect_str(&secrets::redact_url(url.clone()))
+            }
+            proxy => serializer.collect_str(proxy),
+        }
+    }
+}
+
+impl FromStr for RemoteProxy {
+    type Err = url::ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::Direct),
+            url => Url::parse(url).map(Self::Via),
+        }
+    }
+}
+
+/// An error raised when a remote belongs to another cluster than the one
+/// its `expected-genesis` pins it to, see [`Remote::verify_genesis`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("genesis hash is {actual}, expected {expected}")]
+pub struct GenesisMismatch {
+    pub expected: String,
+    pub actual: String,
+}
+
+/// The updates a gRPC remote streams, everything unless given. An empty
+/// list doesn't restrict anything.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GrpcFilters {
+    /// Accounts to stream updates of.
+    pub accounts: SerdePubkeyList,
+    /// Programs whose accounts to stream updates of.
+    pub owners: SerdePubkeyList,
+    /// Whether to stream transactions as well as account updates.
+    pub transactions: bool,
+}
+
+/// How a remote's health is checked, set with `health = { ... }` next to its
+/// URL.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HealthCheck {
+    /// Time between two checks, e.g. `10s`.
+    pub interval: HumanDuration,
+    /// How long to wait for an answer, shorter than `interval`.
+    pub timeout: HumanDuration,
+    /// Consecutive failed checks after which the remote is unhealthy.
+    pub unhealthy_threshold: u32,
+    /// The RPC method called to check the remote.
+    pub method: HealthCheckMethod,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            interval: HumanDuration(consts::DEFAULT_HEALTH_CHECK_INTERVAL),
+            timeout: HumanDuration(consts::DEFAULT_HEALTH_CHECK_TIMEOUT),
+            unhealthy_threshold: consts::DEFAULT_UNHEALTHY_THRESHOLD,
+            method: HealthCheckMethod::default(),
+        }
+    }
+}
+
+/// Limits for clients of a remote to stay within a provider's plan, set
+/// with `rate-limit = { ... }` next to its URL. Unset limits are left to
+/// the provider.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RateLimit {
+    /// Sustained rate of HTTP requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<u32>,
+    /// Requests that can be sent at once above the sustained rate, by
+    /// default `requests-per-second`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    /// WebSocket subscriptions open at the same time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_subscriptions: Option<u32>,
+}
+
+impl RateLimit {
+    /// The burst size, defaulting to one second's worth of requests.
+    pub fn burst(&self) -> Option<u32> {
+        self.burst.or(self.requests_per_second)
+    }
+}
+
+/// The RPC method a health check calls.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum HealthCheckMethod {
+    /// `getHealth`, which fails while the node is behind the cluster.
+    #[default]
+    #[serde(rename = "getHealth")]
+    GetHealth,
+    /// `getSlot`, for providers that don't serve `getHealth`.
+    #[serde(rename = "getSlot")]
+    GetSlot,
+}
+
+/// `url` with the HTTP or WebSocket scheme, keeping it secure if it was.
+/// An explicit port of a local node is shifted along, as a validator serves
+/// WebSocket on the port after the HTTP one. Providers serve both on the
+/// same port, so any other host keeps it.
+fn switch_scheme(url: &Url, websocket: bool) -> Url {
+    let secure = matches!(url.scheme(), "https" | "wss");
+    let scheme = match (websocket, secure) {
+        (false, false) => "http",
+        (false, true) => "https",
+        (true, false) => "ws",
+        (true, true) => "wss",
+    };
+    let mut switched = url.clone();
+    // Unresolved aliases have no scheme to switch, which fails and leaves
+    // them as they are.
+    if url.scheme() == scheme || switched.set_scheme(scheme).is_err() {
+        return switched;
+    }
+    let Some(port) = url.port().filter(|_| is_loopback(url)) else {
+        return switched;
+    };
+    let port = match websocket {
+        true => port.checked_add(1),
+        false => port.checked_sub(1),
+    };
+    // At either end of the range, there is no next port to shift to.
+    if let Some(port) = port {
+        let _ = switched.set_port(Some(port));
+    }
+    switched
+}
+
+/// Whether `url` points to this machine.
+pub(crate) fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// The names of the `[remotes]` to use for some operations instead of the
+/// default cluster.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteRoles {
+    /// Where accounts are cloned from, e.g. a read-optimized provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone: Option<String>,
+    /// Where commit transactions are sent, e.g. a paid sender endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Where account updates are subscribed to, e.g. a gRPC remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscribe: Option<String>,
+}
+
+/// An operation that can be sent to a remote of its own, see
+/// [`RemoteRoles`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemotePurpose {
+    /// Cloning accounts from the base chain.
+    Clone,
+    /// Sending commit transactions.
+    Commit,
+    /// Subscribing to account updates.
+    Subscribe,
+}
+
+/// The part a remote plays in a cluster of several.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum RemoteRole {
+    /// The authoritative remote, used whenever it is healthy.
+    Primary,
+    /// A backup, used while the primary is failed over.
+    Fallback,
+}
+
+/// When to switch from the primary remote to a fallback and back.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct FailoverConfig {
+    /// Consecutive failed requests after which the primary is failed over.
+    pub error_threshold: u32,
+    /// How long to stay on the fallbacks before retrying the primary, e.g.
+    /// `30s`.
+    pub cooldown: HumanDuration,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            error_threshold: consts::DEFAULT_FAILOVER_ERROR_THRESHOLD,
+            cooldown: HumanDuration(consts::DEFAULT_FAILOVER_COOLDOWN),
+        }
+    }
+}
+
+/// Credentials for a remote behind an RPC provider such as Helius, Triton
+/// or QuickNode. Each value is given inline or as a secret reference, e.g.
+/// `env:HELIUS_API_KEY`, and is redacted when the configuration is printed.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteAuth {
+    /// A token sent as `Authorization: Bearer <token>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<SecretRef<Secret<String>>>,
+    /// An API key added to the URLs as the `api-key` query parameter, as
+    /// Helius expects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<SecretRef<Secret<String>>>,
+    /// Headers sent with every request, e.g. `x-token` for Triton.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, SecretRef<Secret<String>>>,
+}
+
+impl RemoteAuth {
+    /// Whether no credentials are given.
+    pub fn is_empty(&self) -> bool {
+        self.token.is_none() && self.api_key.is_none() && self.headers.is_empty()
+    }
+
+    /// `url` with the API key, if any, added to its query. Take care not to
+    /// log the result.
+    pub fn url(&self, url: &Url) -> Url {
+        let mut url = url.clone();
+        if let Some(api_key) = &self.api_key {
+            url.query_pairs_mut()
+                .append_pair("api-key", api_key.expose_secret());
+        }
+        url
+    }
+
+    /// The headers to send with every request, including the bearer token.
+    /// Take care not to log the values.
+    pub fn headers(&self) -> impl Iterator<Item = (&str, Zeroizing<String>)> {
+        let bearer = self.token.iter().map(|token| {
+            let value = format!("Bearer {}", token.expose_secret());
+            ("Authorization", Zeroizing::new(value))
+        });
+        let headers = self.headers.iter().map(|(name, value)| {
+            let value = value.expose_secret().clone();
+            (name.as_str(), Zeroizing::new(value))
+        });
+        bearer.chain(headers)
+    }
 }
 
 /// A URL that can be aliased with shortcuts like "mainnet".
-#[derive(Clone, Debug, Deserialize, Serialize, Display, PartialEq)]
-pub struct AliasedUrl(pub Url);
+///
+/// Any other name, such as `helius`, stands for an entry of
+/// `[remote-aliases]` and is kept as `alias:helius` until the configuration
+/// is assembled and the alias resolved.
+///
+/// It serializes as written, so an alias stays an alias, and a built-in
+/// cluster's URL is written as its alias. Two URLs are equal when they
+/// point to the same place, however they were written.
+#[derive(Clone, Debug, Display)]
+#[display("{url}")]
+pub struct AliasedUrl {
+    pub url: Url,
+    /// The alias the URL was given as, if any.
+    pub original: Option<String>,
+}
+
+impl AliasedUrl {
+    /// The `[remote-aliases]` name this URL stands for, if it is not
+    /// resolved yet.
+    pub fn alias(&self) -> Option<&str> {
+        (self.url.scheme() == consts::REMOTE_ALIAS_SCHEME).then(|| self.url.path())
+    }
+
+    /// The URL as written in the configuration: the alias it was given as,
+    /// the built-in alias of a well-known cluster, or else the URL itself.
+    pub fn as_written(&self) -> &str {
+        if let Some(original) = &self.original {
+            return original;
+        }
+        consts::BUILTIN_REMOTE_ALIASES
+            .iter()
+            .find(|alias| {
+                builtin_url(alias).and_then(|url| Url::parse(url).ok()) == Some(self.url.clone())
+            })
+            .copied()
+            .unwrap_or(self.url.as_str())
+    }
+}
+
+impl From<Url> for AliasedUrl {
+    fn from(url: Url) -> Self {
+        Self {
+            url,
+            original: None,
+        }
+    }
+}
+
+impl PartialEq for AliasedUrl {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
 
 impl FromStr for AliasedUrl {
     type Err = url::ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let url_str = match s {
-            "mainnet" => consts::MAINNET_URL,
-            "devnet" => consts::DEVNET_URL,
-            "testnet" => consts::TESTNET_URL,
-            "localhost" | "dev" => consts::LOCALHOST_URL,
-            custom => custom,
-        };
-        Url::parse(url_str).map(Self)
+        if let Some(url) = builtin_url(s) {
+            return Ok(Self {
+                url: Url::parse(url)?,
+                original: Some(s.to_owned()),
+            });
+        }
+        match Url::parse(s) {
+            Err(url::ParseError::RelativeUrlWithoutBase) if is_alias_name(s) => Ok(Self {
+                url: Url::parse(&format!("{}:{s}", consts::REMOTE_ALIAS_SCHEME))?,
+                original: Some(s.to_owned()),
+            }),
+            url => url.map(Self::from),
+        }
     }
 }
+
+impl Serialize for AliasedUrl {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_written())
+    }
+}
+
+impl<'de> Deserialize<'de> for AliasedUrl {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let url = String::deserialize(deserializer)?;
+        url.parse().map_err(de::Error::custom)
+    }
+}
+
+/// The URL of the built-in alias `name`.
+fn builtin_url(name: &str) -> Option<&'static str> {
+    match name {
+        "mainnet" => Some(consts::MAINNET_URL),
+        "devnet" => Some(consts::DEVNET_URL),
+        "testnet" => Some(consts::TESTNET_URL),
+        "localhost" | "dev" => Some(consts::LOCALHOST_URL),
+        _ => None,
+    }
+}
+
+/// Whether `name` can be a key of `[remote-aliases]`.
+pub(crate) fn is_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
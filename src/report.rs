@@ -0,0 +1,216 @@
+//! Collection of every problem found while assembling the configuration.
+
+use crate::{
+    consts, provenance, secrets, strict::UnknownKey, validate::ValidationError,
+    warning::ConfigWarning, MagicBlockParams,
+};
+use figment::{
+    error::{Actual, Kind},
+    providers::Serialized,
+    value::Value,
+    Figment,
+};
+use std::fmt::{self, Display};
+
+/// Upper bound on extraction attempts, each of which repairs one bad key.
+const MAX_EXTRACTION_ATTEMPTS: usize = 64;
+
+/// Every problem found while assembling the configuration, so operators can
+/// fix them all in one go.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigReport {
+    /// Problems that make the configuration unusable.
+    pub errors: Vec<ValidationError>,
+    /// Non-fatal problems, such as deprecated keys.
+    pub warnings: Vec<ConfigWarning>,
+}
+
+impl ConfigReport {
+    /// Whether the report has no errors. Warnings are allowed.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Keys that no field consumes, found outside strict mode. In strict
+    /// mode they are reported as errors instead.
+    pub fn unknown_keys(&self) -> impl Iterator<Item = &UnknownKey> {
+        self.warnings.iter().filter_map(|warning| match warning {
+            ConfigWarning::UnknownKey(unknown) => Some(unknown),
+            _ => None,
+        })
+    }
+}
+
+impl Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        if !self.errors.is_empty() {
+            write!(f, "invalid configuration:")?;
+            for error in &self.errors {
+                write!(f, "\n  - {error}")?;
+            }
+            separator = "\n";
+        }
+        for warning in &self.warnings {
+            write!(f, "{separator}warning: {warning}")?;
+            separator = "\n";
+        }
+        Ok(())
+    }
+}
+
+impl From<UnknownKey> for ValidationError {
+    fn from(unknown: UnknownKey) -> Self {
+        let mut message = "unknown key".to_owned();
+        if let Some(origin) = &unknown.origin {
+            message.push_str(&format!(" in {origin}"));
+        }
+        if let Some(suggestion) = &unknown.suggestion {
+            message.push_str(&format!(", did you mean `{suggestion}`?"));
+        }
+        Self::new(unknown.key, message)
+    }
+}
+
+/// Extracts [`MagicBlockParams`] from `figment`, recording every bad key in
+/// `errors` instead of stopping at the first one.
+///
+/// Each time extraction fails, the offending key, or else the closest section
+/// around it with a default, is reset to its default and extraction is
+/// retried. Returns `None` if an error can't be pinned to a key with a
+/// default, or comes back after the reset, in which case the remaining keys
+/// go unchecked.
+pub fn extract_all(
+    mut figment: Figment,
+    errors: &mut Vec<ValidationError>,
+) -> Option<MagicBlockParams> {
+    let defaults =
+        secrets::exposed(|| Figment::from(Serialized::defaults(MagicBlockParams::default())));
+    for _ in 0..MAX_EXTRACTION_ATTEMPTS {
+        // Secret references are only resolved once everything else
+        // extracts, rather than on every retry.
+        let extracted = secrets::unresolved(|| figment.extract::<MagicBlockParams>())
+            .and_then(|_| figment.extract());
+        let error = match extracted {
+            Ok(params) => return Some(params),
+            Err(error) => error,
+        };
+        let mut repaired = false;
+        for error in error {
+            let value = figment.find_value(&error.path.join(".")).ok();
+            let error = extraction_error(&error, value);
+            // The reset didn't take, e.g. for an entry of a map.
+            if errors.contains(&error) {
+                continue;
+            }
+            let key = error.key.clone();
+            errors.push(error);
+            // A missing field or an element of a list resets the closest
+            // section that has a default.
+            let mut section = Some(key.as_str()).filter(|key| !key.is_empty());
+            while let Some(key) = section {
+                if let Ok(default) = defaults.find_value(key) {
+                    figment = figment.merge(Serialized::default(key, default));
+                    repaired = true;
+                    break;
+                }
+                section = key.rsplit_once('.').map(|(parent, _)| parent);
+            }
+        }
+        if !repaired {
+            return None;
+        }
+    }
+    None
+}
+
+/// Reports the key of `error` separately from its message, which names the
+/// source that set the bad value, down to the line or environment variable
+/// where known, see [`provenance::Source`].
+impl From<&figment::Error> for ValidationError {
+    fn from(error: &figment::Error) -> Self {
+        extraction_error(error, None)
+    }
+}
+
+/// Like `ValidationError::from(error)`, also naming the offending `value`
+/// if the error message leaves it out. Secrets are redacted.
+fn extraction_error(error: &figment::Error, value: Option<Value>) -> ValidationError {
+    let key = error.path.join(".");
+    let mut message = if secrets::is_secret_key(&key) {
+        redacted_message(&error.kind)
+    } else {
+        let mut message = error.kind.to_string();
+        // These kinds already name the value.
+        if !matches!(
+            error.kind,
+            Kind::InvalidType(..) | Kind::InvalidValue(..) | Kind::UnknownVariant(..)
+        ) {
+            if let Some(value) = value.and_then(|value| toml::Value::try_from(value).ok()) {
+                message.push_str(&format!(", found {value}"));
+            }
+        }
+        message
+    };
+    if let Some(metadata) = &error.metadata {
+        let source = provenance::from_metadata(metadata, &key);
+        message.push_str(&format!(" (in {source})"));
+    }
+    ValidationError::new(key, message)
+}
+
+/// The message of `kind` with the value it names, if any, redacted.
+fn redacted_message(kind: &Kind) -> String {
+    let redacted = consts::REDACTED;
+    match kind {
+        Kind::InvalidType(actual, expected) => {
+            format!(
+                "invalid type: found {}, expected {expected}",
+                redacted_actual(actual)
+            )
+        }
+        Kind::InvalidValue(actual, expected) => {
+            format!(
+                "invalid value {}, expected {expected}",
+                redacted_actual(actual)
+            )
+        }
+        Kind::UnknownVariant(_, expected) => {
+            format!(
+                "unknown variant: found {redacted}, expected `{}`",
+                expected.join("`, `")
+            )
+        }
+        kind => format!("{kind}, found {redacted}"),
+    }
+}
+
+/// Describes `actual` by its type alone, e.g. `signed int ***REDACTED***`.
+fn redacted_actual(actual: &Actual) -> String {
+    let kind = match actual {
+        Actual::Bool(_) => "bool",
+        Actual::Unsigned(_) => "unsigned int",
+        Actual::Signed(_) => "signed int",
+        Actual::Float(_) => "float",
+        Actual::Char(_) => "char",
+        Actual::Str(_) => "string",
+        Actual::Bytes(_) => "bytes",
+        Actual::Other(_) => return consts::REDACTED.to_owned(),
+        // The others carry no value.
+        actual => return actual.to_string(),
+    };
+    format!("{kind} {}", consts::REDACTED)
+}
+
+/// A source that can't be read at all, reported like any other problem.
+impl From<figment::Error> for ConfigReport {
+    fn from(error: figment::Error) -> Self {
+        Self {
+            errors: error
+                .into_iter()
+                .map(|e| ValidationError::from(&e))
+                .collect(),
+            warnings: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,537 @@
+//! JSON Schema of the configuration, for editor autocompletion and for
+//! validating configuration files in CI.
+
+use crate::{
+    builder,
+    config::{BlockSize, MetricsConfig},
+    consts,
+    remote::{AliasedUrl, RemoteProxy},
+    secrets::{self, SeedPhraseRef},
+    sources, template,
+    types::{
+        BindAddress, BoundedU64, ByteSize, ExpandedPath, HumanDuration, Lamports, Percent,
+        SecretRef, SerdeKeypair, SerdePubkey, SerdePubkeyList, SerdePubkeySet,
+    },
+    MagicBlockParams,
+};
+use clap::CommandFactory;
+use figment::{providers::Serialized, Figment, Profile, Provider};
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, NumberValidation, RootSchema, Schema, SchemaObject, SingleOrVec},
+    schema_for, JsonSchema, Map,
+};
+use toml::{Table, Value};
+
+impl MagicBlockParams {
+    /// A JSON Schema covering every section of the configuration file, with
+    /// the built-in defaults.
+    pub fn json_schema() -> RootSchema {
+        schema_for!(MagicBlockParams)
+    }
+}
+
+impl MagicBlockParams {
+    /// A Markdown reference of every key in the [JSON Schema](Self::json_schema),
+    /// with its type, default value, environment variable and command-line
+    /// flag, one table per section.
+    pub fn reference() -> String {
+        let root = Self::json_schema();
+        let definitions = &root.definitions;
+        // The built-in default keypair is well known, not a secret.
+        let defaults = secrets::exposed(|| Value::try_from(Self::default()))
+            .ok()
+            .and_then(|value| value.try_into::<Table>().ok())
+            .unwrap_or_default();
+        let flags = flags();
+        let mut sections = vec![("", "Keys outside of any section.".to_owned(), &root.schema)];
+        let mut nested = Vec::new();
+        collect_sections(&root.schema, definitions, "", &mut nested);
+        sections.extend(
+            nested
+                .iter()
+                .map(|(key, doc, schema)| (key.as_str(), doc.clone(), *schema)),
+        );
+        let mut out = String::from("# Configuration Reference\n");
+        for (section, doc, schema) in sections {
+            match section {
+                "" => out.push_str("\n## Top Level\n\n"),
+                section => out.push_str(&format!("\n## `[{section}]`\n\n")),
+            }
+            if !doc.is_empty() {
+                out.push_str(&format!("{doc}\n\n"));
+            }
+            out.push_str("| Key | Type | Default | Environment variable | Flag | Description |\n");
+            out.push_str("|-----|------|---------|----------------------|------|-------------|\n");
+            for (name, property) in properties(schema) {
+                let key = match section {
+                    "" => name.clone(),
+                    section => format!("{section}.{name}"),
+                };
+                if consts::LOADER_KEYS.contains(&key.as_str())
+                    || section_of(property, definitions).is_some()
+                {
+                    continue;
+                }
+                // Keys in sections that are unset by default, like
+                // `validator.signer`, still have defaults of their own.
+                let value = template::lookup(&defaults, &key).cloned().or_else(|| {
+                    let Schema::Object(object) = property else {
+                        return None;
+                    };
+                    let default = object.metadata.as_ref()?.default.clone()?;
+                    Value::try_from(default).ok()
+                });
+                let kind = value
+                    .as_ref()
+                    .map(value_type_name)
+                    .or_else(|| type_name(property, definitions))
+                    .unwrap_or("");
+                let default = match value {
+                    Some(_) if secrets::is_secret_key(&key) => "*built-in*".to_owned(),
+                    Some(value) => format!("`{value}`"),
+                    None => "*not set*".to_owned(),
+                };
+                let var = format!(
+                    "{}{}",
+                    consts::ENV_VAR_PREFIX,
+                    key.replace(['.', '-'], "_").to_ascii_uppercase()
+                );
+                // Keys nested in optional sections may be out of the
+                // environment's reach, see `sources::env_key_path`.
+                let env = match sources::env_key_path(&var) {
+                    Some(path) if path == key => format!("`{var}`"),
+                    _ => String::new(),
+                };
+                let flag = flags
+                    .iter()
+                    .find(|(flagged, _)| *flagged == key)
+                    .map_or(String::new(), |(_, flag)| format!("`--{flag}`"));
+                out.push_str(&format!(
+                    "| `{key}` | {kind} | {default} | {env} | {flag} | {} |\n",
+                    description(property, definitions)
+                        .replace('\n', " ")
+                        .replace('|', "\\|")
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Every section below `schema`, depth first, as its dotted key, its
+/// description and its schema.
+fn collect_sections<'a>(
+    schema: &'a SchemaObject,
+    definitions: &'a Map<String, Schema>,
+    prefix: &str,
+    sections: &mut Vec<(String, String, &'a SchemaObject)>,
+) {
+    for (name, property) in properties(schema) {
+        if let Some(section) = section_of(property, definitions) {
+            let key = format!("{prefix}{name}");
+            // The section's own doc comment rather than the field's, which
+            // may be about its command-line flags.
+            let doc = section
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.description.as_deref())
+                .unwrap_or_else(|| description(property, definitions))
+                .replace('\n', " ");
+            sections.push((key.clone(), doc, section));
+            collect_sections(section, definitions, &format!("{key}."), sections);
+        }
+    }
+}
+
+/// The properties of an object schema, in order.
+fn properties(schema: &SchemaObject) -> impl Iterator<Item = (&String, &Schema)> {
+    schema.object.iter().flat_map(|object| &object.properties)
+}
+
+/// The schema `schema` stands for: the definition it refers to, or its
+/// first alternative other than `null`, if any.
+fn next<'a>(schema: &'a SchemaObject, definitions: &'a Map<String, Schema>) -> Option<&'a Schema> {
+    if let Some(reference) = &schema.reference {
+        return definitions.get(reference.trim_start_matches("#/definitions/"));
+    }
+    let subschemas = schema.subschemas.as_deref()?;
+    let variants = subschemas.all_of.as_ref().or(subschemas.any_of.as_ref())?;
+    variants.iter().find(|variant| {
+        !matches!(
+            variant,
+            Schema::Object(SchemaObject { instance_type: Some(SingleOrVec::Single(kind)), .. })
+                if **kind == InstanceType::Null
+        )
+    })
+}
+
+/// The object schema `schema` stands for, if it has properties of its own
+/// and so is a section of the configuration file. Values that can also be
+/// given as a table, like bind addresses, are left out.
+fn section_of<'a>(
+    schema: &'a Schema,
+    definitions: &'a Map<String, Schema>,
+) -> Option<&'a SchemaObject> {
+    let Schema::Object(object) = schema else {
+        return None;
+    };
+    match next(object, definitions) {
+        Some(next) => section_of(next, definitions),
+        None => properties(object).next().map(|_| object),
+    }
+}
+
+/// The description of `schema`, or of the schema it stands for.
+fn description<'a>(schema: &'a Schema, definitions: &'a Map<String, Schema>) -> &'a str {
+    let Schema::Object(object) = schema else {
+        return "";
+    };
+    match object
+        .metadata
+        .as_ref()
+        .and_then(|m| m.description.as_deref())
+    {
+        Some(description) => description,
+        None => next(object, definitions).map_or("", |next| description(next, definitions)),
+    }
+}
+
+/// The TOML type of `schema`, for keys without a default.
+fn type_name(schema: &Schema, definitions: &Map<String, Schema>) -> Option<&'static str> {
+    let Schema::Object(object) = schema else {
+        return None;
+    };
+    let kind = match &object.instance_type {
+        Some(SingleOrVec::Single(kind)) => **kind,
+        Some(SingleOrVec::Vec(kinds)) => *kinds.iter().find(|kind| **kind != InstanceType::Null)?,
+        None => return type_name(next(object, definitions)?, definitions),
+    };
+    match kind {
+        InstanceType::String => Some("string"),
+        InstanceType::Integer => Some("integer"),
+        InstanceType::Number => Some("float"),
+        InstanceType::Boolean => Some("boolean"),
+        InstanceType::Array => Some("array"),
+        InstanceType::Object => Some("table"),
+        InstanceType::Null => None,
+    }
+}
+
+/// The TOML type of `value`, for keys with a default.
+fn value_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Integer(_) => "integer",
+        Value::Float(_) => "float",
+        Value::Boolean(_) => "boolean",
+        Value::Datetime(_) => "datetime",
+        Value::Array(_) => "array",
+        Value::Table(_) => "table",
+    }
+}
+
+/// The dotted key each command-line flag sets, as `(key, flag)` pairs.
+fn flags() -> Vec<(String, String)> {
+    let defaults = secrets::exposed(|| {
+        Figment::from(Serialized::defaults(MagicBlockParams::default())).data()
+    })
+    .ok()
+    .and_then(|mut data| data.remove(&Profile::Default))
+    .unwrap_or_default();
+    MagicBlockParams::command()
+        .get_arguments()
+        .filter_map(|arg| {
+            let flag = arg.get_long()?;
+            let id = arg.get_id().as_str().replace('_', "-");
+            let (key, _) = builder::find_key(&defaults, &id, "")?;
+            Some((key, flag.to_owned()))
+        })
+        .collect()
+}
+
+/// The `[metrics]` section, or the listen address alone as its shorthand.
+pub(crate) fn metrics_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject::default();
+    schema.subschemas().any_of = Some(vec![
+        gen.subschema_for::<MetricsConfig>(),
+        gen.subschema_for::<BindAddress>(),
+    ]);
+    schema.into()
+}
+
+/// A string schema with the given `format`, if any, and description.
+fn string_schema(format: Option<&str>, description: &str) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        format: format.map(str::to_owned),
+        ..Default::default()
+    };
+    schema.metadata().description = Some(description.to_owned());
+    schema.into()
+}
+
+impl JsonSchema for AliasedUrl {
+    fn schema_name() -> String {
+        "AliasedUrl".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            None,
+            "A URL, one of the aliases `mainnet`, `devnet`, `testnet`, `localhost` and `dev`, or a key of `[remote-aliases]`.",
+        )
+    }
+}
+
+impl JsonSchema for RemoteProxy {
+    fn schema_name() -> String {
+        "RemoteProxy".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            None,
+            "URL of a proxy with scheme `http`, `https`, `socks5` or `socks5h`, or `none` to connect directly.",
+        )
+    }
+}
+
+impl JsonSchema for BindAddress {
+    fn schema_name() -> String {
+        "BindAddress".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject::default();
+        schema.subschemas().any_of = Some(vec![
+            string_schema(
+                None,
+                "An address and port, e.g. `0.0.0.0:8899`, a hostname and port, `:8899` for the default interface, or a Unix domain socket such as `unix:/run/magicblock/rpc.sock?mode=0660`.",
+            ),
+            gen.subschema_for::<u16>(),
+            dual_stack_schema(gen),
+        ]);
+        schema.into()
+    }
+}
+
+impl JsonSchema for HumanDuration {
+    fn schema_name() -> String {
+        "HumanDuration".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(None, "A duration such as `400ms`, `2s` or `1h 30m`.")
+    }
+}
+
+impl JsonSchema for SerdeKeypair {
+    fn schema_name() -> String {
+        "Keypair".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(None, "A keypair encoded in Base58.")
+    }
+}
+
+impl JsonSchema for ExpandedPath {
+    fn schema_name() -> String {
+        "ExpandedPath".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            None,
+            "A path, in which `~`, `$VAR` and `${VAR}` are expanded from the environment.",
+        )
+    }
+}
+
+impl JsonSchema for SerdePubkey {
+    fn schema_name() -> String {
+        "Pubkey".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(None, "A public key encoded in Base58.")
+    }
+}
+
+impl JsonSchema for SerdePubkeyList {
+    fn schema_name() -> String {
+        "PubkeyList".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        pubkeys_schema(gen)
+    }
+}
+
+impl JsonSchema for SerdePubkeySet {
+    fn schema_name() -> String {
+        "PubkeySet".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        pubkeys_schema(gen)
+    }
+}
+
+/// An array of public keys, or the same keys in a comma-separated string.
+fn pubkeys_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject::default();
+    schema.subschemas().any_of = Some(vec![
+        gen.subschema_for::<Vec<SerdePubkey>>(),
+        string_schema(None, "Public keys encoded in Base58, separated by commas."),
+    ]);
+    schema.into()
+}
+
+/// Secret references are plain strings, whatever the resolved value, or
+/// seed phrase tables.
+impl<T> JsonSchema for SecretRef<T> {
+    fn schema_name() -> String {
+        "SecretRef".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject::default();
+        schema.subschemas().any_of = Some(vec![
+            string_schema(
+                None,
+                "An inline value, or a reference such as `aws-ssm:/path`, `env:NAME`, `file:/path` or `keyring:service/user`.",
+            ),
+            gen.subschema_for::<Vec<u8>>(),
+            gen.subschema_for::<SeedPhraseRef>(),
+        ]);
+        schema.into()
+    }
+}
+
+/// An IPv6 bind address given as a table, choosing whether it also accepts
+/// IPv4 connections.
+fn dual_stack_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        ..Default::default()
+    };
+    let object = schema.object();
+    object.properties.insert(
+        "addr".to_owned(),
+        string_schema(None, "An IPv6 address and port, e.g. `[::]:8899`."),
+    );
+    let mut v6_only = gen.subschema_for::<bool>().into_object();
+    v6_only.metadata().description = Some(
+        "Whether only IPv6 connections are accepted (`IPV6_V6ONLY`), instead of the OS default."
+            .to_owned(),
+    );
+    object
+        .properties
+        .insert("v6-only".to_owned(), v6_only.into());
+    object.required.insert("addr".to_owned());
+    object.additional_properties = Some(Box::new(false.into()));
+    schema.into()
+}
+
+/// Sizes are plain numbers of bytes or human-readable strings.
+impl<const MIN: u64, const MAX: u64> JsonSchema for ByteSize<MIN, MAX> {
+    fn schema_name() -> String {
+        format!("ByteSize_{MIN}_{MAX}")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject::default();
+        schema.subschemas().any_of = Some(vec![
+            gen.subschema_for::<BoundedU64<MIN, MAX>>(),
+            string_schema(
+                None,
+                "A size such as `512MiB` or `8GB`, in B, kB, MB, GB, TB, KiB, MiB, GiB or TiB.",
+            ),
+        ]);
+        schema.into()
+    }
+}
+
+impl<const MAX: u64> JsonSchema for Lamports<MAX> {
+    fn schema_name() -> String {
+        format!("Lamports_{MAX}")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject::default();
+        schema.subschemas().any_of = Some(vec![
+            gen.subschema_for::<BoundedU64<0, MAX>>(),
+            string_schema(
+                None,
+                "An amount such as `0.5 SOL` or `5000 lamports`, or a number of lamports.",
+            ),
+        ]);
+        schema.into()
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> JsonSchema for Percent<MIN, MAX> {
+    fn schema_name() -> String {
+        format!("Percent_{MIN}_{MAX}")
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let ratio = SchemaObject {
+            instance_type: Some(InstanceType::Number.into()),
+            number: Some(Box::new(NumberValidation {
+                minimum: Some(MIN as f64 / 100.0),
+                maximum: Some(MAX as f64 / 100.0),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let mut schema = SchemaObject::default();
+        schema.subschemas().any_of = Some(vec![
+            ratio.into(),
+            string_schema(
+                None,
+                &format!("A percentage between {MIN}% and {MAX}%, such as `75%`."),
+            ),
+        ]);
+        schema.into()
+    }
+}
+
+impl JsonSchema for BlockSize {
+    fn schema_name() -> String {
+        "BlockSize".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::Integer.into()),
+            enum_values: Some(vec![128.into(), 256.into(), 512.into()]),
+            ..Default::default()
+        };
+        schema.metadata().description =
+            Some("Block size of the accounts database in bytes.".to_owned());
+        schema.into()
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> JsonSchema for BoundedU64<MIN, MAX> {
+    fn schema_name() -> String {
+        format!("BoundedU64_{MIN}_{MAX}")
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::Integer.into()),
+            format: Some("uint64".to_owned()),
+            number: Some(Box::new(NumberValidation {
+                minimum: Some(MIN as f64),
+                maximum: Some(MAX as f64),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
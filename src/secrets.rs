@@ -0,0 +1,672 @@
+//! Resolution of secret references such as `aws-ssm:/magicblock/prod/keypair`,
+//! `env:HELIUS_API_KEY`, `file:/etc/magicblock/id.json` or
+//! `keyring:magicblock/validator`.
+
+use crate::{consts, sources};
+use figment::{value::Value, Figment};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    cell::Cell,
+    fmt::{self, Display},
+    sync::Mutex,
+    thread::LocalKey,
+};
+use url::Url;
+use zeroize::Zeroizing;
+
+/// Reference prefix for AWS SSM Parameter Store parameters.
+pub const AWS_SSM_PREFIX: &str = "aws-ssm:";
+/// Reference prefix for AWS Secrets Manager secrets.
+pub const AWS_SECRETS_MANAGER_PREFIX: &str = "aws-sm:";
+/// Reference prefix for files on the host, e.g. a Solana JSON keypair file.
+pub const FILE_PREFIX: &str = "file:";
+/// Reference prefix for environment variables, e.g. `env:HELIUS_API_KEY`.
+pub const ENV_PREFIX: &str = "env:";
+/// Reference prefix for entries of the platform keyring, as
+/// `keyring:<service>/<user>`.
+pub const KEYRING_PREFIX: &str = "keyring:";
+
+/// Value of a keypair to read interactively instead, see [`prompt`].
+pub const PROMPT: &str = "prompt";
+/// Prefix of a keypair value naming a file descriptor to read it from
+/// instead, e.g. `prompt:fd=3`, see [`prompt`].
+pub const PROMPT_FD_PREFIX: &str = "prompt:fd=";
+
+/// An error raised while resolving or parsing a secret value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretError(pub String);
+
+impl Display for SecretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+/// Whether `value` is a secret store reference rather than an inline value.
+pub fn is_reference(value: &str) -> bool {
+    [
+        AWS_SSM_PREFIX,
+        AWS_SECRETS_MANAGER_PREFIX,
+        FILE_PREFIX,
+        ENV_PREFIX,
+        KEYRING_PREFIX,
+    ]
+    .iter()
+    .any(|prefix| value.starts_with(prefix))
+}
+
+/// Whether the dotted `key` holds a secret: one of [`consts::SECRET_KEYS`],
+/// e.g. `tracing.headers.x-api-key`, or a credential of a remote, e.g.
+/// `remote.token`, `remote.1.headers.x-token` or `remotes.helius.api-key`.
+pub fn is_secret_key(key: &str) -> bool {
+    let is_remote_credential = || {
+        consts::REMOTE_KEYS.iter().any(|remote| {
+            consts::REMOTE_SECRET_KEYS
+                .iter()
+                .any(|field| matches_key(&format!("{remote}.{field}"), key))
+        })
+    };
+    consts::SECRET_KEYS
+        .iter()
+        .any(|pattern| matches_key(pattern, key))
+        || is_remote_credential()
+}
+
+/// Whether the dotted `key` holds a URL whose password is a secret: one of
+/// [`consts::CREDENTIAL_URL_KEYS`], e.g. `network.proxy.url`, or the proxy
+/// of a remote, e.g. `remotes.helius.proxy`.
+pub fn is_credential_url_key(key: &str) -> bool {
+    let is_remote_url = || {
+        consts::REMOTE_KEYS.iter().any(|remote| {
+            consts::REMOTE_CREDENTIAL_URL_KEYS
+                .iter()
+                .any(|field| matches_key(&format!("{remote}.{field}"), key))
+        })
+    };
+    consts::CREDENTIAL_URL_KEYS
+        .iter()
+        .any(|pattern| matches_key(pattern, key))
+        || is_remote_url()
+}
+
+/// `url` with its password, if it has one, replaced by
+/// [`consts::REDACTED`].
+pub fn redact_url(mut url: Url) -> Url {
+    if url.password().is_some() {
+        // Only URLs that can't have a password refuse one.
+        let _ = url.set_password(Some(consts::REDACTED));
+    }
+    url
+}
+
+/// `value`, a URL set at a key of [`is_credential_url_key`], with its
+/// password redacted. Values that don't parse as URLs are kept as they are.
+fn redact_url_str(value: String) -> String {
+    match Url::parse(&value) {
+        Ok(url) => redact_url(url).to_string(),
+        Err(_) => value,
+    }
+}
+
+/// Serializes `url` with its password redacted, unless secrets are
+/// [`exposed`].
+pub(crate) fn serialize_url<S: Serializer>(
+    url: &Option<Url>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match url {
+        Some(url) if !is_exposed() => redact_url(url.clone()).serialize(serializer),
+        url => url.serialize(serializer),
+    }
+}
+
+/// Whether the dotted `key` matches `pattern`, where `*` stands for any one
+/// key or list index.
+fn matches_key(pattern: &str, key: &str) -> bool {
+    let (mut pattern, mut key) = (pattern.split('.'), key.split('.'));
+    loop {
+        match (pattern.next(), key.next()) {
+            (None, None) => return true,
+            (Some(expected), Some(part)) if expected == "*" || expected == part => {}
+            _ => return false,
+        }
+    }
+}
+
+/// `value`, set at the dotted `key`, with the inline secrets anywhere in it
+/// replaced by [`consts::REDACTED`], e.g. the tokens of a list of remotes.
+pub(crate) fn redact(key: &str, value: Value) -> Value {
+    match value {
+        Value::String(tag, value) if is_secret_key(key) && !is_reference(&value) => {
+            Value::String(tag, consts::REDACTED.to_owned())
+        }
+        Value::String(tag, value) if is_credential_url_key(key) => {
+            Value::String(tag, redact_url_str(value))
+        }
+        Value::Dict(tag, dict) => {
+            let dict = dict
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = redact(&format!("{key}.{name}"), value);
+                    (name, value)
+                })
+                .collect();
+            Value::Dict(tag, dict)
+        }
+        Value::Array(tag, values) => {
+            let values = values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| redact(&format!("{key}.{i}"), value))
+                .collect();
+            Value::Array(tag, values)
+        }
+        value => value,
+    }
+}
+
+/// Replaces every secret anywhere in `value`, set at the dotted `key`, with
+/// [`consts::REDACTED`], references included.
+pub(crate) fn redact_toml(key: &str, value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (name, value) in table {
+                redact_toml(&format!("{key}.{name}"), value);
+            }
+        }
+        toml::Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                redact_toml(&format!("{key}.{i}"), value);
+            }
+        }
+        value if is_secret_key(key) => *value = toml::Value::from(consts::REDACTED),
+        toml::Value::String(url) if is_credential_url_key(key) => {
+            *url = redact_url_str(std::mem::take(url));
+        }
+        _ => {}
+    }
+}
+
+thread_local! {
+    /// Set while [`exposed`] runs, so that [`Secret`](crate::types::Secret)s
+    /// serialize as their values.
+    static EXPOSED: Cell<bool> = const { Cell::new(false) };
+    /// Set while [`unresolved`] runs, so that secret references are not
+    /// resolved.
+    static UNRESOLVED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with `flag` set.
+fn with_flag<R>(flag: &'static LocalKey<Cell<bool>>, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous state, even if `f` panics.
+    struct Restore(&'static LocalKey<Cell<bool>>, bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            self.0.with(|flag| flag.set(self.1));
+        }
+    }
+
+    let _restore = Restore(flag, flag.with(|flag| flag.replace(true)));
+    f()
+}
+
+/// Runs `f` with [`Secret`](crate::types::Secret)s serializing as their
+/// values rather than redacted, e.g. to merge a configuration back into a
+/// `Figment`. Never log what `f` serializes.
+pub(crate) fn exposed<R>(f: impl FnOnce() -> R) -> R {
+    with_flag(&EXPOSED, f)
+}
+
+/// Whether secrets are currently serialized as their values.
+pub(crate) fn is_exposed() -> bool {
+    EXPOSED.with(Cell::get)
+}
+
+/// Runs `f` with secret references, prompts and seed phrases kept as
+/// written and a placeholder standing in for their values, e.g. to check a
+/// configuration without reaching out to the secret stores each time.
+pub(crate) fn unresolved<R>(f: impl FnOnce() -> R) -> R {
+    with_flag(&UNRESOLVED, f)
+}
+
+/// Whether secret references currently stand in for their values.
+pub(crate) fn is_unresolved() -> bool {
+    UNRESOLVED.with(Cell::get)
+}
+
+/// Resolves `value` if it is a secret reference, returning `None` for inline
+/// values. The resolved secret is wiped from memory when dropped.
+pub fn resolve(value: &str) -> Result<Option<Zeroizing<String>>, SecretError> {
+    if let Some(name) = value.strip_prefix(AWS_SSM_PREFIX) {
+        return aws::ssm_parameter(name).map(Some);
+    }
+    if let Some(id) = value.strip_prefix(AWS_SECRETS_MANAGER_PREFIX) {
+        return aws::secrets_manager_secret(id).map(Some);
+    }
+    if let Some(path) = value.strip_prefix(FILE_PREFIX) {
+        return read_file(path).map(Some);
+    }
+    if let Some(name) = value.strip_prefix(ENV_PREFIX) {
+        return std::env::var(name)
+            .map(|value| Some(Zeroizing::new(value)))
+            .map_err(|_| SecretError(format!("environment variable {name} is not set")));
+    }
+    if let Some(entry) = value.strip_prefix(KEYRING_PREFIX) {
+        let (service, user) = entry
+            .split_once('/')
+            .filter(|(service, user)| !service.is_empty() && !user.is_empty())
+            .ok_or_else(|| {
+                SecretError(format!(
+                    "invalid keyring reference {value}, expected `{KEYRING_PREFIX}<service>/<user>`"
+                ))
+            })?;
+        return keyring::password(service, user).map(Some);
+    }
+    Ok(None)
+}
+
+/// The content of the file at `path`, without trailing whitespace.
+fn read_file(path: &str) -> Result<Zeroizing<String>, SecretError> {
+    let mut content = Zeroizing::new(String::new());
+    std::fs::File::open(path)
+        .and_then(|mut file| std::io::Read::read_to_string(&mut file, &mut content))
+        .map_err(|e| SecretError(format!("cannot read secret file {path}: {e}")))?;
+    trim_end(&mut content);
+    Ok(content)
+}
+
+/// Strips trailing whitespace from `secret` in place, rather than copying
+/// it to a new `String` that would leave the original unwiped.
+fn trim_end(secret: &mut String) {
+    secret.truncate(secret.trim_end().len());
+}
+
+thread_local! {
+    /// What [`prompt`] asks for, see [`prompting_for`].
+    static PROMPT_LABEL: Cell<&'static str> = const { Cell::new(IDENTITY_PROMPT_LABEL) };
+}
+
+/// What [`prompt`] asks for unless [`prompting_for`] says otherwise.
+pub(crate) const IDENTITY_PROMPT_LABEL: &str = "Validator keypair (Base58)";
+
+/// Runs `f` with keypairs set to `prompt` asked for as `label`, e.g. while
+/// the keypair of a role other than the identity deserializes.
+pub(crate) fn prompting_for<R>(label: &'static str, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous label, even if `f` panics.
+    struct Restore(&'static str);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            PROMPT_LABEL.with(|label| label.set(self.0));
+        }
+    }
+
+    let _restore = Restore(PROMPT_LABEL.with(|previous| previous.replace(label)));
+    f()
+}
+
+/// The label of the keypair being read, see [`prompting_for`].
+pub(crate) fn prompt_label() -> &'static str {
+    PROMPT_LABEL.with(Cell::get)
+}
+
+/// Whether `value` asks for the secret to be read interactively, see
+/// [`prompt`].
+pub(crate) fn is_prompt(value: &str) -> bool {
+    value == PROMPT || value.starts_with(PROMPT_FD_PREFIX)
+}
+
+/// Reads the secret `value` asks for: with [`PROMPT`], from the terminal
+/// with echo turned off, asking for it with `label`, and with
+/// `prompt:fd=<n>`, from the inherited file descriptor `n`, e.g. with
+/// `3< keypair.txt`. Either way, it never shows up in `ps` or the shell
+/// history. Only the first call reads, later ones for the same `value` and
+/// `label` return the same answer, so that reloading doesn't ask again.
+pub(crate) fn prompt(value: &str, label: &str) -> Result<Zeroizing<String>, SecretError> {
+    static ANSWERS: Mutex<Vec<(String, Zeroizing<String>)>> = Mutex::new(Vec::new());
+
+    let fd = match value.strip_prefix(PROMPT_FD_PREFIX) {
+        Some(fd) => Some(fd.parse().map_err(|_| {
+            SecretError(format!(
+                "`{value}` must name a file descriptor, e.g. `prompt:fd=3`"
+            ))
+        })?),
+        None => None,
+    };
+    let asked = format!("{value} {label}");
+    let mut answers = ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, answer)) = answers.iter().find(|(key, _)| *key == asked) {
+        return Ok(answer.clone());
+    }
+    let mut read = match fd {
+        Some(fd) => prompt::read_fd(fd)?,
+        None => prompt::read(label)?,
+    };
+    trim_end(&mut read);
+    let start = read.len() - read.trim_start().len();
+    read.drain(..start);
+    if read.is_empty() {
+        return Err(SecretError("no secret was entered".to_owned()));
+    }
+    answers.push((asked, read.clone()));
+    Ok(read)
+}
+
+#[cfg(unix)]
+mod prompt {
+    use super::SecretError;
+    use std::{
+        fs::{File, OpenOptions},
+        io::{self, BufRead, BufReader, Read, Write},
+        mem::ManuallyDrop,
+        os::fd::{AsRawFd, FromRawFd, RawFd},
+    };
+    use zeroize::Zeroizing;
+
+    pub fn read(label: &str) -> Result<Zeroizing<String>, SecretError> {
+        terminal(label).map_err(|e| {
+            SecretError(format!(
+                "cannot prompt for the secret on the terminal ({e}); pass it on a file descriptor with `prompt:fd=<n>` instead"
+            ))
+        })
+    }
+
+    /// Reads the secret from the inherited descriptor `fd` up to its end.
+    /// The descriptor is left open, as it belongs to whoever passed it.
+    pub fn read_fd(fd: RawFd) -> Result<Zeroizing<String>, SecretError> {
+        // SAFETY: `fcntl` only queries the descriptor's flags.
+        if fd < 0 || unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+            return Err(SecretError(format!("descriptor {fd} is not open")));
+        }
+        // SAFETY: the descriptor is open. It is only borrowed: never
+        // dropped, so never closed.
+        let mut file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+        let mut secret = Zeroizing::new(String::new());
+        file.read_to_string(&mut secret).map_err(|e| {
+            SecretError(format!("cannot read the secret from descriptor {fd}: {e}"))
+        })?;
+        Ok(secret)
+    }
+
+    /// Asks for the secret on the controlling terminal, with echo turned
+    /// off while it's typed.
+    fn terminal(label: &str) -> io::Result<Zeroizing<String>> {
+        let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let fd = tty.as_raw_fd();
+        // SAFETY: `termios` is plain data, filled in by `tcgetattr`.
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        // SAFETY: `fd` is open and `termios` is valid for writes.
+        if unsafe { libc::tcgetattr(fd, &mut termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = termios;
+        termios.c_lflag &= !libc::ECHO;
+        termios.c_lflag |= libc::ECHONL;
+        // SAFETY: `fd` is open and `termios` is initialized.
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut secret = Zeroizing::new(String::new());
+        let read = write!(tty, "{label}: ")
+            .and_then(|()| tty.flush())
+            .and_then(|()| BufReader::new(&tty).read_line(&mut secret));
+        // Echo is restored even if reading failed.
+        // SAFETY: `fd` is open and `original` was filled in by `tcgetattr`.
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+        read.map(|_| secret)
+    }
+}
+
+#[cfg(not(unix))]
+mod prompt {
+    use super::SecretError;
+    use zeroize::Zeroizing;
+
+    pub fn read(_: &str) -> Result<Zeroizing<String>, SecretError> {
+        Err(SecretError(
+            "prompting for a secret is only supported on Unix".to_owned(),
+        ))
+    }
+
+    pub fn read_fd(_: i32) -> Result<Zeroizing<String>, SecretError> {
+        read("")
+    }
+}
+
+/// A keypair derived from a BIP-39 seed phrase held in an environment
+/// variable, e.g. one filled in from a secret manager, as `solana-keygen`
+/// derives it. Written in the configuration as a table:
+/// `keypair = { mnemonic-env = "MBV_SEED", derivation = "m/44'/501'/0'/0'" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SeedPhraseRef {
+    /// The environment variable holding the seed phrase.
+    pub mnemonic_env: String,
+    /// The derivation path, either absolute like `m/44'/501'/0'/0'` or
+    /// relative to `m/44'/501'` like `0/0`. Without one, the keypair is
+    /// made from the seed itself, like `solana-keygen recover` does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation: Option<String>,
+    /// The environment variable holding the BIP-39 passphrase, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase_env: Option<String>,
+}
+
+impl SeedPhraseRef {
+    /// Derives the keypair, returning it encoded in Base58. The phrase, the
+    /// passphrase and the keypair are wiped from memory when dropped.
+    pub fn resolve(&self) -> Result<Zeroizing<String>, SecretError> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .map(Zeroizing::new)
+                .map_err(|_| SecretError(format!("environment variable {name} is not set")))
+        };
+        let phrase = var(&self.mnemonic_env)?;
+        let passphrase = match &self.passphrase_env {
+            Some(name) => var(name)?,
+            None => Zeroizing::default(),
+        };
+        seed_phrase::derive_keypair(
+            &self.mnemonic_env,
+            &phrase,
+            &passphrase,
+            self.derivation.as_deref(),
+        )
+    }
+
+    /// The environment variables the seed phrase and passphrase are read
+    /// from.
+    fn vars(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.mnemonic_env.as_str()).chain(self.passphrase_env.as_deref())
+    }
+}
+
+/// Dotted keys that environment variables read by a [`SeedPhraseRef`] in
+/// `figment` would otherwise set, e.g. `seed` for `MBV_SEED`. They hold
+/// secrets rather than configuration, so they are neither reported as
+/// unknown nor explained.
+pub(crate) fn seed_phrase_keys(figment: &Figment) -> Vec<String> {
+    consts::SECRET_KEYS
+        .iter()
+        .filter_map(|key| figment.find_value(key).ok())
+        .filter_map(|value| match value {
+            value @ Value::Dict(..) => value.deserialize::<SeedPhraseRef>().ok(),
+            _ => None,
+        })
+        .flat_map(|reference| {
+            reference
+                .vars()
+                .filter_map(sources::env_key_path)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(feature = "seed-phrase")]
+mod seed_phrase {
+    use super::SecretError;
+    use bip39::{Language, Mnemonic, Seed};
+    use solana_derivation_path::DerivationPath;
+    use solana_keypair::{
+        keypair_from_seed, seed_derivable::keypair_from_seed_and_derivation_path,
+    };
+    use zeroize::Zeroizing;
+
+    /// Derives a keypair from `phrase`, read from `var`, and `passphrase`,
+    /// following `derivation` if given.
+    pub fn derive_keypair(
+        var: &str,
+        phrase: &str,
+        passphrase: &str,
+        derivation: Option<&str>,
+    ) -> Result<Zeroizing<String>, SecretError> {
+        // The error never includes the phrase.
+        let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+            .map_err(|e| SecretError(format!("invalid seed phrase in {var}: {e}")))?;
+        let seed = Seed::new(&mnemonic, passphrase);
+        let keypair = match derivation {
+            Some(path) => {
+                let path = if path.starts_with('m') {
+                    DerivationPath::from_absolute_path_str(path)
+                } else {
+                    DerivationPath::from_key_str(path)
+                }
+                .map_err(|e| SecretError(format!("invalid derivation path {path}: {e}")))?;
+                keypair_from_seed_and_derivation_path(seed.as_bytes(), Some(path))
+            }
+            None => keypair_from_seed(seed.as_bytes()),
+        }
+        .map_err(|e| SecretError(format!("cannot derive a keypair: {e}")))?;
+        Ok(Zeroizing::new(keypair.to_base58_string()))
+    }
+}
+
+#[cfg(not(feature = "seed-phrase"))]
+mod seed_phrase {
+    use super::SecretError;
+    use zeroize::Zeroizing;
+
+    pub fn derive_keypair(
+        var: &str,
+        _: &str,
+        _: &str,
+        _: Option<&str>,
+    ) -> Result<Zeroizing<String>, SecretError> {
+        Err(SecretError(format!(
+            "cannot derive a keypair from the seed phrase in {var}: built without the `seed-phrase` feature"
+        )))
+    }
+}
+
+#[cfg(feature = "keyring")]
+mod keyring {
+    use super::SecretError;
+    use zeroize::Zeroizing;
+
+    pub fn password(service: &str, user: &str) -> Result<Zeroizing<String>, SecretError> {
+        ::keyring::Entry::new(service, user)
+            .and_then(|entry| entry.get_password())
+            .map(Zeroizing::new)
+            .map_err(|e| {
+                SecretError(format!(
+                    "cannot read {service}/{user} from the keyring: {e}"
+                ))
+            })
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod keyring {
+    use super::SecretError;
+    use zeroize::Zeroizing;
+
+    pub fn password(service: &str, user: &str) -> Result<Zeroizing<String>, SecretError> {
+        Err(SecretError(format!(
+            "cannot read {service}/{user} from the keyring: built without the `keyring` feature"
+        )))
+    }
+}
+
+/// Lookups through the `aws` CLI, which picks up credentials, profile and
+/// region the same way the rest of the AWS tooling on the host does.
+#[cfg(feature = "aws")]
+mod aws {
+    use super::SecretError;
+    use std::process::Command;
+    use zeroize::{Zeroize, Zeroizing};
+
+    pub fn ssm_parameter(name: &str) -> Result<Zeroizing<String>, SecretError> {
+        run(&[
+            "ssm",
+            "get-parameter",
+            "--name",
+            name,
+            "--with-decryption",
+            "--query",
+            "Parameter.Value",
+            "--output",
+            "text",
+        ])
+    }
+
+    pub fn secrets_manager_secret(id: &str) -> Result<Zeroizing<String>, SecretError> {
+        run(&[
+            "secretsmanager",
+            "get-secret-value",
+            "--secret-id",
+            id,
+            "--query",
+            "SecretString",
+            "--output",
+            "text",
+        ])
+    }
+
+    fn run(args: &[&str]) -> Result<Zeroizing<String>, SecretError> {
+        let output = Command::new("aws")
+            .args(args)
+            .output()
+            .map_err(|e| SecretError(format!("failed to run the aws CLI: {e}")))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(SecretError(format!(
+                "aws {} {} failed: {}",
+                args[0],
+                args[1],
+                stderr.trim()
+            )));
+        }
+        let mut value = String::from_utf8(output.stdout)
+            .map(Zeroizing::new)
+            .map_err(|e| {
+                e.into_bytes().zeroize();
+                SecretError(format!("aws {} returned a non UTF-8 value", args[0]))
+            })?;
+        super::trim_end(&mut value);
+        Ok(value)
+    }
+}
+
+#[cfg(not(feature = "aws"))]
+mod aws {
+    use super::SecretError;
+    use zeroize::Zeroizing;
+
+    pub fn ssm_parameter(name: &str) -> Result<Zeroizing<String>, SecretError> {
+        Err(unsupported(name))
+    }
+
+    pub fn secrets_manager_secret(id: &str) -> Result<Zeroizing<String>, SecretError> {
+        Err(unsupported(id))
+    }
+
+    fn unsupported(name: &str) -> SecretError {
+        SecretError(format!(
+            "cannot resolve AWS secret {name}: built without the `aws` feature"
+        ))
+    }
+}
@@ -0,0 +1,598 @@
+use crate::{
+    aliases, consts,
+    encrypted::{Decrypted, Encryption},
+    provenance, MagicBlockParams,
+};
+use clap::ValueEnum;
+use figment::{
+    providers::{Env, Format, Json, Serialized, Toml, Yaml},
+    value::{Dict, Map, Value},
+    Figment, Metadata, Profile, Provider,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+use zeroize::Zeroizing;
+
+/// Supported formats for configuration files.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Infers the format from the file extension, falling back to TOML.
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            // `config.yaml.age` is an encrypted YAML file.
+            Some("age") => Self::from_path(&path.with_extension("")),
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// Guesses the format of a document without a file name: JSON if it
+    /// starts with `{`, TOML otherwise.
+    pub fn sniff(content: &str) -> Self {
+        if content.trim_start().starts_with('{') {
+            Self::Json
+        } else {
+            Self::Toml
+        }
+    }
+
+    /// Creates a figment holding only the file at `path`, parsed with this format.
+    pub fn figment(self, path: &Path) -> Figment {
+        match self {
+            Self::Toml => Figment::from(Toml::file(path).profile(Profile::Default)),
+            Self::Yaml => Figment::from(Yaml::file(path).profile(Profile::Default)),
+            Self::Json => Figment::from(Json::file(path).profile(Profile::Default)),
+        }
+    }
+
+    /// Creates a figment holding only the document `content`, parsed with this format.
+    pub fn figment_from_str(self, content: &str) -> Figment {
+        match self {
+            Self::Toml => Figment::from(Toml::string(content).profile(Profile::Default)),
+            Self::Yaml => Figment::from(Yaml::string(content).profile(Profile::Default)),
+            Self::Json => Figment::from(Json::string(content).profile(Profile::Default)),
+        }
+    }
+}
+
+/// Options controlling how configuration files are read.
+#[derive(Debug, Clone)]
+pub struct FileOptions {
+    /// Format of the files, instead of inferring it from their extension.
+    pub format: Option<ConfigFormat>,
+    /// Timeout for fetching configuration documents from HTTP(S) URLs.
+    pub fetch_timeout: Duration,
+    /// Bearer token sent along when fetching configuration documents.
+    pub fetch_token: Option<String>,
+    /// Active profile, whose `[profile.<name>]` table overrides the rest of
+    /// each document.
+    pub profile: Option<String>,
+    /// The age identity file to decrypt encrypted files with, see
+    /// [`Encryption`].
+    pub age_identity: Option<PathBuf>,
+    /// The document read from stdin for `-`, shared with later loads.
+    pub stdin: StdinDocument,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            fetch_timeout: consts::DEFAULT_CONFIG_FETCH_TIMEOUT,
+            fetch_token: None,
+            profile: None,
+            age_identity: None,
+            stdin: StdinDocument::default(),
+        }
+    }
+}
+
+/// The file options given on the command line.
+impl From<&MagicBlockParams> for FileOptions {
+    fn from(cli: &MagicBlockParams) -> Self {
+        Self {
+            format: cli.config_format,
+            fetch_timeout: cli.config_timeout,
+            fetch_token: cli.config_token.clone(),
+            profile: cli.profile.clone(),
+            age_identity: cli.age_identity.clone(),
+            stdin: StdinDocument::default(),
+        }
+    }
+}
+
+/// The configuration document read from stdin, for `--config -`. Stdin can
+/// only be read once, so loads that share a `StdinDocument`, such as the
+/// reloads of a [`ConfigHandle`](crate::ConfigHandle), read it on the first
+/// load and reuse the document on the next ones.
+#[derive(Clone, Default)]
+pub struct StdinDocument(Arc<Mutex<Option<Zeroizing<String>>>>);
+
+/// Leaves out the document, which may hold secrets.
+impl std::fmt::Debug for StdinDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StdinDocument")
+    }
+}
+
+impl StdinDocument {
+    /// The document, read from stdin if it hasn't been yet.
+    fn read(&self) -> std::io::Result<Zeroizing<String>> {
+        let mut document = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(content) = &*document {
+            return Ok(content.clone());
+        }
+        let mut content = Zeroizing::new(String::new());
+        std::io::stdin().read_to_string(&mut content)?;
+        *document = Some(content.clone());
+        Ok(content)
+    }
+}
+
+/// Splits a colon-separated list of configuration paths, keeping the scheme
+/// separator and port of `http://` and `https://` URLs intact.
+pub fn split_config_list(list: &Path) -> Vec<PathBuf> {
+    let Some(list) = list.to_str() else {
+        return vec![list.to_owned()];
+    };
+    let continues_url = |prev: &str, part: &str| match prev {
+        "http" | "https" => part.starts_with("//"),
+        // A port, as long as the authority of the URL hasn't ended yet.
+        _ => {
+            as_url(Path::new(prev))
+                .is_some_and(|url| !url.split_once("//").unwrap().1.contains('/'))
+                && part.starts_with(|c: char| c.is_ascii_digit())
+        }
+    };
+    let mut paths: Vec<String> = Vec::new();
+    for part in list.split(':') {
+        match paths.last_mut() {
+            Some(prev) if continues_url(prev, part) => {
+                prev.push(':');
+                prev.push_str(part);
+            }
+            _ => paths.push(part.to_owned()),
+        }
+    }
+    paths
+        .into_iter()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Returns the `path` as a URL if it refers to an HTTP(S) resource.
+fn as_url(path: &Path) -> Option<&str> {
+    path.to_str()
+        .filter(|path| path.starts_with("http://") || path.starts_with("https://"))
+}
+
+/// Downloads the document at `url`. The token is only sent over HTTPS, or
+/// to this machine, and is dropped when a redirect leaves the host or
+/// downgrades to plain HTTP.
+#[cfg(feature = "http")]
+fn fetch(url: &str, options: &FileOptions) -> figment::Result<String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(options.fetch_timeout)
+        .redirect_auth_headers(ureq::RedirectAuthHeaders::SameHost)
+        .build();
+    let mut request = agent.get(url);
+    if let Some(token) = &options.fetch_token {
+        let parsed = url::Url::parse(url).map_err(|e| format!("invalid URL {url}: {e}"))?;
+        if parsed.scheme() != "https" && !crate::remote::is_loopback(&parsed) {
+            return Err(format!(
+                "refusing to send `--config-token` to {url} over plain HTTP, use an https URL"
+            )
+            .into());
+        }
+        request = request.set("Authorization", &format!("Bearer {token}"));
+    }
+    let response = request
+        .call()
+        .map_err(|e| format!("failed to fetch configuration from {url}: {e}"))?;
+    let content = response
+        .into_string()
+        .map_err(|e| format!("failed to read configuration from {url}: {e}"))?;
+    Ok(content)
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch(url: &str, _: &FileOptions) -> figment::Result<String> {
+    Err(format!("cannot fetch {url}: built without the `http` feature").into())
+}
+
+/// Returns the locations probed, in order, for a configuration file when none
+/// is given explicitly: the working directory, the user's XDG config directory
+/// and finally the system-wide directory.
+pub fn default_search_path() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(consts::LOCAL_CONFIG_FILE)];
+    let user_config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    if let Some(dir) = user_config_dir {
+        paths.push(dir.join(consts::USER_CONFIG_FILE));
+    }
+    paths.push(PathBuf::from(consts::SYSTEM_CONFIG_FILE));
+    paths
+}
+
+/// Returns the first existing file on the [default search path](default_search_path).
+pub fn find_default_config() -> Option<PathBuf> {
+    default_search_path()
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/// The top-level `include` directive of a configuration file.
+#[derive(Deserialize, Default)]
+struct Includes {
+    #[serde(default)]
+    include: Vec<PathBuf>,
+}
+
+/// Merges the configuration file at `path` into `figment`.
+///
+/// Files listed in the file's top-level `include` key are merged first, in
+/// order, so the including file can still override them. Included paths are
+/// resolved relative to the including file and their format is inferred from
+/// their extension. `options.format` only applies to `path` itself.
+///
+/// SOPS- and age-encrypted files are decrypted first, see [`Encryption`].
+///
+/// If `path` is a directory, every `*.toml` file inside it is merged in
+/// lexicographic order, conf.d style. If it is an HTTP(S) URL, the document
+/// is downloaded and merged as is, without resolving includes. The same goes
+/// for `-`, which reads the document from stdin, see [`StdinDocument`].
+pub fn merge_config_file(
+    mut figment: Figment,
+    path: &Path,
+    options: &FileOptions,
+) -> figment::Result<Figment> {
+    let format = options.format;
+    if path == Path::new(consts::STDIN_CONFIG) {
+        let content = options
+            .stdin
+            .read()
+            .map_err(|e| format!("failed to read configuration from stdin: {e}"))?;
+        let format = format.unwrap_or_else(|| ConfigFormat::sniff(&content));
+        let document = format.figment_from_str(&content);
+        return merge_document(figment, document, options.profile.as_deref());
+    }
+    if let Some(url) = as_url(path) {
+        let content = fetch(url, options)?;
+        let format = format.unwrap_or_else(|| ConfigFormat::from_path(path));
+        let document = format.figment_from_str(&content);
+        return merge_document(figment, document, options.profile.as_deref());
+    }
+    if path.is_dir() {
+        for fragment in config_dir_fragments(path)? {
+            let toml = Some(ConfigFormat::Toml);
+            figment = merge_with_includes(figment, &fragment, toml, options, &mut Vec::new())?;
+        }
+        return Ok(figment);
+    }
+    merge_with_includes(figment, path, format, options, &mut Vec::new())
+}
+
+/// Lists the `*.toml` files directly inside `dir`, sorted lexicographically.
+fn config_dir_fragments(dir: &Path) -> figment::Result<Vec<PathBuf>> {
+    let entries = dir
+        .read_dir()
+        .map_err(|e| format!("failed to read config directory {}: {e}", dir.display()))?;
+    let mut fragments = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("failed to read config directory {}: {e}", dir.display()))?
+            .path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "toml") {
+            fragments.push(path);
+        }
+    }
+    fragments.sort();
+    Ok(fragments)
+}
+
+fn merge_with_includes(
+    mut figment: Figment,
+    path: &Path,
+    format: Option<ConfigFormat>,
+    options: &FileOptions,
+    stack: &mut Vec<PathBuf>,
+) -> figment::Result<Figment> {
+    let format = format.unwrap_or_else(|| ConfigFormat::from_path(path));
+    let file = match Encryption::detect(path, format) {
+        Some(encryption) => {
+            let content = encryption.decrypt(path, options.age_identity.as_deref())?;
+            Figment::from(Decrypted {
+                path: path.to_owned(),
+                format,
+                encryption,
+                content,
+            })
+        }
+        None => format.figment(path),
+    };
+    let Includes { include } = file.extract()?;
+    if !include.is_empty() {
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| format!("failed to resolve {}: {e}", path.display()))?;
+        if stack.contains(&canonical) {
+            return Err(format!("include cycle detected at {}", path.display()).into());
+        }
+        stack.push(canonical);
+        let base = path.parent().unwrap_or(Path::new(""));
+        for included in include {
+            let included = base.join(included);
+            if !included.is_file() {
+                return Err(format!(
+                    "{} includes missing file {}",
+                    path.display(),
+                    included.display()
+                )
+                .into());
+            }
+            figment = merge_with_includes(figment, &included, None, options, stack)?;
+        }
+        stack.pop();
+    }
+    merge_document(figment, file, options.profile.as_deref())
+}
+
+/// Merges a single configuration document into `figment`, followed by its
+/// `[profile.<name>]` table if `profile` is active and the document has one.
+/// Deprecated keys are mapped to their replacements.
+fn merge_document(
+    figment: Figment,
+    document: Figment,
+    profile: Option<&str>,
+) -> figment::Result<Figment> {
+    let overrides = match profile {
+        Some(name) => document
+            .find_value(&format!("{}.{name}", consts::PROFILES_KEY))
+            .ok(),
+        None => None,
+    };
+    let mut figment = aliases::merge_aliased(figment, document);
+    if let Some(overrides) = overrides {
+        let Some(overrides) = overrides.into_dict() else {
+            let name = profile.unwrap_or_default();
+            return Err(format!("profile `{name}` must be a table").into());
+        };
+        figment = aliases::merge_aliased(figment, Serialized::defaults(overrides));
+    }
+    Ok(figment)
+}
+
+/// Describes the source that set `key` in `figment`, e.g. "TOML file
+/// `config.toml:3`", for use in diagnostics. See [`provenance::Source`].
+pub fn key_origin(figment: &Figment, key: &str) -> Option<String> {
+    provenance::of(figment, key).map(|source| source.to_string())
+}
+
+/// The dotted paths of every value in `dict`, descending into nested
+/// dictionaries. Empty dictionaries count as values.
+pub(crate) fn leaf_keys(dict: &Dict) -> Vec<String> {
+    fn collect(dict: &Dict, prefix: &str, out: &mut Vec<String>) {
+        for (key, value) in dict {
+            let key = format!("{prefix}{key}");
+            match value.as_dict() {
+                Some(section) if !section.is_empty() => collect(section, &format!("{key}."), out),
+                _ => out.push(key),
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(dict, "", &mut out);
+    out
+}
+
+/// Maps an environment variable name to the dotted key path it sets, or
+/// `None` if the variable is not a configuration variable.
+///
+/// Variables must carry the `MBV_` prefix. The remaining words, separated by
+/// `_`, are matched against the known keys, where a `_` either starts a
+/// nested key or joins the words of a multi-word key, which may also be
+/// written as one word. E.g. `MBV_VALIDATOR_BASEFEE` sets
+/// `validator.basefee`, and both `MBV_ACCOUNTS_DB_DATABASE_SIZE` and
+/// `MBV_ACCOUNTSDB_DATABASE_SIZE` set `accounts-db.database-size`. Variables
+/// matching no known key nest on every `_`.
+pub fn env_key_path(var: &str) -> Option<String> {
+    let prefix = consts::ENV_VAR_PREFIX;
+    if var.len() <= prefix.len()
+        || !var
+            .get(..prefix.len())
+            .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+    {
+        return None;
+    }
+    let key = var[prefix.len()..].to_ascii_lowercase();
+    if consts::LOADER_ENV_KEYS.contains(&key.as_str()) {
+        return None;
+    }
+    let words: Vec<_> = key.split('_').collect();
+    Some(resolve_words(&words, known_keys()).unwrap_or_else(|| key.replace('_', ".")))
+}
+
+/// Every configuration key, nested like in a configuration file, with its
+/// built-in default.
+fn known_keys() -> &'static Dict {
+    static KNOWN: OnceLock<Dict> = OnceLock::new();
+    KNOWN.get_or_init(|| {
+        let mut known: Dict = Figment::from(Serialized::defaults(MagicBlockParams::default()))
+            .extract()
+            .unwrap_or_default();
+        // `admin` has no default, but its keys must still be told apart
+        // from multi-word keys.
+        let admin = ["listen", "token"].map(|key| (key.to_owned(), Value::from("")));
+        known.insert("admin".to_owned(), Value::from(Dict::from(admin)));
+        known
+    })
+}
+
+/// Finds the key of `dict` named by `words`, preferring the longest
+/// multi-word keys, and returns its dotted path.
+fn resolve_words(words: &[&str], dict: &Dict) -> Option<String> {
+    (1..=words.len()).rev().find_map(|len| {
+        let (name, rest) = words.split_at(len);
+        let (key, value) = dict
+            .iter()
+            .find(|(key, _)| **key == name.join("-") || key.replace('-', "") == name.concat())?;
+        if rest.is_empty() {
+            return Some(key.clone());
+        }
+        match value {
+            // Maps, such as `log.filters`, take the rest as a single key, as is.
+            Value::Dict(_, nested) if nested.is_empty() => {
+                Some(format!("{key}.{}", rest.join("_")))
+            }
+            Value::Dict(_, nested) => Some(format!("{key}.{}", resolve_words(rest, nested)?)),
+            // Sections without a default, such as `chain-operation`, have no
+            // known keys, so the rest is taken as a single multi-word key.
+            Value::Empty(..) => Some(format!("{key}.{}", rest.join("-"))),
+            _ => None,
+        }
+    })
+}
+
+/// The environment variable layer, reading `MBV_*` variables of the process.
+pub fn env_provider() -> Env {
+    Env::raw()
+        .filter_map(|var| env_key_path(var.as_str()).map(Into::into))
+        .profile(Profile::Default)
+}
+
+/// Name of the environment layer.
+pub(crate) const ENVIRONMENT_LAYER: &str = "environment variable";
+
+/// Name of the `.env` file layer.
+pub(crate) const DOTENV_LAYER: &str = ".env file";
+
+/// Variables loaded from a `.env` file, layered just below the process
+/// environment. Keys are mapped like real environment variables.
+pub struct DotEnv {
+    path: PathBuf,
+    vars: Vec<(String, String)>,
+}
+
+impl DotEnv {
+    /// Reads and parses the `.env` file at `path`.
+    pub fn read(path: &Path) -> figment::Result<Self> {
+        let read_err = |e: dotenvy::Error| format!("failed to read {}: {e}", path.display());
+        let vars = dotenvy::from_path_iter(path)
+            .map_err(read_err)?
+            .collect::<Result<_, _>>()
+            .map_err(read_err)?;
+        Ok(Self {
+            path: path.to_owned(),
+            vars,
+        })
+    }
+}
+
+impl Provider for DotEnv {
+    fn metadata(&self) -> Metadata {
+        Metadata::from(DOTENV_LAYER, self.path.as_path())
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        let pairs = self.vars.iter().filter_map(|(var, value)| {
+            let value: Value = value.parse().expect("infallible");
+            Some((env_key_path(var)?, value))
+        });
+        Ok(Profile::Default.collect(nested_dict(pairs)))
+    }
+}
+
+/// A `--set KEY=VALUE` override of a single configuration key, e.g.
+/// `commit.compute-unit-price=2000000`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyOverride {
+    /// Dotted path of the key.
+    pub key: String,
+    /// The raw value, parsed like an environment variable.
+    pub value: String,
+}
+
+impl FromStr for KeyOverride {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got `{s}`"))?;
+        let key = key.trim();
+        if key.is_empty() || key.split('.').any(str::is_empty) {
+            return Err(format!("`{key}` is not a dotted key path"));
+        }
+        Ok(Self {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+/// The `--set` overrides, layered above the configuration files. Later
+/// overrides of the same key win.
+pub struct KeyOverrides(pub Vec<KeyOverride>);
+
+impl Provider for KeyOverrides {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("`--set` argument")
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        let pairs = self.0.iter().map(|KeyOverride { key, value }| {
+            let value: Value = value.parse().expect("infallible");
+            (key.clone(), value)
+        });
+        Ok(Profile::Default.collect(nested_dict(pairs)))
+    }
+}
+
+/// Nests dotted `(key, value)` pairs into a dictionary, later pairs winning.
+/// Unlike merging them through a [`Figment`], this leaves the values
+/// untagged, so they are attributed to the provider returning them.
+fn nested_dict(pairs: impl IntoIterator<Item = (String, Value)>) -> Dict {
+    fn insert(dict: &mut Dict, key: &str, value: Value) {
+        let Some((head, rest)) = key.split_once('.') else {
+            dict.insert(key.to_owned(), value);
+            return;
+        };
+        let entry = dict
+            .entry(head.to_owned())
+            .or_insert_with(|| Dict::new().into());
+        if entry.as_dict().is_none() {
+            *entry = Dict::new().into();
+        }
+        if let Value::Dict(_, nested) = entry {
+            insert(nested, rest, value);
+        }
+    }
+
+    let mut dict = Dict::new();
+    for (key, value) in pairs {
+        insert(&mut dict, &key, value);
+    }
+    dict
+}
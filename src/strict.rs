@@ -0,0 +1,114 @@
+//! Detection of configuration keys that don't map to any field.
+
+use crate::{consts, provenance::Locator, secrets, MagicBlockParams};
+use figment::{providers::Serialized, value::Value, Figment};
+use std::fmt::{self, Display};
+
+/// Minimum Jaro-Winkler similarity for a known key to be suggested.
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// A key found in the merged sources that no configuration field consumes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    /// Dotted path of the key, e.g. `ledger.blocks-per-partiton`.
+    pub key: String,
+    /// The source that set the key, e.g. a TOML file.
+    pub origin: Option<String>,
+    /// The closest known key at the same level, if any is similar enough.
+    pub suggestion: Option<String>,
+}
+
+impl Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key `{}`", self.key)?;
+        if let Some(origin) = &self.origin {
+            write!(f, " in {origin}")?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean `{suggestion}`?)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns every key of `figment` that deserializing [`MagicBlockParams`]
+/// would silently ignore. The file directives `include` and `profile`, as
+/// well as deprecated keys, are consumed by the loader itself and never
+/// reported, nor are the variables holding a seed phrase. Secrets are left
+/// for the regular extraction to resolve.
+pub fn unknown_keys(figment: &Figment) -> figment::Result<Vec<UnknownKey>> {
+    let mut value: Value = figment.extract()?;
+    for key in secrets::seed_phrase_keys(figment) {
+        remove(&mut value, &key);
+    }
+    let mut ignored = Vec::new();
+    // Type errors are left to the regular extraction, which reports them
+    // along with the source they came from.
+    let _ = secrets::unresolved(|| {
+        serde_ignored::deserialize(&value, |path| ignored.push(dotted_key(&path)))
+            .map(|_: MagicBlockParams| ())
+    });
+
+    let known = Figment::from(Serialized::defaults(MagicBlockParams::default())).extract()?;
+    let mut locator = Locator::default();
+    let unknown = ignored
+        .into_iter()
+        .filter(|key| {
+            let root = key.split('.').next().unwrap_or_default();
+            root != consts::INCLUDE_KEY
+                && root != consts::PROFILES_KEY
+                && !consts::DEPRECATED_KEYS.iter().any(|(old, _)| old == key)
+        })
+        .map(|key| UnknownKey {
+            origin: locator.of(figment, &key).map(|source| source.to_string()),
+            suggestion: suggest(&known, &key),
+            key,
+        })
+        .collect();
+    Ok(unknown)
+}
+
+/// The dotted key at `path`, leaving out the `?` that stands for the
+/// content of an optional value, e.g. `admin.?.tls`.
+fn dotted_key(path: &serde_ignored::Path) -> String {
+    let path = path.to_string();
+    let segments = path.split('.').filter(|segment| *segment != "?");
+    segments.collect::<Vec<_>>().join(".")
+}
+
+/// Removes the dotted `key` from `value`, along with the tables it leaves
+/// empty.
+fn remove(value: &mut Value, key: &str) {
+    let Value::Dict(_, dict) = value else {
+        return;
+    };
+    match key.split_once('.') {
+        Some((parent, rest)) => {
+            if let Some(child) = dict.get_mut(parent) {
+                remove(child, rest);
+                if child.as_dict().is_some_and(|child| child.is_empty()) {
+                    dict.remove(parent);
+                }
+            }
+        }
+        None => {
+            dict.remove(key);
+        }
+    }
+}
+
+/// Finds the known key most similar to the last segment of `key`, among the
+/// siblings at the same level.
+fn suggest(known: &Value, key: &str) -> Option<String> {
+    let (parent, name) = match key.rsplit_once('.') {
+        Some((parent, name)) => (known.find_ref(parent)?, name),
+        None => (known, key),
+    };
+    parent
+        .as_dict()?
+        .keys()
+        .map(|candidate| (strsim::jaro_winkler(name, candidate), candidate))
+        .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate.clone())
+}
@@ -0,0 +1,432 @@
+//! A commented configuration file listing every key with its default, so new
+//! operators don't have to reverse-engineer the format.
+
+use crate::{consts, secrets, MagicBlockParams};
+use toml::{Table, Value};
+
+/// A documented key of the configuration file, in template order.
+struct Entry {
+    /// Dotted path of the key, or the name of a section.
+    key: &'static str,
+    /// The field's doc comment.
+    doc: &'static str,
+    /// Value shown, commented out, for keys without a default.
+    example: Option<&'static str>,
+}
+
+const fn entry(key: &'static str, doc: &'static str) -> Entry {
+    Entry {
+        key,
+        doc,
+        example: None,
+    }
+}
+
+const fn example(key: &'static str, doc: &'static str, example: &'static str) -> Entry {
+    Entry {
+        key,
+        doc,
+        example: Some(example),
+    }
+}
+
+const ENTRIES: &[Entry] = &[
+    entry(
+        "remote",
+        "Remote Solana cluster URL, a predefined alias (e.g., \"mainnet\") or one of `remote-aliases`. Several can be given separated by commas, e.g. `mainnet,https://backup.example.com`.",
+    ),
+    entry(
+        "remotes",
+        "Remotes declared once by name, e.g. `[remotes.helius]`, for `chainlink.remote` and `commit.remote` to refer to.",
+    ),
+    entry(
+        "remote-aliases",
+        "URLs by alias, e.g. `helius = \"https://mainnet.helius-rpc.com\"`, usable wherever a remote URL is given, like the built-in `mainnet`.",
+    ),
+    entry(
+        "lifecycle-defaults",
+        "Remotes by lifecycle, e.g. `replica = \"mainnet\"`, used in place of the built-in default when no layer sets `remote`.",
+    ),
+    entry(
+        "lifecycle",
+        "The application's operational mode: `ephemeral`, `replica`, `offline` or `programs-replica`.",
+    ),
+    example(
+        "storage",
+        "Root directory for application storage (e.g., accounts, ledger). `~` and environment variables are expanded, and a relative path is relative to this file.",
+        "\"/var/lib/magicblock\"",
+    ),
+    entry(
+        "create-storage-dirs",
+        "Create the `storage` directory, including its parents, if it doesn't exist yet.",
+    ),
+    example(
+        "storage-min-free-space",
+        "Minimum free space, in bytes, required on the volume holding `storage`.",
+        "10737418240",
+    ),
+    entry(
+        "bind-all",
+        "Bind addresses given as a port alone, e.g. `--listen :9000`, to every interface (`0.0.0.0`) instead of the loopback one (`127.0.0.1`).",
+    ),
+    entry(
+        "listen",
+        "Listen addresses for the main RPC service, one or a list, e.g. to bind both IPv4 and IPv6. A port alone, e.g. `:9000`, binds the default interface, see `bind-all`, and `unix:/run/magicblock/rpc.sock` a Unix domain socket.",
+    ),
+    entry(
+        "metrics",
+        "Configuration for the metrics endpoint. `metrics = \"127.0.0.1:9000\"`, like `--metrics`, is shorthand for `metrics.listen`.",
+    ),
+    example(
+        "metrics.listen",
+        "Listen address for the metrics endpoint. If disabled, this is not set. `off`, `disabled` or `none` turn it off, e.g. one enabled by a lower layer.",
+        "\"127.0.0.1:9000\"",
+    ),
+    entry("metrics.path", "HTTP path the metrics are served at."),
+    entry(
+        "metrics.labels",
+        "Labels added to every metric, e.g. `{ cluster = \"devnet\" }`.",
+    ),
+    entry(
+        "metrics.buckets",
+        "Bucket boundaries by histogram name, in place of its built-in ones, e.g. `{ rpc-request-duration = [0.005, 0.01, 0.1, 1.0] }`.",
+    ),
+    entry(
+        "metrics.subsystems",
+        "Whether each subsystem records metrics, e.g. `{ accounts-db = false }`. Subsystems not listed record them.",
+    ),
+    entry(
+        "metrics.push-gateway",
+        "Prometheus push gateway to push the metrics to as well.",
+    ),
+    example(
+        "metrics.push-gateway.url",
+        "URL of the push gateway. Nothing is pushed if not set.",
+        "\"http://pushgateway:9091\"",
+    ),
+    entry(
+        "metrics.push-gateway.interval",
+        "How often the metrics are pushed.",
+    ),
+    entry(
+        "metrics.tls",
+        "Serve the metrics over TLS with this identity. Formerly `[metrics-tls]`.",
+    ),
+    example(
+        "metrics.tls.cert",
+        "PEM file with the certificate chain, leaf first.",
+        "\"/etc/magicblock/tls/metrics.crt\"",
+    ),
+    example(
+        "metrics.tls.key",
+        "PEM file with the private key. Must not be accessible to group or others.",
+        "\"/etc/magicblock/tls/metrics.key\"",
+    ),
+    example(
+        "metrics.tls.ca",
+        "PEM file with the CA certificates to verify client certificates against. Clients need no certificate if not set.",
+        "\"/etc/magicblock/tls/scrapers.crt\"",
+    ),
+    entry("validator", "Configuration for the validator behavior."),
+    entry("validator.basefee", "Base fee in lamports for transactions."),
+    entry(
+        "validator.keypair",
+        "The validator's identity keypair, encoded in Base58 or hex or as a JSON array of bytes, or a reference such as `aws-ssm:/magicblock/prod/keypair` or `file:/etc/magicblock/id.json`, a Solana JSON keypair file. With `prompt`, it is read from the terminal without echo, and with `prompt:fd=3` from file descriptor 3.",
+    ),
+    entry(
+        "validator.signer",
+        "A remote signer holding the identity instead, so that the private key never lives on the validator host. Takes precedence over `keypair`.",
+    ),
+    example(
+        "validator.signer.url",
+        "URL of the signer, e.g. `https://signer.internal:8443`.",
+        "\"https://signer.internal:8443\"",
+    ),
+    example(
+        "validator.signer.token",
+        "Bearer token to authenticate with the signer, or a secret store reference.",
+        "\"aws-ssm:/magicblock/prod/signer-token\"",
+    ),
+    entry(
+        "validator.signer.pubkey",
+        "Public key of the keypair the signer holds.",
+    ),
+    example(
+        "validator.signer.connection",
+        "How to connect to the signer: `connect-timeout`, `request-timeout`, `keepalive`, `pool-size` and `retry`.",
+        "{ request-timeout = \"10s\", retry = { attempts = 5 } }",
+    ),
+    entry(
+        "validator.commit-fee-payer",
+        "The keypair paying the fees of commit transactions, instead of the identity. Set `keypair`, or a `signer` table like `validator.signer`.",
+    ),
+    example(
+        "validator.commit-fee-payer.keypair",
+        "The keypair, encoded in Base58, or a reference to one, e.g. with `file:` or `env:`.",
+        "\"file:/etc/magicblock/commit-fee-payer.json\"",
+    ),
+    example(
+        "validator.commit-fee-payer.signer",
+        "A remote signer holding the keypair instead, with the keys of `validator.signer`. Takes precedence over `keypair`.",
+        "{ url = \"https://signer.internal:8443\", token = \"env:MB_COMMIT_SIGNER_TOKEN\", pubkey = \"\" }",
+    ),
+    entry(
+        "validator.fee-claim-authority",
+        "The keypair with authority to claim the validator's fees, instead of the identity. Set `keypair`, or a `signer` table like `validator.signer`.",
+    ),
+    example(
+        "validator.fee-claim-authority.keypair",
+        "The keypair, encoded in Base58, or a reference to one, e.g. with `file:` or `env:`.",
+        "\"file:/etc/magicblock/fee-claim-authority.json\"",
+    ),
+    example(
+        "validator.fee-claim-authority.signer",
+        "A remote signer holding the keypair instead, with the keys of `validator.signer`. Takes precedence over `keypair`.",
+        "{ url = \"https://signer.internal:8443\", token = \"env:MB_FEE_CLAIM_SIGNER_TOKEN\", pubkey = \"\" }",
+    ),
+    entry(
+        "commit",
+        "Defines the strategy for committing transactions to the ledger.",
+    ),
+    entry(
+        "commit.compute-unit-price",
+        "Compute unit price in micro-lamports for commit transactions.",
+    ),
+    example(
+        "commit.remote",
+        "Name of the remote in `[remotes]` to send commit transactions to, instead of the primary `remote`.",
+        "\"triton\"",
+    ),
+    entry("accounts-db", "Configuration for the accounts database."),
+    entry(
+        "accounts-db.database-size",
+        "Accounts database size, in bytes or with a unit, e.g. `512MiB` or `8GB`.",
+    ),
+    entry(
+        "accounts-db.block-size",
+        "Block size of the database in bytes: 128, 256 or 512.",
+    ),
+    entry(
+        "accounts-db.index-size",
+        "Accounts index size, in bytes or with a unit, smaller than `database-size`.",
+    ),
+    entry("accounts-db.max-snapshots", "Number of snapshots to keep."),
+    entry(
+        "accounts-db.snapshot-frequency",
+        "Number of blocks between snapshots, at most `ledger.blocks-per-partition`.",
+    ),
+    entry("ledger", "Configuration for the ledger database."),
+    entry(
+        "ledger.blocks-per-partition",
+        "Number of blocks stored in each ledger partition.",
+    ),
+    entry("ledger.block-time", "Target time per blocks"),
+    entry("ledger.reset", "Whether to reset the ledger on startup."),
+    entry(
+        "chainlink",
+        "Configuration specific to ChainLink oracle integration.",
+    ),
+    entry(
+        "chainlink.prepare-lookup-tables",
+        "Whether to prepare address lookup tables.",
+    ),
+    entry(
+        "chainlink.auto-airdrop-lamports",
+        "Lamports to airdrop automatically, or an amount such as `1 SOL`.",
+    ),
+    entry(
+        "chainlink.commitment",
+        "Commitment at which accounts are cloned: `processed`, `confirmed` or `finalized`.",
+    ),
+    entry(
+        "chainlink.max-monitored-accounts",
+        "Maximum number of monitored accounts.",
+    ),
+    example(
+        "chainlink.remote",
+        "Name of the remote in `[remotes]` to clone accounts from, instead of the primary `remote`.",
+        "\"helius\"",
+    ),
+    entry(
+        "failover",
+        "When to fail over between the `primary` and `fallback` remotes.",
+    ),
+    entry(
+        "failover.error-threshold",
+        "Consecutive failed requests after which the primary is failed over.",
+    ),
+    entry(
+        "failover.cooldown",
+        "How long to stay on the fallbacks before retrying the primary, e.g. `30s`.",
+    ),
+    entry("network", "Settings for outbound connections."),
+    entry(
+        "network.proxy",
+        "Proxy to reach the remote clusters through, instead of the `HTTP_PROXY` family of variables.",
+    ),
+    example(
+        "network.proxy.url",
+        "URL of the proxy, with scheme `http`, `https`, `socks5` or `socks5h`. Remotes are connected to directly if not set.",
+        "\"http://proxy.internal:3128\"",
+    ),
+    entry(
+        "network.proxy.no-proxy",
+        "Hosts to connect to directly, including their subdomains, e.g. `[\"localhost\", \".internal\"]`.",
+    ),
+    entry(
+        "log",
+        "Configuration for logging, for binaries to set up their logger with.",
+    ),
+    entry(
+        "log.level",
+        "Level of the modules without a filter of their own: `off`, `error`, `warn`, `info`, `debug` or `trace`. Raised by `-v` and lowered by `-q`.",
+    ),
+    entry(
+        "log.filters",
+        "Levels of individual modules, e.g. `{ magicblock_ledger = \"debug\" }`.",
+    ),
+    entry(
+        "log.format",
+        "Format of the log lines: `full`, `compact` or `json`.",
+    ),
+    entry(
+        "tracing",
+        "Configuration for exporting traces over OTLP, for binaries to set up their OpenTelemetry pipeline with.",
+    ),
+    example(
+        "tracing.endpoint",
+        "URL of the OTLP collector. Traces are not exported if not set.",
+        "\"http://127.0.0.1:4317\"",
+    ),
+    entry(
+        "tracing.protocol",
+        "Protocol spoken with the collector: `grpc`, `http/protobuf` or `http/json`.",
+    ),
+    entry(
+        "tracing.headers",
+        "Headers sent with every export, e.g. `{ x-api-key = \"env:OTLP_API_KEY\" }`. Values can be secret references.",
+    ),
+    entry(
+        "tracing.sampling-ratio",
+        "Share of traces to sample, e.g. `10%` or `0.1`.",
+    ),
+    entry(
+        "tracing.resource-attributes",
+        "Attributes of the resource the traces come from, e.g. `{ \"service.name\" = \"magicblock-validator\" }`.",
+    ),
+    entry(
+        "chain-operation",
+        "Configuration for on-chain operations and validator identity. Required in `ephemeral` mode.",
+    ),
+    example(
+        "chain-operation.country-code",
+        "Validator's two-letter country code (e.g., \"US\").",
+        "\"US\"",
+    ),
+    example(
+        "chain-operation.fqdn",
+        "Validator's fully qualified domain name (FQDN).",
+        "\"https://validator.example.com\"",
+    ),
+    example(
+        "chain-operation.claim-fees-frequency",
+        "How often to claim fees from the chain",
+        "\"24h\"",
+    ),
+    entry(
+        "admin",
+        "Configuration for the admin HTTP service, served with the `admin` feature.",
+    ),
+    example(
+        "admin.listen",
+        "Listen address of the admin service.",
+        "\"127.0.0.1:9900\"",
+    ),
+    example(
+        "admin.token",
+        "Bearer token every request must carry, or a secret store reference.",
+        "\"aws-ssm:/magicblock/prod/admin-token\"",
+    ),
+    entry("tls", "Serve the `listen` endpoints over TLS with this identity."),
+    example(
+        "tls.cert",
+        "PEM file with the certificate chain, leaf first.",
+        "\"/etc/magicblock/tls/rpc.crt\"",
+    ),
+    example(
+        "tls.key",
+        "PEM file with the private key. Must not be accessible to group or others.",
+        "\"/etc/magicblock/tls/rpc.key\"",
+    ),
+    example(
+        "tls.ca",
+        "PEM file with the CA certificates to verify client certificates against. Clients need no certificate if not set.",
+        "\"/etc/magicblock/tls/clients.crt\"",
+    ),
+];
+
+impl MagicBlockParams {
+    /// A configuration file listing every key, documented, with its default
+    /// value. Keys without a default are included commented out.
+    pub fn template() -> String {
+        // The built-in default keypair is well known, not a secret.
+        let defaults = secrets::exposed(|| Value::try_from(Self::default()))
+            .ok()
+            .and_then(|value| value.try_into::<Table>().ok())
+            .unwrap_or_default();
+        let mut out = String::new();
+        out.push_str("# Version of the configuration format this file was written for.\n");
+        out.push_str(&format!(
+            "{} = {}\n",
+            consts::CONFIG_VERSION_KEY,
+            consts::CONFIG_VERSION
+        ));
+        for entry in ENTRIES {
+            let value = lookup(&defaults, entry.key);
+            let name = entry
+                .key
+                .rsplit_once('.')
+                .map_or(entry.key, |(_, name)| name);
+            out.push('\n');
+            out.push_str(&format!("# {}\n", entry.doc));
+            match value {
+                // A section, which is left out by default if it's optional.
+                _ if is_section(entry.key) => {
+                    let comment = if value.is_some() { "" } else { "# " };
+                    out.push_str(&format!("{comment}[{}]\n", entry.key));
+                }
+                Some(value) => out.push_str(&format!("{name} = {value}\n")),
+                None => {
+                    let example = entry.example.unwrap_or("\"\"");
+                    out.push_str(&format!("# {name} = {example}\n"));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The value of the dotted `key` in `table`, if set.
+pub(crate) fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let first = table.get(parts.next()?)?;
+    parts.try_fold(first, |value, part| value.get(part))
+}
+
+/// The doc comment of the dotted `key`, if it's a documented key or section.
+pub(crate) fn doc(key: &str) -> Option<&'static str> {
+    ENTRIES
+        .iter()
+        .find(|entry| entry.key == key)
+        .map(|entry| entry.doc)
+}
+
+/// Whether `key` names a section rather than a value.
+fn is_section(key: &str) -> bool {
+    ENTRIES.iter().any(|entry| {
+        entry
+            .key
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
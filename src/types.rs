@@ -1,18 +1,356 @@
 use crate::consts;
+use crate::secrets::{self, SecretError, SeedPhraseRef};
 use derive_more::{Display, FromStr};
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 use solana_keypair::Keypair;
-use solana_pubkey::Pubkey;
-use std::convert::Infallible;
+use solana_pubkey::{ParsePubkeyError, Pubkey};
+use std::collections::BTreeSet;
 use std::fmt::{Debug, Display};
-use std::net::SocketAddr;
+use std::marker::PhantomData;
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs};
+use std::num::ParseIntError;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
-/// A network bind address that can be parsed from a string like "0.0.0.0:8080".
-#[derive(Clone, Debug, Deserialize, Serialize, FromStr, Display)]
-#[serde(transparent)]
-pub struct BindAddress(pub SocketAddr);
+/// Prefix of a [`BindAddress`] naming a Unix domain socket.
+const UNIX_PREFIX: &str = "unix:";
+
+/// A bind address: a network address parsed from a string like
+/// "0.0.0.0:8080", "[::]:8080" or "node1.internal:8080", or a Unix domain
+/// socket such as "unix:/run/magicblock/rpc.sock". Hostnames are resolved
+/// when parsed, see [`BindAddress::from_str`]. A port alone, as ":8080" or
+/// the integer 8080, binds the default interface, see [`BindAddress::expand`].
+/// An IPv6 address can also be given as a table, e.g.
+/// `{ addr = "[::]:8080", v6-only = false }`, to choose whether it accepts
+/// IPv4 connections as well instead of relying on the OS default.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindAddress {
+    /// A TCP socket address.
+    Tcp {
+        address: SocketAddr,
+        /// Whether an IPv6 socket only accepts IPv6 connections
+        /// (`IPV6_V6ONLY`), or the OS default if not set.
+        v6_only: Option<bool>,
+    },
+    /// A port alone, whose interface is yet to be chosen by
+    /// [`BindAddress::expand`].
+    Port(u16),
+    /// A Unix domain socket.
+    Unix(UnixSocket),
+}
+
+impl BindAddress {
+    /// The TCP socket address, on the loopback interface for a port alone,
+    /// or `None` for a Unix domain socket.
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp { address, .. } => Some(*address),
+            Self::Port(port) => Some((Ipv4Addr::LOCALHOST, *port).into()),
+            Self::Unix(_) => None,
+        }
+    }
+
+    /// Binds a port alone to every interface if `bind_all` is set, or to the
+    /// loopback interface otherwise. Other addresses are kept as they are.
+    pub fn expand(&mut self, bind_all: bool) {
+        if let Self::Port(port) = *self {
+            let ip = if bind_all {
+                Ipv4Addr::UNSPECIFIED
+            } else {
+                Ipv4Addr::LOCALHOST
+            };
+            *self = Self::Tcp {
+                address: (ip, port).into(),
+                v6_only: None,
+            };
+        }
+    }
+
+    /// Binds a TCP listener on the address, on the loopback interface for a
+    /// port alone, applying `v6_only` if set. Fails for a Unix domain
+    /// socket, see [`UnixSocket::bind`].
+    pub fn bind_tcp(&self) -> std::io::Result<std::net::TcpListener> {
+        let unsupported = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{self} is not a TCP address"),
+            )
+        };
+        let address = self.socket_addr().ok_or_else(unsupported)?;
+        match self {
+            Self::Tcp {
+                v6_only: Some(v6_only),
+                ..
+            } if address.is_ipv6() => bind_ipv6(address, *v6_only),
+            _ => std::net::TcpListener::bind(address),
+        }
+    }
+}
+
+/// Binds an IPv6 listener with `IPV6_V6ONLY` set to `v6_only`, which has to
+/// happen between creating and binding the socket.
+#[cfg(unix)]
+fn bind_ipv6(address: SocketAddr, v6_only: bool) -> std::io::Result<std::net::TcpListener> {
+    use rustix::net::{self, sockopt, AddressFamily, SocketType};
+
+    let socket = net::socket(AddressFamily::INET6, SocketType::STREAM, None)?;
+    // As `TcpListener::bind` does, so that a restart can rebind right away.
+    sockopt::set_socket_reuseaddr(&socket, true)?;
+    sockopt::set_ipv6_v6only(&socket, v6_only)?;
+    net::bind(&socket, &address)?;
+    net::listen(&socket, 128)?;
+    Ok(std::net::TcpListener::from(socket))
+}
+
+/// Elsewhere IPv6 sockets are left with the OS default, IPv6 only on
+/// Windows.
+#[cfg(not(unix))]
+fn bind_ipv6(address: SocketAddr, _v6_only: bool) -> std::io::Result<std::net::TcpListener> {
+    std::net::TcpListener::bind(address)
+}
+
+/// A Unix domain socket to listen on, written as
+/// "unix:/run/magicblock/rpc.sock", optionally followed by the permissions
+/// and ownership to give the socket file once bound, as in
+/// "unix:/run/magicblock/rpc.sock?mode=0660&owner=magicblock&group=proxy".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnixSocket {
+    /// Path of the socket file.
+    pub path: PathBuf,
+    /// Permissions of the socket file, given in octal, e.g. `0660`.
+    pub mode: Option<u32>,
+    /// Owner of the socket file, a user name or id.
+    pub owner: Option<String>,
+    /// Group of the socket file, a group name or id.
+    pub group: Option<String>,
+}
+
+impl UnixSocket {
+    fn parse(s: &str) -> Result<Self, String> {
+        let (path, options) = s.split_once('?').unwrap_or((s, ""));
+        if path.is_empty() {
+            return Err("missing socket path".to_owned());
+        }
+        let mut socket = Self {
+            path: path.into(),
+            mode: None,
+            owner: None,
+            group: None,
+        };
+        for option in options.split('&').filter(|option| !option.is_empty()) {
+            let (name, value) = option
+                .split_once('=')
+                .ok_or_else(|| format!("option `{option}` has no value"))?;
+            match name {
+                "mode" => {
+                    let mode = u32::from_str_radix(value, 8)
+                        .ok()
+                        .filter(|mode| *mode <= 0o7777)
+                        .ok_or_else(|| {
+                            format!("invalid mode `{value}`, expected octal such as `0660`")
+                        })?;
+                    socket.mode = Some(mode);
+                }
+                "owner" => socket.owner = Some(value.to_owned()),
+                "group" => socket.group = Some(value.to_owned()),
+                _ => {
+                    return Err(format!(
+                        "unknown option `{name}`, expected `mode`, `owner` or `group`"
+                    ))
+                }
+            }
+        }
+        Ok(socket)
+    }
+}
+
+impl Display for UnixSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{UNIX_PREFIX}{}", self.path.display())?;
+        let options = [
+            ("mode", self.mode.map(|mode| format!("{mode:04o}"))),
+            ("owner", self.owner.clone()),
+            ("group", self.group.clone()),
+        ];
+        let mut separator = '?';
+        for (name, value) in options {
+            if let Some(value) = value {
+                write!(f, "{separator}{name}={value}")?;
+                separator = '&';
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl UnixSocket {
+    /// Binds the socket, replacing a stale socket file left behind by a
+    /// previous run, and applies `mode`, `owner` and `group` to the file.
+    pub fn bind(&self) -> std::io::Result<std::os::unix::net::UnixListener> {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let is_socket = std::fs::symlink_metadata(&self.path)
+            .is_ok_and(|metadata| metadata.file_type().is_socket());
+        if is_socket && UnixStream::connect(&self.path).is_err() {
+            std::fs::remove_file(&self.path)?;
+        }
+        let listener = UnixListener::bind(&self.path)?;
+        if let Some(mode) = self.mode {
+            std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(mode))?;
+        }
+        if self.owner.is_some() || self.group.is_some() {
+            let owner = self.owner.as_deref().map(ownership::user_id).transpose()?;
+            let group = self.group.as_deref().map(ownership::group_id).transpose()?;
+            std::os::unix::fs::chown(&self.path, owner, group)?;
+        }
+        Ok(listener)
+    }
+}
+
+/// Looks up user and group ids by name, for [`UnixSocket::bind`].
+#[cfg(unix)]
+mod ownership {
+    use std::{ffi::CString, io, ptr};
+
+    /// Size of the buffer holding the strings of a looked-up entry.
+    const BUFFER_SIZE: usize = 16 * 1024;
+
+    /// The id of `user`, given by name or as a number.
+    pub fn user_id(user: &str) -> io::Result<u32> {
+        if let Ok(id) = user.parse() {
+            return Ok(id);
+        }
+        let name = CString::new(user)?;
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut found = ptr::null_mut();
+        // SAFETY: every pointer is valid for the duration of the call, and
+        // `buffer.len()` is the size of `buffer`.
+        let code = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                &mut entry,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut found,
+            )
+        };
+        match (code, found.is_null()) {
+            (0, false) => Ok(entry.pw_uid),
+            (0, true) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no such user `{user}`"),
+            )),
+            (code, _) => Err(io::Error::from_raw_os_error(code)),
+        }
+    }
+
+    /// The id of `group`, given by name or as a number.
+    pub fn group_id(group: &str) -> io::Result<u32> {
+        if let Ok(id) = group.parse() {
+            return Ok(id);
+        }
+        let name = CString::new(group)?;
+        let mut entry: libc::group = unsafe { std::mem::zeroed() };
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut found = ptr::null_mut();
+        // SAFETY: as in `user_id`.
+        let code = unsafe {
+            libc::getgrnam_r(
+                name.as_ptr(),
+                &mut entry,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut found,
+            )
+        };
+        match (code, found.is_null()) {
+            (0, false) => Ok(entry.gr_gid),
+            (0, true) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no such group `{group}`"),
+            )),
+            (code, _) => Err(io::Error::from_raw_os_error(code)),
+        }
+    }
+}
+
+/// An error raised when parsing a [`BindAddress`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BindAddressError {
+    #[error("cannot resolve `{address}`: {reason}")]
+    Unresolved { address: String, reason: String },
+    #[error("`{address}` resolves to several addresses, {candidates}, give one of them instead")]
+    Ambiguous { address: String, candidates: String },
+    #[error("invalid Unix socket `{address}`: {reason}")]
+    Unix { address: String, reason: String },
+}
+
+/// Parses a Unix domain socket after `unix:`, an IP address and port, a port
+/// after `:`, or else resolves a hostname and port. A
+/// hostname must resolve to a single address, except that one IPv4 address
+/// is chosen over IPv6 ones, as for `localhost`, so that the same address is
+/// bound on every start.
+impl FromStr for BindAddress {
+    type Err = BindAddressError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix(UNIX_PREFIX) {
+            return UnixSocket::parse(path).map(Self::Unix).map_err(|reason| {
+                BindAddressError::Unix {
+                    address: s.to_owned(),
+                    reason,
+                }
+            });
+        }
+        if let Ok(address) = s.parse() {
+            return Ok(Self::Tcp {
+                address,
+                v6_only: None,
+            });
+        }
+        if let Some(port) = s.strip_prefix(':') {
+            return port
+                .parse()
+                .map(Self::Port)
+                .map_err(|e| BindAddressError::Unresolved {
+                    address: s.to_owned(),
+                    reason: format!("invalid port: {e}"),
+                });
+        }
+        let unresolved = |reason: String| BindAddressError::Unresolved {
+            address: s.to_owned(),
+            reason,
+        };
+        let candidates: BTreeSet<SocketAddr> = s
+            .to_socket_addrs()
+            .map_err(|e| unresolved(e.to_string()))?
+            .collect();
+        let ipv4: Vec<_> = candidates.iter().filter(|a| a.is_ipv4()).collect();
+        let tcp = |address: SocketAddr| Self::Tcp {
+            address,
+            v6_only: None,
+        };
+        match (candidates.len(), ipv4.as_slice()) {
+            (0, _) => Err(unresolved("no addresses found".to_owned())),
+            (1, _) => Ok(tcp(*candidates.first().expect("one candidate"))),
+            (_, [address]) => Ok(tcp(**address)),
+            _ => Err(BindAddressError::Ambiguous {
+                address: s.to_owned(),
+                candidates: candidates
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }),
+        }
+    }
+}
 
 impl Default for BindAddress {
     fn default() -> Self {
@@ -20,11 +358,338 @@ impl Default for BindAddress {
     }
 }
 
+/// Shows a port alone as ":8080", so that it round-trips through the
+/// configuration layers until it is expanded. `v6_only` is left out, see
+/// the `Serialize` implementation.
+impl Display for BindAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp { address, .. } => Display::fmt(address, f),
+            Self::Port(port) => write!(f, ":{port}"),
+            Self::Unix(socket) => Display::fmt(socket, f),
+        }
+    }
+}
+
+/// Serializes as the address string, or as a table if `v6_only` is set.
+impl Serialize for BindAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        match self {
+            Self::Tcp {
+                address,
+                v6_only: Some(v6_only),
+            } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("addr", &address.to_string())?;
+                map.serialize_entry("v6-only", v6_only)?;
+                map.end()
+            }
+            address => serializer.collect_str(address),
+        }
+    }
+}
+
+/// Accepts an address string, an integer port as a shorthand for ":port", or
+/// a table with the address under `addr` and `v6-only` for an IPv6 address.
+impl<'de> Deserialize<'de> for BindAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = BindAddress;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a socket address, a hostname and port, a port or a Unix socket")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut address, mut v6_only) = (None, None);
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "addr" => address = Some(map.next_value::<String>()?),
+                        "v6-only" => v6_only = Some(map.next_value::<bool>()?),
+                        key => return Err(de::Error::unknown_field(key, &["addr", "v6-only"])),
+                    }
+                }
+                let address = address.ok_or_else(|| de::Error::missing_field("addr"))?;
+                match address.parse().map_err(de::Error::custom)? {
+                    Self::Value::Tcp { address, .. } if address.is_ipv6() => {
+                        Ok(Self::Value::Tcp { address, v6_only })
+                    }
+                    _ if v6_only.is_some() => Err(de::Error::custom(format!(
+                        "`v6-only` only applies to IPv6 addresses, not `{address}`"
+                    ))),
+                    parsed => Ok(parsed),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                let port = u16::try_from(v)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &"a port"))?;
+                Ok(BindAddress::Port(port))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                let port = u16::try_from(v)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &"a port"))?;
+                Ok(BindAddress::Port(port))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Whether `s` is one of [`consts::DISABLED_ENDPOINT`], e.g. `off`.
+fn is_disabled(s: &str) -> bool {
+    consts::DISABLED_ENDPOINT
+        .iter()
+        .any(|disabled| s.eq_ignore_ascii_case(disabled))
+}
+
+/// Parses an optional endpoint given on the command line, `None` if it is
+/// turned off with `off`, `disabled` or `none`.
+pub fn parse_endpoint<T: FromStr>(s: &str) -> Result<Option<T>, T::Err> {
+    if is_disabled(s) {
+        return Ok(None);
+    }
+    s.parse().map(Some)
+}
+
+/// Deserializes an optional endpoint, `None` if it is turned off with `off`,
+/// `disabled` or `none`, so that a higher layer can turn off an endpoint a
+/// lower one enabled. Use with `#[serde(deserialize_with)]`.
+pub fn deserialize_endpoint<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct Visitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+        type Value = Option<T>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("an endpoint, or `off` to turn it off")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            if is_disabled(s) {
+                return Ok(None);
+            }
+            T::deserialize(de::value::StrDeserializer::new(s)).map(Some)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            T::deserialize(de::value::U64Deserializer::new(v)).map(Some)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            T::deserialize(de::value::I64Deserializer::new(v)).map(Some)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            T::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(Some)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            T::deserialize(de::value::MapAccessDeserializer::new(map)).map(Some)
+        }
+    }
+
+    deserializer.deserialize_option(Visitor(PhantomData))
+}
+
 /// A wrapper for `solana_pubkey::Pubkey` to enable deserializing from Base58.
 #[derive(Clone, Debug, DeserializeFromStr, SerializeDisplay, FromStr, Display)]
 pub struct SerdePubkey(pub Pubkey);
 
-/// A wrapper for `solana_keypair::Keypair` to enable Serde.
+/// An ordered list of public keys, for allowlists and denylists. Parses from
+/// a comma-separated string such as `"key1, key2"` or from an array of
+/// Base58 strings, and drops repeated keys while keeping the first
+/// occurrence of each. Collect a `Vec<SerdePubkey>` CLI argument declared
+/// with `value_delimiter = ','` into it to accept repeated flags as well.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SerdePubkeyList(Vec<Pubkey>);
+
+/// A sorted set of public keys, parsed like [`SerdePubkeyList`] when the
+/// order of the keys does not matter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SerdePubkeySet(pub BTreeSet<Pubkey>);
+
+/// Splits a comma-separated list of Base58 keys, skipping empty entries.
+fn parse_pubkeys<C: FromIterator<Pubkey>>(s: &str) -> Result<C, ParsePubkeyError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(Pubkey::from_str)
+        .collect()
+}
+
+/// Accepts a comma-separated string or an array of Base58 strings.
+fn deserialize_pubkeys<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<Pubkey>,
+{
+    struct Visitor<C>(PhantomData<C>);
+
+    impl<'de, C: FromIterator<Pubkey>> de::Visitor<'de> for Visitor<C> {
+        type Value = C;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("a comma-separated string or an array of public keys")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            parse_pubkeys(s).map_err(E::custom)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut keys = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(SerdePubkey(key)) = seq.next_element()? {
+                keys.push(key);
+            }
+            Ok(keys.into_iter().collect())
+        }
+    }
+
+    deserializer.deserialize_any(Visitor(PhantomData))
+}
+
+impl SerdePubkeyList {
+    pub fn into_inner(self) -> Vec<Pubkey> {
+        self.0
+    }
+}
+
+impl Deref for SerdePubkeyList {
+    type Target = [Pubkey];
+    fn deref(&self) -> &[Pubkey] {
+        &self.0
+    }
+}
+
+impl FromIterator<Pubkey> for SerdePubkeyList {
+    fn from_iter<I: IntoIterator<Item = Pubkey>>(iter: I) -> Self {
+        let mut seen = BTreeSet::new();
+        Self(iter.into_iter().filter(|key| seen.insert(*key)).collect())
+    }
+}
+
+impl FromIterator<SerdePubkey> for SerdePubkeyList {
+    fn from_iter<I: IntoIterator<Item = SerdePubkey>>(iter: I) -> Self {
+        iter.into_iter().map(|SerdePubkey(key)| key).collect()
+    }
+}
+
+impl FromStr for SerdePubkeyList {
+    type Err = ParsePubkeyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_pubkeys(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdePubkeyList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_pubkeys(deserializer)
+    }
+}
+
+impl Serialize for SerdePubkeyList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(Pubkey::to_string))
+    }
+}
+
+impl Display for SerdePubkeyList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_pubkeys(f, &self.0)
+    }
+}
+
+impl Deref for SerdePubkeySet {
+    type Target = BTreeSet<Pubkey>;
+    fn deref(&self) -> &BTreeSet<Pubkey> {
+        &self.0
+    }
+}
+
+impl FromIterator<Pubkey> for SerdePubkeySet {
+    fn from_iter<I: IntoIterator<Item = Pubkey>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<SerdePubkey> for SerdePubkeySet {
+    fn from_iter<I: IntoIterator<Item = SerdePubkey>>(iter: I) -> Self {
+        iter.into_iter().map(|SerdePubkey(key)| key).collect()
+    }
+}
+
+impl FromStr for SerdePubkeySet {
+    type Err = ParsePubkeyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_pubkeys(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdePubkeySet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_pubkeys(deserializer)
+    }
+}
+
+impl Serialize for SerdePubkeySet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(Pubkey::to_string))
+    }
+}
+
+impl Display for SerdePubkeySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_pubkeys(f, &self.0)
+    }
+}
+
+/// Writes the keys comma-separated, the form accepted by `FromStr`.
+fn write_pubkeys<'a>(
+    f: &mut std::fmt::Formatter<'_>,
+    keys: impl IntoIterator<Item = &'a Pubkey>,
+) -> std::fmt::Result {
+    for (i, key) in keys.into_iter().enumerate() {
+        if i > 0 {
+            f.write_str(",")?;
+        }
+        write!(f, "{key}")?;
+    }
+    Ok(())
+}
+
+/// A wrapper for `solana_keypair::Keypair` to enable Serde. Parses from
+/// Base58, from the JSON array of bytes found in Solana keypair files, or
+/// from the bytes in hex. Parsing [`secrets::PROMPT`] reads the keypair from
+/// the terminal instead, and `prompt:fd=<n>` from an inherited file
+/// descriptor. The secret key is wiped from memory when dropped, but not the
+/// string it was parsed from, see [`SecretRef`].
 #[derive(DeserializeFromStr, SerializeDisplay, PartialEq)]
 pub struct SerdeKeypair(pub Keypair);
 
@@ -34,21 +699,1101 @@ impl Clone for SerdeKeypair {
     }
 }
 
+/// Replaces the keypair with the one made from an all-zero seed, wiping the
+/// replaced secret key as it's dropped.
+impl Zeroize for SerdeKeypair {
+    fn zeroize(&mut self) {
+        self.0 = Keypair::new_from_array([0; 32]);
+    }
+}
+
+/// The secret key of the inner keypair wipes itself when dropped.
+impl ZeroizeOnDrop for SerdeKeypair {}
+
+/// An error raised when parsing a [`SerdeKeypair`]. The message never
+/// includes the (possibly secret) input.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum KeypairError {
+    #[error("keypair is not valid Base58")]
+    Base58,
+    #[error("keypair is not a valid JSON array of bytes")]
+    Json,
+    #[error("keypair is not valid hex")]
+    Hex,
+    #[error("cannot read the keypair: {0}")]
+    Prompt(SecretError),
+    #[error("invalid keypair: {0}")]
+    Invalid(String),
+}
+
 impl FromStr for SerdeKeypair {
-    type Err = Infallible;
+    type Err = KeypairError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(Keypair::from_base58_string(s)))
+        if secrets::is_prompt(s) {
+            let answer =
+                secrets::prompt(s, secrets::prompt_label()).map_err(KeypairError::Prompt)?;
+            return Self::decode(&answer);
+        }
+        Self::decode(s)
+    }
+}
+
+impl SerdeKeypair {
+    /// Decodes a keypair given in Base58, as a JSON array of bytes, or in
+    /// hex. Hex is told apart from Base58 by a `0x` prefix or by its length,
+    /// 128 digits, which no Base58-encoded keypair reaches.
+    fn decode(s: &str) -> Result<Self, KeypairError> {
+        let is_hex = |s: &str| s.len() == 128 && s.bytes().all(|b| b.is_ascii_hexdigit());
+        let bytes = Zeroizing::new(if s.trim_start().starts_with('[') {
+            serde_json::from_str::<Vec<u8>>(s).map_err(|_| KeypairError::Json)?
+        } else if let Some(hex) = s.strip_prefix("0x") {
+            decode_hex(hex).ok_or(KeypairError::Hex)?
+        } else if is_hex(s) {
+            decode_hex(s).ok_or(KeypairError::Hex)?
+        } else {
+            bs58::decode(s)
+                .into_vec()
+                .map_err(|_| KeypairError::Base58)?
+        });
+        let keypair = Keypair::try_from(bytes.as_slice())
+            .map_err(|e| KeypairError::Invalid(e.to_string()))?;
+        Ok(Self(keypair))
+    }
+}
+
+/// Decodes `s`, a string of hex digits, or returns `None` if it isn't one.
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
     }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
 }
 
 impl Display for SerdeKeypair {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.to_base58_string())
+        f.write_str(&Zeroizing::new(self.0.to_base58_string()))
     }
 }
 
+/// Redacted, unlike `Display`, so that debug output of the types holding a
+/// keypair doesn't leak it.
 impl Debug for SerdeKeypair {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self}")
+        f.write_str(consts::REDACTED)
+    }
+}
+
+/// A value kept out of logs and printed configurations. `Debug`, `Display`
+/// and `Serialize` write `***REDACTED***` instead of the value, which is only
+/// reachable through [`Secret::expose_secret`]. The value is wiped from
+/// memory when dropped, but not an inline value it was parsed from, which the
+/// configuration sources keep copies of.
+#[derive(Clone, PartialEq, DeserializeFromStr)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wraps `value`.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The secret value. Take care not to log it.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize + FromStr> FromStr for Secret<T> {
+    type Err = T::Err;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl<T: Zeroize> Display for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(consts::REDACTED)
+    }
+}
+
+impl<T: Zeroize> Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(consts::REDACTED)
+    }
+}
+
+/// Redacted, except while merging configurations internally, see
+/// [`secrets::exposed`].
+impl<T: Zeroize + Serialize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if secrets::is_exposed() {
+            self.0.serialize(serializer)
+        } else {
+            serializer.serialize_str(consts::REDACTED)
+        }
+    }
+}
+
+impl<T: Zeroize> Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Secret<T> {}
+
+/// A value that [`SecretRef`] can hold.
+pub trait SecretValue: FromStr {
+    /// Whether the value is a keypair, which can also be written as an array
+    /// of bytes or derived from a seed phrase. Anything else must be a
+    /// string.
+    const IS_KEYPAIR: bool = false;
+}
+
+impl SecretValue for Secret<String> {}
+
+impl SecretValue for Secret<SerdeKeypair> {
+    const IS_KEYPAIR: bool = true;
+}
+
+/// A value given either inline or as a reference to a secret store or file,
+/// such as `aws-ssm:/magicblock/prod/keypair`, or, for keypairs, derived
+/// from a seed phrase, see [`SeedPhraseRef`]. References are resolved when
+/// parsed, once per load as the configuration is extracted, and serialized
+/// back as the reference, never as the resolved secret. Unlike an inline
+/// value, a resolved secret never enters the configuration sources, so it
+/// isn't left behind in their memory.
+#[derive(Clone)]
+pub struct SecretRef<T> {
+    value: T,
+    reference: Option<Reference>,
+}
+
+/// Where a [`SecretRef`] was resolved from.
+#[derive(Clone, Debug)]
+enum Reference {
+    /// A secret store or file reference.
+    Uri(String),
+    SeedPhrase(SeedPhraseRef),
+}
+
+impl<T> SecretRef<T> {
+    /// Wraps an inline value.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            reference: None,
+        }
+    }
+
+    /// The secret store or file reference the value was resolved from, if
+    /// any.
+    pub fn reference(&self) -> Option<&str> {
+        match &self.reference {
+            Some(Reference::Uri(reference)) => Some(reference),
+            _ => None,
+        }
+    }
+
+    /// The seed phrase the value was derived from, if any.
+    pub fn seed_phrase(&self) -> Option<&SeedPhraseRef> {
+        match &self.reference {
+            Some(Reference::SeedPhrase(reference)) => Some(reference),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromStr> SecretRef<T>
+where
+    T::Err: Display,
+{
+    /// Parses `s` like [`SecretRef::from_str`], except that a reference, or
+    /// a prompt, is kept as written with `placeholder` standing in for its
+    /// value. Meant for values that are only carried over into the
+    /// configuration sources, such as command-line arguments, so that
+    /// references are resolved once, when the configuration is extracted.
+    pub(crate) fn parse_unresolved(
+        s: &str,
+        placeholder: impl FnOnce() -> T,
+    ) -> Result<Self, SecretError> {
+        if !secrets::is_reference(s) && !secrets::is_prompt(s) {
+            return s.parse();
+        }
+        Ok(Self {
+            value: placeholder(),
+            reference: Some(Reference::Uri(s.to_owned())),
+        })
+    }
+
+    /// Derives the value from the seed phrase `reference` points to.
+    fn from_seed_phrase(reference: SeedPhraseRef) -> Result<Self, SecretError> {
+        let value = match secrets::is_unresolved() {
+            true => stand_in()?,
+            false => parse_secret(&reference.resolve()?)?,
+        };
+        Ok(Self {
+            value,
+            reference: Some(Reference::SeedPhrase(reference)),
+        })
+    }
+}
+
+fn parse_secret<T: FromStr>(s: &str) -> Result<T, SecretError>
+where
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| SecretError(format!("invalid secret value: {e}")))
+}
+
+/// The value standing in for an unresolved secret, see
+/// [`secrets::unresolved`]. The built-in keypair reads both as a keypair and
+/// as a string.
+fn stand_in<T: FromStr>() -> Result<T, SecretError>
+where
+    T::Err: Display,
+{
+    parse_secret(consts::DEFAULT_VALIDATOR_KEYPAIR)
+}
+
+impl<T> Deref for SecretRef<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// Secrets are equal when their values are, regardless of where they came from.
+impl<T: PartialEq> PartialEq for SecretRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: FromStr> FromStr for SecretRef<T>
+where
+    T::Err: Display,
+{
+    type Err = SecretError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == consts::REDACTED {
+            return Err(SecretError(format!(
+                "`{s}` is a placeholder for a redacted secret, not a value"
+            )));
+        }
+        if secrets::is_unresolved() && (secrets::is_reference(s) || secrets::is_prompt(s)) {
+            return Ok(Self {
+                value: stand_in()?,
+                reference: Some(Reference::Uri(s.to_owned())),
+            });
+        }
+        let resolved = secrets::resolve(s)?;
+        let value = parse_secret(resolved.as_deref().map_or(s, String::as_str))?;
+        let reference = resolved.is_some().then(|| Reference::Uri(s.to_owned()));
+        Ok(Self { value, reference })
+    }
+}
+
+/// Deserializes from a string, parsed like [`SecretRef::from_str`], or, for
+/// keypairs, from an array of bytes, parsed like its JSON form as in Solana
+/// keypair files, or from a [`SeedPhraseRef`] table.
+impl<'de, T: SecretValue> Deserialize<'de> for SecretRef<T>
+where
+    T::Err: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<T>(PhantomData<T>);
+
+        impl<'de, T: SecretValue> de::Visitor<'de> for Visitor<T>
+        where
+            T::Err: Display,
+        {
+            type Value = SecretRef<T>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                if T::IS_KEYPAIR {
+                    f.write_str("a string, an array of bytes or a seed phrase table")
+                } else {
+                    f.write_str("a string")
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                if !T::IS_KEYPAIR {
+                    return Err(de::Error::invalid_type(de::Unexpected::Seq, &self));
+                }
+                let mut bytes = Zeroizing::new(Vec::new());
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                let json = Zeroizing::new(format!("{:?}", *bytes));
+                let value = parse_secret(&json).map_err(de::Error::custom)?;
+                Ok(SecretRef::new(value))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                if !T::IS_KEYPAIR {
+                    return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+                }
+                let reference =
+                    SeedPhraseRef::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                SecretRef::from_seed_phrase(reference).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
+}
+
+impl<T: Serialize> Serialize for SecretRef<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.reference {
+            Some(Reference::Uri(reference)) => serializer.serialize_str(reference),
+            Some(Reference::SeedPhrase(reference)) => reference.serialize(serializer),
+            None => self.value.serialize(serializer),
+        }
+    }
+}
+
+impl<T: Debug> Debug for SecretRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reference {
+            Some(Reference::Uri(reference)) => write!(f, "{reference}"),
+            Some(Reference::SeedPhrase(reference)) => reference.fmt(f),
+            None => self.value.fmt(f),
+        }
+    }
+}
+
+/// A `u64` restricted to `MIN..=MAX`. Deserializing or parsing a value out of
+/// range fails with the allowed range in the message.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct BoundedU64<const MIN: u64, const MAX: u64>(u64);
+
+/// An error raised when parsing or checking a [`BoundedU64`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BoundedError {
+    #[error(transparent)]
+    Parse(#[from] ParseIntError),
+    #[error("{value} is out of range, expected a value in {min}..={max}")]
+    OutOfRange { value: u64, min: u64, max: u64 },
+    #[error("invalid size `{0}`, expected a number of bytes or a size such as `512MiB` or `8GB`")]
+    ByteSize(String),
+    #[error(
+        "invalid amount `{0}`, expected lamports or an amount such as `0.5 SOL` or `5000 lamports`"
+    )]
+    Lamports(String),
+    #[error(
+        "invalid percentage `{0}`, expected a percentage such as `75%` or a ratio such as `0.75`"
+    )]
+    Percent(String),
+    #[error("{value} is out of range, expected a percentage in {min}%..={max}%")]
+    PercentOutOfRange { value: String, min: u64, max: u64 },
+}
+
+impl<const MIN: u64, const MAX: u64> BoundedU64<MIN, MAX> {
+    /// Checks that `value` lies within `MIN..=MAX`.
+    pub const fn new(value: u64) -> Result<Self, BoundedError> {
+        if value < MIN || value > MAX {
+            return Err(BoundedError::OutOfRange {
+                value,
+                min: MIN,
+                max: MAX,
+            });
+        }
+        Ok(Self(value))
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Deref for BoundedU64<MIN, MAX> {
+    type Target = u64;
+    fn deref(&self) -> &u64 {
+        &self.0
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> PartialEq<u64> for BoundedU64<MIN, MAX> {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> FromStr for BoundedU64<MIN, MAX> {
+    type Err = BoundedError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.parse()?)
+    }
+}
+
+impl<'de, const MIN: u64, const MAX: u64> Deserialize<'de> for BoundedU64<MIN, MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(u64::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Display for BoundedU64<MIN, MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Debug for BoundedU64<MIN, MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// A size in bytes restricted to `MIN..=MAX`, parsed from a plain number of
+/// bytes or a human-readable size such as `512MiB` or `8GB`, and serialized
+/// back in the largest unit that divides it evenly.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SerializeDisplay)]
+pub struct ByteSize<const MIN: u64 = 0, const MAX: u64 = { u64::MAX }>(u64);
+
+/// Units accepted by [`ByteSize`], matched case-insensitively, from the
+/// largest.
+const BYTE_UNITS: &[(&str, u64)] = &[
+    ("TiB", 1 << 40),
+    ("TB", 1_000_000_000_000),
+    ("GiB", 1 << 30),
+    ("GB", 1_000_000_000),
+    ("MiB", 1 << 20),
+    ("MB", 1_000_000),
+    ("KiB", 1 << 10),
+    ("kB", 1_000),
+    ("B", 1),
+];
+
+impl<const MIN: u64, const MAX: u64> ByteSize<MIN, MAX> {
+    /// Checks that `bytes` lies within `MIN..=MAX`.
+    pub const fn new(bytes: u64) -> Result<Self, BoundedError> {
+        if bytes < MIN || bytes > MAX {
+            return Err(BoundedError::OutOfRange {
+                value: bytes,
+                min: MIN,
+                max: MAX,
+            });
+        }
+        Ok(Self(bytes))
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Deref for ByteSize<MIN, MAX> {
+    type Target = u64;
+    fn deref(&self) -> &u64 {
+        &self.0
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> PartialEq<u64> for ByteSize<MIN, MAX> {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> FromStr for ByteSize<MIN, MAX> {
+    type Err = BoundedError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BoundedError::ByteSize(s.to_owned());
+        let trimmed = s.trim();
+        let split = trimmed
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(trimmed.len());
+        let (number, unit) = trimmed.split_at(split);
+        let number: u64 = number.parse().map_err(|_| invalid())?;
+        let unit = unit.trim_start();
+        let multiplier = match unit {
+            "" => 1,
+            unit => BYTE_UNITS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+                .map(|(_, multiplier)| *multiplier)
+                .ok_or_else(invalid)?,
+        };
+        Self::new(number.checked_mul(multiplier).ok_or_else(invalid)?)
+    }
+}
+
+/// Accepts a plain number of bytes or a string parsed like
+/// [`ByteSize::from_str`].
+impl<'de, const MIN: u64, const MAX: u64> Deserialize<'de> for ByteSize<MIN, MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<const MIN: u64, const MAX: u64>;
+
+        impl<const MIN: u64, const MAX: u64> de::Visitor<'_> for Visitor<MIN, MAX> {
+            type Value = ByteSize<MIN, MAX>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a number of bytes or a size such as `512MiB`")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                ByteSize::new(v).map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                let bytes = u64::try_from(v)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))?;
+                self.visit_u64(bytes)
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Shows the size in the largest unit that divides it evenly, e.g. `100MiB`
+/// or `8GB`.
+impl<const MIN: u64, const MAX: u64> Display for ByteSize<MIN, MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (unit, multiplier) = BYTE_UNITS
+            .iter()
+            .find(|(_, multiplier)| self.0 != 0 && self.0 % multiplier == 0)
+            .unwrap_or(&("B", 1));
+        write!(f, "{}{unit}", self.0 / multiplier)
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Debug for ByteSize<MIN, MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// An amount of lamports, at most `MAX`, parsed from a plain number of
+/// lamports, from `5000 lamports` or from an amount of SOL such as `0.5 SOL`
+/// so that configurations need not count the nine zeros by hand. Serialized
+/// back as a number of lamports.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Lamports<const MAX: u64 = { u64::MAX }>(u64);
+
+impl<const MAX: u64> Lamports<MAX> {
+    /// Checks that `lamports` does not exceed `MAX`.
+    pub const fn new(lamports: u64) -> Result<Self, BoundedError> {
+        if lamports > MAX {
+            return Err(BoundedError::OutOfRange {
+                value: lamports,
+                min: 0,
+                max: MAX,
+            });
+        }
+        Ok(Self(lamports))
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const MAX: u64> Deref for Lamports<MAX> {
+    type Target = u64;
+    fn deref(&self) -> &u64 {
+        &self.0
+    }
+}
+
+impl<const MAX: u64> PartialEq<u64> for Lamports<MAX> {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<const MAX: u64> FromStr for Lamports<MAX> {
+    type Err = BoundedError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BoundedError::Lamports(s.to_owned());
+        let trimmed = s.trim();
+        let split = trimmed
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(trimmed.len());
+        let (amount, unit) = trimmed.split_at(split);
+        let lamports = match unit.trim_start().to_ascii_lowercase().as_str() {
+            "" | "lamport" | "lamports" => amount.parse().map_err(|_| invalid())?,
+            "sol" => sol_to_lamports(amount).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+        Self::new(lamports)
+    }
+}
+
+/// Converts a decimal amount of SOL to lamports exactly, or `None` if it has
+/// more than nine decimals or does not fit in a `u64`.
+fn sol_to_lamports(amount: &str) -> Option<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 9 || whole.len() + fraction.len() == 0 {
+        return None;
+    }
+    let whole: u64 = match whole {
+        "" => 0,
+        whole => whole.parse().ok()?,
+    };
+    let fraction: u64 = format!("{fraction:0<9}").parse().ok()?;
+    whole
+        .checked_mul(consts::LAMPORTS_PER_SOL)?
+        .checked_add(fraction)
+}
+
+/// Accepts a plain number of lamports or a string parsed like
+/// [`Lamports::from_str`].
+impl<'de, const MAX: u64> Deserialize<'de> for Lamports<MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<const MAX: u64>;
+
+        impl<const MAX: u64> de::Visitor<'_> for Visitor<MAX> {
+            type Value = Lamports<MAX>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a number of lamports or an amount such as `0.5 SOL`")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Lamports::new(v).map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                let lamports = u64::try_from(v)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))?;
+                self.visit_u64(lamports)
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl<const MAX: u64> Display for Lamports<MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<const MAX: u64> Debug for Lamports<MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// A percentage restricted to `MIN%..=MAX%`, parsed from a string such as
+/// `"75%"` or from a ratio such as `0.75`, and serialized back as a
+/// percentage. For disk watermarks, fee multipliers, sampling ratios and the
+/// like; `MAX` may exceed 100 for multipliers.
+#[derive(Clone, Copy, PartialEq, PartialOrd, SerializeDisplay)]
+pub struct Percent<const MIN: u64 = 0, const MAX: u64 = 100>(f64);
+
+impl<const MIN: u64, const MAX: u64> Percent<MIN, MAX> {
+    /// Checks that `ratio`, e.g. `0.75` for 75%, lies within `MIN%..=MAX%`.
+    pub fn new(ratio: f64) -> Result<Self, BoundedError> {
+        let percent = Self::round(ratio * 100.0);
+        if !(MIN as f64..=MAX as f64).contains(&percent) {
+            return Err(BoundedError::PercentOutOfRange {
+                value: format!("{percent}%"),
+                min: MIN,
+                max: MAX,
+            });
+        }
+        Ok(Self(ratio))
+    }
+
+    /// The percentage as a ratio, e.g. `0.75` for 75%.
+    pub fn ratio(self) -> f64 {
+        self.0
+    }
+
+    /// The percentage itself, e.g. `75.0` for 75%.
+    pub fn percent(self) -> f64 {
+        Self::round(self.0 * 100.0)
+    }
+
+    /// Drops the noise of converting between ratios and percentages, such
+    /// as the `7.000000000000001` of `0.07 * 100`.
+    fn round(percent: f64) -> f64 {
+        (percent * 1e9).round() / 1e9
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> FromStr for Percent<MIN, MAX> {
+    type Err = BoundedError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let (number, divisor) = match trimmed.strip_suffix('%') {
+            Some(number) => (number.trim_end(), 100.0),
+            None => (trimmed, 1.0),
+        };
+        let number: f64 = number
+            .parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite())
+            .ok_or_else(|| BoundedError::Percent(s.to_owned()))?;
+        Self::new(number / divisor)
+    }
+}
+
+/// Accepts a ratio such as `0.75` or a string parsed like
+/// [`Percent::from_str`].
+impl<'de, const MIN: u64, const MAX: u64> Deserialize<'de> for Percent<MIN, MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<const MIN: u64, const MAX: u64>;
+
+        impl<const MIN: u64, const MAX: u64> de::Visitor<'_> for Visitor<MIN, MAX> {
+            type Value = Percent<MIN, MAX>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a percentage such as `75%` or a ratio such as `0.75`")
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                if !v.is_finite() {
+                    return Err(E::invalid_value(de::Unexpected::Float(v), &self));
+                }
+                Percent::new(v).map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                self.visit_f64(v as f64)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                self.visit_f64(v as f64)
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Display for Percent<MIN, MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.percent())
+    }
+}
+
+impl<const MIN: u64, const MAX: u64> Debug for Percent<MIN, MAX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// A filesystem path with `~`, `$VAR` and `${VAR}` expanded from the
+/// environment when parsed, so that paths such as `~/magicblock` work the same
+/// under systemd as in a shell. Relative paths set in a configuration file are
+/// resolved against that file's directory once loaded, see
+/// [`ExpandedPath::resolve_against`].
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, DeserializeFromStr, Serialize)]
+#[serde(transparent)]
+pub struct ExpandedPath(PathBuf);
+
+/// An error raised when expanding an [`ExpandedPath`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PathError {
+    #[error("environment variable `{0}` is not set")]
+    UnsetVariable(String),
+    #[error("invalid variable reference in `{0}`, expected `$VAR` or `${{VAR}}`")]
+    InvalidVariable(String),
+}
+
+impl ExpandedPath {
+    /// Joins the path onto `dir` if it is relative.
+    pub fn resolve_against(&mut self, dir: &Path) {
+        if self.0.is_relative() {
+            self.0 = dir.join(&self.0);
+        }
+    }
+
+    pub fn into_inner(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl Deref for ExpandedPath {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ExpandedPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<PathBuf> for ExpandedPath {
+    fn from(path: PathBuf) -> Self {
+        Self(path)
+    }
+}
+
+impl PartialEq<PathBuf> for ExpandedPath {
+    fn eq(&self, other: &PathBuf) -> bool {
+        self.0 == *other
+    }
+}
+
+impl FromStr for ExpandedPath {
+    type Err = PathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let variable =
+            |name: &str| std::env::var(name).map_err(|_| PathError::UnsetVariable(name.to_owned()));
+        let mut expanded = String::with_capacity(s.len());
+        let mut rest = s;
+        if rest == "~" || rest.starts_with("~/") {
+            expanded.push_str(&variable("HOME")?);
+            rest = &rest[1..];
+        }
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let (name, next) = match after.strip_prefix('{') {
+                Some(braced) => {
+                    let end = braced
+                        .find('}')
+                        .filter(|end| *end > 0)
+                        .ok_or_else(|| PathError::InvalidVariable(s.to_owned()))?;
+                    (&braced[..end], &braced[end + 1..])
+                }
+                None => {
+                    let end = after
+                        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                        .unwrap_or(after.len());
+                    after.split_at(end)
+                }
+            };
+            match name {
+                // A lone `$` is kept as is.
+                "" => expanded.push('$'),
+                name => expanded.push_str(&variable(name)?),
+            }
+            rest = next;
+        }
+        expanded.push_str(rest);
+        Ok(Self(expanded.into()))
+    }
+}
+
+impl Display for ExpandedPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0.display(), f)
+    }
+}
+
+impl Debug for ExpandedPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+/// A certificate chain and private key in PEM files, served by a TLS
+/// endpoint, with an optional CA bundle clients must present certificates
+/// from.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct TlsIdentity {
+    /// PEM file with the certificate chain, leaf first.
+    pub cert: ExpandedPath,
+    /// PEM file with the private key. Must not be accessible to group or
+    /// others.
+    pub key: ExpandedPath,
+    /// PEM file with the CA certificates to verify client certificates
+    /// against. Clients need no certificate if not set.
+    pub ca: Option<ExpandedPath>,
+}
+
+impl TlsIdentity {
+    /// Every path of the identity, keyed by field name.
+    pub(crate) fn paths_mut(&mut self) -> impl Iterator<Item = (&'static str, &mut ExpandedPath)> {
+        [("cert", &mut self.cert), ("key", &mut self.key)]
+            .into_iter()
+            .chain(self.ca.as_mut().map(|ca| ("ca", ca)))
+    }
+}
+
+/// How a client connects to a network service, shared by every section
+/// that talks to one, e.g. `connection = { request-timeout = "1m" }` on a
+/// remote.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ConnectionOptions {
+    /// How long to wait for a connection to be established.
+    pub connect_timeout: HumanDuration,
+    /// How long to wait for the answer to a request.
+    pub request_timeout: HumanDuration,
+    /// Interval of TCP keepalive probes on idle connections, or `0s` to
+    /// send none.
+    pub keepalive: HumanDuration,
+    /// Connections kept open to the service.
+    pub pool_size: u32,
+    /// How failed requests are retried.
+    pub retry: RetryPolicy,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: HumanDuration(consts::DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: HumanDuration(consts::DEFAULT_REQUEST_TIMEOUT),
+            keepalive: HumanDuration(consts::DEFAULT_KEEPALIVE),
+            pool_size: consts::DEFAULT_POOL_SIZE,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// How many times a failed request is tried, and how long to wait in
+/// between.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one. `1` disables retries.
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each further one.
+    pub backoff: HumanDuration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: consts::DEFAULT_RETRY_ATTEMPTS,
+            backoff: HumanDuration(consts::DEFAULT_RETRY_BACKOFF),
+        }
+    }
+}
+
+/// How settled a base chain state must be before it is read, ordered from
+/// the freshest to the safest, as understood by Solana RPC clients.
+#[derive(
+    Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum CommitmentLevel {
+    /// The most recent block seen by the node, which may still be skipped.
+    Processed,
+    /// Voted on by a supermajority of the cluster.
+    #[default]
+    Confirmed,
+    /// Rooted by a supermajority of the cluster, and thus irreversible.
+    Finalized,
+}
+
+impl Display for CommitmentLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Processed => "processed",
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        })
+    }
+}
+
+/// A duration written in a human-readable form such as `400ms`, `2s` or
+/// `1h 30m`, the same way on the command line, in environment variables and
+/// in configuration files.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, SerializeDisplay)]
+pub struct HumanDuration(pub Duration);
+
+impl Deref for HumanDuration {
+    type Target = Duration;
+    fn deref(&self) -> &Duration {
+        &self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl PartialEq<Duration> for HumanDuration {
+    fn eq(&self, other: &Duration) -> bool {
+        self.0 == *other
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = humantime::re::humantime::DurationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        humantime::re::humantime::parse_duration(s).map(Self)
+    }
+}
+
+/// Accepts a string parsed like [`HumanDuration::from_str`]. A bare number,
+/// e.g. from `MBV_LEDGER_BLOCK_TIME=2`, is parsed the same way, so it is
+/// rejected for its missing unit rather than for its type.
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = HumanDuration;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a duration such as `400ms` or `2s`")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                self.visit_str(&v.to_string())
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                self.visit_str(&v.to_string())
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&humantime::re::humantime::format_duration(self.0), f)
+    }
+}
+
+impl Debug for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
     }
 }
@@ -0,0 +1,1045 @@
+//! Semantic checks run on the configuration once it has been extracted.
+
+use crate::{
+    config::{
+        AccountsDbConfig, AdminConfig, ChainLinkConfig, ChainOperationConfig, LedgerConfig,
+        MetricsConfig, NetworkConfig, ProxyConfig, PushGatewayConfig, RemoteSigner, RoleKeypair,
+        TracingConfig, ValidatorConfig,
+    },
+    consts,
+    remote::{
+        self, AliasedUrl, FailoverConfig, HealthCheck, RateLimit, Remote, RemoteAuth,
+        RemoteCluster, RemoteProxy, RemoteRole,
+    },
+    secrets,
+    types::{BindAddress, CommitmentLevel, ConnectionOptions, Secret, SecretRef, TlsIdentity},
+    LifecycleMode, MagicBlockParams,
+};
+use clap::ValueEnum;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    io,
+    net::SocketAddr,
+    path::Path,
+};
+use url::Url;
+use zeroize::Zeroizing;
+
+/// A semantic problem found in an otherwise well-formed configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Dotted path of the offending key, e.g. `accounts-db.index-size`.
+    pub key: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Prepends the `section` name to the key.
+    fn in_section(mut self, section: &str) -> Self {
+        self.key = if self.key.is_empty() {
+            section.to_owned()
+        } else {
+            format!("{section}.{}", self.key)
+        };
+        self
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "`{}`: {}", self.key, self.message)
+    }
+}
+
+/// Semantic validation of a configuration section.
+pub trait Validate {
+    /// Checks the section and returns every problem found, with keys
+    /// relative to the section.
+    fn validate(&self) -> Vec<ValidationError>;
+}
+
+impl Validate for MagicBlockParams {
+    fn validate(&self) -> Vec<ValidationError> {
+        fn section(name: &str, section: &impl Validate) -> Vec<ValidationError> {
+            let errors = section.validate().into_iter();
+            errors.map(|e| e.in_section(name)).collect()
+        }
+
+        let mut errors = self.remote.validate();
+        for (name, remote) in &self.remotes {
+            errors.extend(section(&format!("remotes.{name}"), remote));
+        }
+        for (mode, remote) in &self.lifecycle_defaults {
+            let key = format!("lifecycle-defaults.{}", mode.name());
+            errors.extend(remote.validate().into_iter().map(|e| e.in_section(&key)));
+        }
+        errors.extend(self.validate_remote_aliases());
+        if self.config_refresh_interval.is_some_and(|i| i.is_zero()) {
+            errors.push(ValidationError::new(
+                "config-refresh-interval",
+                "must be greater than zero",
+            ));
+        }
+        if self.listen.is_empty() {
+            errors.push(ValidationError::new(
+                "listen",
+                "at least one address is required",
+            ));
+        }
+        for (key, address) in self.bind_addresses() {
+            if key != "admin.listen" {
+                errors.extend(section(&key, address));
+            }
+        }
+        errors.extend(section("validator", &self.validator));
+        errors.extend(section("accounts-db", &self.accounts_db));
+        errors.extend(section("ledger", &self.ledger));
+        errors.extend(section("failover", &self.failover));
+        errors.extend(section("network", &self.network));
+        errors.extend(section("metrics", &self.metrics));
+        errors.extend(section("tracing", &self.tracing));
+        if let Some(chain_operation) = &self.chain_operation {
+            errors.extend(section("chain-operation", chain_operation));
+        }
+        if let Some(admin) = &self.admin {
+            errors.extend(section("admin", admin));
+        }
+        if let Some(tls) = &self.tls {
+            errors.extend(section("tls", tls));
+        }
+        errors.extend(self.validate_cross_fields());
+        errors.extend(self.validate_storage());
+        errors
+    }
+}
+
+impl MagicBlockParams {
+    /// Aliases can't shadow the built-in ones, which are resolved first, and
+    /// must name a URL remotes can connect to.
+    fn validate_remote_aliases(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        for (name, url) in &self.remote_aliases {
+            let key = format!("remote-aliases.{name}");
+            if consts::BUILTIN_REMOTE_ALIASES.contains(&name.as_str()) {
+                errors.push(ValidationError::new(key, "is a built-in alias"));
+            } else if !remote::is_alias_name(name) {
+                errors.push(ValidationError::new(
+                    key,
+                    "must only contain letters, digits, `-` and `_`",
+                ));
+            } else if !["http", "https", "ws", "wss"].contains(&url.scheme()) {
+                let message = format!(
+                    "`{url}` has scheme `{}`, expected `http` or `https` or `ws` or `wss`",
+                    url.scheme()
+                );
+                errors.push(ValidationError::new(key, message));
+            }
+        }
+        errors
+    }
+
+    /// Checks the relationships between sections, which no single section
+    /// can validate on its own.
+    fn validate_cross_fields(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let roles = self.remote.roles();
+        // Whether each reference needs a JSON-RPC remote, which a gRPC one isn't.
+        let references = [
+            ("chainlink.remote", &self.chainlink.remote, true),
+            ("commit.remote", &self.commit.remote, true),
+            ("remote.roles.clone", &roles.clone, true),
+            ("remote.roles.commit", &roles.commit, true),
+            ("remote.roles.subscribe", &roles.subscribe, false),
+        ];
+        for (key, name, json_rpc) in references {
+            let Some(name) = name.as_deref() else {
+                continue;
+            };
+            match self.remotes.get(name) {
+                Some(remote) if json_rpc && remote.is_grpc() => {
+                    errors.push(ValidationError::new(
+                        key,
+                        format!("refers to `{name}`, a gRPC remote serving no JSON-RPC"),
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    let names: Vec<_> = self.remotes.keys().map(|n| format!("`{n}`")).collect();
+                    let message = match names.as_slice() {
+                        [] => format!("refers to `{name}`, but `[remotes]` is empty"),
+                        names => {
+                            format!("refers to `{name}`, expected one of {}", names.join(", "))
+                        }
+                    };
+                    errors.push(ValidationError::new(key, message));
+                }
+            }
+        }
+        let addresses = self.bind_addresses();
+        for (i, (key, address)) in addresses.iter().enumerate() {
+            for (taken, _) in addresses[..i]
+                .iter()
+                .filter(|(_, a)| same_address(a, address))
+            {
+                errors.push(ValidationError::new(
+                    key,
+                    format!("must not be the same address as `{taken}` ({address})"),
+                ));
+            }
+        }
+        let (frequency, partition) = (
+            self.accounts_db.snapshot_frequency,
+            self.ledger.blocks_per_partition,
+        );
+        // A zero partition size is reported by the ledger section itself.
+        if partition != 0 && frequency > partition as u64 {
+            errors.push(ValidationError::new(
+                "accounts-db.snapshot-frequency",
+                format!("must not exceed `ledger.blocks-per-partition` ({partition})"),
+            ));
+        }
+        let lifecycle = self.lifecycle.name();
+        for requirement in self.lifecycle.requirements() {
+            if !requirement.is_met(self) {
+                let message = format!(
+                    "{} when `lifecycle` is `{lifecycle}`",
+                    requirement.describe()
+                );
+                errors.push(ValidationError::new(requirement.key(), message));
+            }
+        }
+        errors
+    }
+
+    /// Checks that `storage`, if set, is a writable directory with enough
+    /// free space, or is missing with `create_storage_dirs` enabled, in
+    /// which case the volume it would be created on is checked. Nothing is
+    /// created, see [`MagicBlockParams::prepare_storage`].
+    fn validate_storage(&self) -> Vec<ValidationError> {
+        let Some(storage) = &self.storage else {
+            return Vec::new();
+        };
+        let error = |message: String| {
+            let message = format!("{}: {message}", storage.display());
+            vec![ValidationError::new("storage", message)]
+        };
+        if !storage.exists() {
+            if !self.create_storage_dirs {
+                return error(
+                    "directory does not exist, set `create-storage-dirs` to create it".into(),
+                );
+            }
+        } else if !storage.is_dir() {
+            return error("not a directory".into());
+        } else if let Err(message) = check_writable_dir(storage) {
+            return error(message);
+        }
+        if let Some(required) = self.storage_min_free_space {
+            // The closest existing directory is on the volume a missing one
+            // would be created on.
+            let Some(volume) = storage.ancestors().find(|path| path.exists()) else {
+                return Vec::new();
+            };
+            match free_space(volume) {
+                Ok(Some(available)) if available < required => {
+                    return error(format!(
+                        "only {available} bytes free, at least {required} required"
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => return error(format!("failed to query free space: {e}")),
+            }
+        }
+        Vec::new()
+    }
+}
+
+/// The certificate files are checked up front, as a missing or unreadable
+/// one would otherwise only surface once the endpoint starts.
+impl Validate for TlsIdentity {
+    fn validate(&self) -> Vec<ValidationError> {
+        let paths = [("cert", Some(&self.cert)), ("key", Some(&self.key))];
+        let paths = paths.into_iter().chain([("ca", self.ca.as_ref())]);
+        let mut errors: Vec<_> = paths
+            .filter_map(|(key, path)| Some((key, path?)))
+            .filter_map(|(key, path)| {
+                let message = check_readable_file(path).err()?;
+                Some(ValidationError::new(key, format!("{path}: {message}")))
+            })
+            .collect();
+        if let Some(mode) = exposed_key_mode(&self.key) {
+            let message = format!(
+                "{}: accessible to group or others (mode {mode:04o}), restrict it with `chmod 600`",
+                self.key
+            );
+            errors.push(ValidationError::new("key", message));
+        }
+        errors
+    }
+}
+
+/// Checks that `path` is a regular file the process can open.
+fn check_readable_file(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err("file does not exist".into());
+    }
+    if !path.is_file() {
+        return Err("not a file".into());
+    }
+    match std::fs::File::open(path) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("file is not readable: {e}")),
+    }
+}
+
+/// Checks that the directory at `path` can be written to by the process,
+/// without writing to it.
+#[cfg(unix)]
+pub(crate) fn check_writable_dir(path: &Path) -> Result<(), String> {
+    use rustix::fs::{access, Access};
+    access(path, Access::WRITE_OK | Access::EXEC_OK)
+        .map_err(|e| format!("directory is not writable: {}", io::Error::from(e)))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn check_writable_dir(path: &Path) -> Result<(), String> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.permissions().readonly() => {
+            Err("directory is not writable: read-only".into())
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("directory is not writable: {e}")),
+    }
+}
+
+/// The permission bits of the private key at `path`, if group or others
+/// have any access to it.
+#[cfg(unix)]
+fn exposed_key_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o077 != 0).then_some(mode)
+}
+
+#[cfg(not(unix))]
+fn exposed_key_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Returns the space available to unprivileged users on the volume holding
+/// `path`, or `None` where this can't be queried.
+#[cfg(unix)]
+fn free_space(path: &Path) -> io::Result<Option<u64>> {
+    let stat = rustix::fs::statvfs(path)?;
+    Ok(Some(stat.f_bavail.saturating_mul(stat.f_frsize)))
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> io::Result<Option<u64>> {
+    Ok(None)
+}
+
+/// Whether two bind addresses would be bound to the same socket, whatever
+/// the permissions given to a Unix domain socket, or overlap because one is
+/// a dual-stack IPv6 address also taking IPv4 connections on the port.
+fn same_address(a: &BindAddress, b: &BindAddress) -> bool {
+    match (a, b) {
+        (BindAddress::Unix(a), BindAddress::Unix(b)) => a.path == b.path,
+        (
+            BindAddress::Tcp {
+                address: a,
+                v6_only: a_v6_only,
+            },
+            BindAddress::Tcp {
+                address: b,
+                v6_only: b_v6_only,
+            },
+        ) => a == b || takes_ipv4(a, *a_v6_only, b) || takes_ipv4(b, *b_v6_only, a),
+        _ => a == b,
+    }
+}
+
+/// Whether `address`, with `v6_only` explicitly turned off, accepts the
+/// connections to the IPv4 address `other`.
+fn takes_ipv4(address: &SocketAddr, v6_only: Option<bool>, other: &SocketAddr) -> bool {
+    v6_only == Some(false)
+        && address.is_ipv6()
+        && address.ip().is_unspecified()
+        && other.is_ipv4()
+        && address.port() == other.port()
+}
+
+/// Longest Unix domain socket path accepted on every supported platform,
+/// in bytes. Linux allows 107, macOS 103.
+const MAX_SOCKET_PATH_LEN: usize = 103;
+
+impl Validate for BindAddress {
+    fn validate(&self) -> Vec<ValidationError> {
+        let BindAddress::Unix(socket) = self else {
+            return Vec::new();
+        };
+        if cfg!(not(unix)) {
+            return vec![ValidationError::new(
+                "",
+                "Unix domain sockets are not supported on this platform",
+            )];
+        }
+        let length = socket.path.as_os_str().len();
+        if length > MAX_SOCKET_PATH_LEN {
+            return vec![ValidationError::new(
+                "",
+                format!("socket path is {length} bytes long, at most {MAX_SOCKET_PATH_LEN} are supported"),
+            )];
+        }
+        Vec::new()
+    }
+}
+
+/// A requirement a [`LifecycleMode`] places on the rest of the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// `remote` must point to clusters with a network host.
+    ReachableRemote,
+    /// `remote` must keep its default, as it is never contacted.
+    DefaultRemote,
+    /// The `chain-operation` section must be present.
+    ChainOperation,
+    /// `validator.keypair` must not be the well-known built-in default,
+    /// unless `validator.signer` holds the identity instead.
+    CustomKeypair,
+    /// The `chainlink` section must keep its defaults.
+    NoChainLink,
+    /// `chainlink.commitment` must be at least `confirmed`, so that no
+    /// account is cloned from a block the cluster later skips.
+    ConfirmedCommitment,
+    /// `remote` must not include gRPC remotes, as the mode only fetches
+    /// programs over JSON-RPC and streams no account updates.
+    NoGrpcRemote,
+}
+
+impl Requirement {
+    /// Dotted path of the key the requirement applies to.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::ReachableRemote | Self::DefaultRemote | Self::NoGrpcRemote => "remote",
+            Self::ChainOperation => "chain-operation",
+            Self::CustomKeypair => "validator.keypair",
+            Self::NoChainLink => "chainlink",
+            Self::ConfirmedCommitment => "chainlink.commitment",
+        }
+    }
+
+    /// Whether `params` satisfies the requirement.
+    pub fn is_met(self, params: &MagicBlockParams) -> bool {
+        match self {
+            // Unknown aliases are reported by the remote itself.
+            Self::ReachableRemote => params
+                .remote
+                .urls()
+                .all(|url| url.has_host() || url.scheme() == consts::REMOTE_ALIAS_SCHEME),
+            Self::DefaultRemote => params.remote == RemoteCluster::default(),
+            Self::ChainOperation => params.chain_operation.is_some(),
+            Self::CustomKeypair => {
+                params.validator.signer.is_some()
+                    || *Zeroizing::new(params.validator.keypair.expose_secret().to_string())
+                        != consts::DEFAULT_VALIDATOR_KEYPAIR
+            }
+            Self::NoChainLink => params.chainlink == ChainLinkConfig::default(),
+            Self::ConfirmedCommitment => params.chainlink.commitment >= CommitmentLevel::Confirmed,
+            Self::NoGrpcRemote => params.remote.grpc().next().is_none(),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::ReachableRemote => "must point to a network host",
+            Self::DefaultRemote => "must not be set",
+            Self::ChainOperation => "is required",
+            Self::CustomKeypair => "must not be the built-in default keypair",
+            Self::NoChainLink => "must not be set",
+            Self::ConfirmedCommitment => "must be at least `confirmed`",
+            Self::NoGrpcRemote => "must not include gRPC remotes",
+        }
+    }
+}
+
+impl LifecycleMode {
+    /// The requirements this mode places on the rest of the configuration,
+    /// enforced by [`MagicBlockParams::validate`].
+    pub fn requirements(&self) -> &'static [Requirement] {
+        match self {
+            Self::Ephemeral => &[
+                Requirement::ReachableRemote,
+                Requirement::ChainOperation,
+                Requirement::CustomKeypair,
+                Requirement::ConfirmedCommitment,
+            ],
+            Self::Replica => &[Requirement::ReachableRemote],
+            Self::ProgramsReplica => &[Requirement::ReachableRemote, Requirement::NoGrpcRemote],
+            Self::Offline => &[Requirement::DefaultRemote, Requirement::NoChainLink],
+        }
+    }
+
+    /// The mode's name as written in configuration files.
+    pub(crate) fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// At most one remote can be the primary, and not every remote can be a
+/// fallback or a gRPC stream, or there would be nothing to send requests to.
+impl Validate for RemoteCluster {
+    fn validate(&self) -> Vec<ValidationError> {
+        let remotes = self.remotes();
+        // Keys relative to `remote`, of the list of remotes and of each one.
+        let list = match self {
+            Self::Scoped { .. } => "default",
+            Self::Single(_) | Self::Multiple(_) => "",
+        };
+        let key = |i: usize| match self {
+            Self::Single(_) => String::new(),
+            Self::Multiple(_) => i.to_string(),
+            Self::Scoped { default, .. } if default.len() == 1 => "default".to_owned(),
+            Self::Scoped { .. } => format!("default.{i}"),
+        };
+        let in_remote = |error: ValidationError, key: &str| match key {
+            "" => error,
+            key => error.in_section(key),
+        };
+        let mut errors: Vec<_> = remotes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, remote)| {
+                let key = key(i);
+                let errors = remote.validate().into_iter();
+                errors.map(move |e| in_remote(e, &key)).collect::<Vec<_>>()
+            })
+            .collect();
+        let mut primaries = remotes
+            .iter()
+            .enumerate()
+            .filter(|(_, remote)| remote.role() == Some(RemoteRole::Primary));
+        if remotes.is_empty() {
+            errors.push(ValidationError::new(
+                list,
+                "at least one remote is required",
+            ));
+        } else if let Some((first, _)) = primaries.next() {
+            errors.extend(primaries.map(|(i, _)| {
+                let message = format!("`remote.{}` is already the primary", key(first));
+                in_remote(ValidationError::new("role", message), &key(i))
+            }));
+        } else if remotes.iter().all(Remote::is_grpc) {
+            errors.push(ValidationError::new(
+                list,
+                "at least one remote must serve JSON-RPC, gRPC remotes only stream updates",
+            ));
+        } else if self.primary().is_none() {
+            errors.push(ValidationError::new(
+                list,
+                "at least one remote must not be a fallback",
+            ));
+        }
+        errors.into_iter().map(|e| e.in_section("remote")).collect()
+    }
+}
+
+/// URL schemes are checked up front, as a mismatch would otherwise only
+/// surface once the RPC client connects.
+impl Validate for Remote {
+    fn validate(&self) -> Vec<ValidationError> {
+        let check = |key: &str, url: &AliasedUrl, schemes: &[&str]| {
+            if let Some(name) = url.alias() {
+                let message = format!("`{name}` is neither a URL nor a key of `[remote-aliases]`");
+                return Some(ValidationError::new(key, message));
+            }
+            let scheme = url.url.scheme();
+            (!schemes.contains(&scheme)).then(|| {
+                let expected = schemes.join("` or `");
+                let message = format!("`{url}` has scheme `{scheme}`, expected `{expected}`");
+                ValidationError::new(key, message)
+            })
+        };
+        let mut errors: Vec<_> = match self {
+            Self::Unified(url) => check("", url, &["http", "https", "ws", "wss"])
+                .into_iter()
+                .collect(),
+            Self::Authenticated { url, .. } => check("url", url, &["http", "https", "ws", "wss"])
+                .into_iter()
+                .collect(),
+            Self::Disjointed { http, ws, .. } => [
+                check("http", http, &["http", "https"]),
+                check("ws", ws, &["ws", "wss"]),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            Self::Grpc {
+                endpoint, x_token, ..
+            } => {
+                let mut errors: Vec<_> = check("endpoint", endpoint, &["http", "https"])
+                    .into_iter()
+                    .collect();
+                if x_token
+                    .as_ref()
+                    .is_some_and(|t| t.expose_secret().is_empty())
+                {
+                    errors.push(ValidationError::new("x-token", "must not be empty"));
+                }
+                errors
+            }
+        };
+        if let Some(hash) = self.expected_genesis() {
+            let decoded = bs58::decode(hash).into_vec();
+            if decoded.map_or(true, |bytes| bytes.len() != 32) {
+                errors.push(ValidationError::new(
+                    "expected-genesis",
+                    format!("`{hash}` is not a base58-encoded 32-byte hash"),
+                ));
+            }
+        }
+        if let Some(RemoteProxy::Via(url)) = self.proxy() {
+            errors
+                .extend(check_proxy_url(url).map(|message| ValidationError::new("proxy", message)));
+        }
+        errors.extend(self.auth().validate());
+        let health = self.health().validate().into_iter();
+        errors.extend(health.map(|e| e.in_section("health")));
+        let rate_limit = self.rate_limit().validate().into_iter();
+        errors.extend(rate_limit.map(|e| e.in_section("rate-limit")));
+        let connection = self.connection().validate().into_iter();
+        errors.extend(connection.map(|e| e.in_section("connection")));
+        errors
+    }
+}
+
+impl Validate for NetworkConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let errors = self.proxy.validate().into_iter();
+        errors.map(|e| e.in_section("proxy")).collect()
+    }
+}
+
+impl Validate for ProxyConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<_> = self
+            .url
+            .as_ref()
+            .and_then(check_proxy_url)
+            .map(|message| ValidationError::new("url", message))
+            .into_iter()
+            .collect();
+        if self.no_proxy.iter().any(|host| host.trim().is_empty()) {
+            errors.push(ValidationError::new("no-proxy", "hosts must not be empty"));
+        }
+        errors
+    }
+}
+
+/// Why `url` can't be used as a proxy, if it can't.
+fn check_proxy_url(url: &Url) -> Option<String> {
+    const SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+    let url = secrets::redact_url(url.clone());
+    if !SCHEMES.contains(&url.scheme()) {
+        let expected = SCHEMES.join("` or `");
+        return Some(format!(
+            "`{url}` has scheme `{}`, expected `{expected}`",
+            url.scheme()
+        ));
+    }
+    url.host().is_none().then(|| format!("`{url}` has no host"))
+}
+
+impl Validate for RateLimit {
+    fn validate(&self) -> Vec<ValidationError> {
+        let limits = [
+            ("requests-per-second", self.requests_per_second),
+            ("burst", self.burst),
+            (
+                "max-concurrent-subscriptions",
+                self.max_concurrent_subscriptions,
+            ),
+        ];
+        let mut errors: Vec<_> = limits
+            .into_iter()
+            .filter(|(_, limit)| *limit == Some(0))
+            .map(|(key, _)| ValidationError::new(key, "must be greater than zero"))
+            .collect();
+        if self.burst.is_some() && self.requests_per_second.is_none() {
+            errors.push(ValidationError::new(
+                "burst",
+                "requires `requests-per-second`",
+            ));
+        }
+        errors
+    }
+}
+
+/// A check that times out after the next one is due would pile up requests
+/// on a slow remote.
+impl Validate for HealthCheck {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if self.interval.is_zero() {
+            errors.push(ValidationError::new(
+                "interval",
+                "must be greater than zero",
+            ));
+        }
+        if self.timeout.is_zero() {
+            errors.push(ValidationError::new("timeout", "must be greater than zero"));
+        } else if !self.interval.is_zero() && self.timeout >= self.interval {
+            errors.push(ValidationError::new(
+                "timeout",
+                format!("must be shorter than `interval` ({})", self.interval),
+            ));
+        }
+        if self.unhealthy_threshold == 0 {
+            errors.push(ValidationError::new(
+                "unhealthy-threshold",
+                "must be greater than zero",
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for FailoverConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if self.error_threshold == 0 {
+            errors.push(ValidationError::new(
+                "error-threshold",
+                "must be greater than zero",
+            ));
+        }
+        if self.cooldown.is_zero() {
+            errors.push(ValidationError::new(
+                "cooldown",
+                "must be greater than zero",
+            ));
+        }
+        errors
+    }
+}
+
+/// Credentials must not be empty, and header names must be valid HTTP
+/// tokens, or every request to the remote would be rejected.
+impl Validate for RemoteAuth {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        for (key, value) in [("token", &self.token), ("api-key", &self.api_key)] {
+            if value
+                .as_ref()
+                .is_some_and(|value| value.expose_secret().is_empty())
+            {
+                errors.push(ValidationError::new(key, "must not be empty"));
+            }
+        }
+        errors.extend(validate_headers(&self.headers));
+        errors
+    }
+}
+
+/// Header names must be HTTP tokens, and values can't be empty.
+fn validate_headers(headers: &BTreeMap<String, SecretRef<Secret<String>>>) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for (name, value) in headers {
+        let is_token = !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+        if !is_token {
+            let message = format!("`{name}` is not a valid header name");
+            errors.push(ValidationError::new("headers", message));
+        } else if value.expose_secret().is_empty() {
+            errors.push(ValidationError::new(
+                format!("headers.{name}"),
+                "must not be empty",
+            ));
+        }
+    }
+    errors
+}
+
+impl Validate for TracingConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if let Some(endpoint) = &self.endpoint {
+            if !matches!(endpoint.scheme(), "http" | "https") || endpoint.host().is_none() {
+                errors.push(ValidationError::new(
+                    "endpoint",
+                    format!("`{endpoint}` is not an http(s) URL with a host"),
+                ));
+            }
+        }
+        errors.extend(validate_headers(&self.headers));
+        if self.resource_attributes.keys().any(|key| key.is_empty()) {
+            errors.push(ValidationError::new(
+                "resource-attributes",
+                "keys must not be empty",
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for MetricsConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if !self.path.starts_with('/') {
+            errors.push(ValidationError::new("path", "must start with `/`"));
+        }
+        // Prometheus reserves label names starting with `__` for itself.
+        for name in self.labels.keys() {
+            let is_valid = name
+                .bytes()
+                .enumerate()
+                .all(|(i, b)| b == b'_' || b.is_ascii_alphabetic() || i > 0 && b.is_ascii_digit());
+            if name.is_empty() || !is_valid || name.starts_with("__") {
+                let message = format!("`{name}` is not a valid Prometheus label name");
+                errors.push(ValidationError::new("labels", message));
+            }
+        }
+        for (name, buckets) in &self.buckets {
+            let key = format!("buckets.{name}");
+            if buckets.is_empty() {
+                errors.push(ValidationError::new(key, "must list at least one bucket"));
+            } else if buckets.iter().any(|bucket| !bucket.is_finite()) {
+                errors.push(ValidationError::new(key, "buckets must be finite numbers"));
+            } else if buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+                errors.push(ValidationError::new(
+                    key,
+                    "buckets must be strictly increasing",
+                ));
+            }
+        }
+        if self.subsystems.keys().any(|name| name.is_empty()) {
+            errors.push(ValidationError::new(
+                "subsystems",
+                "names must not be empty",
+            ));
+        }
+        let push_gateway = self.push_gateway.validate().into_iter();
+        errors.extend(push_gateway.map(|e| e.in_section("push-gateway")));
+        if let Some(tls) = &self.tls {
+            errors.extend(tls.validate().into_iter().map(|e| e.in_section("tls")));
+            if self.listen.is_none() {
+                errors.push(ValidationError::new("tls", "requires `listen` to be set"));
+            }
+        }
+        errors
+    }
+}
+
+impl Validate for PushGatewayConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if let Some(url) = &self.url {
+            if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+                errors.push(ValidationError::new(
+                    "url",
+                    format!("`{url}` is not an http(s) URL with a host"),
+                ));
+            }
+        }
+        if self.interval.is_zero() {
+            errors.push(ValidationError::new(
+                "interval",
+                "must be greater than zero",
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for ValidatorConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<_> = self
+            .signer
+            .iter()
+            .flat_map(Validate::validate)
+            .map(|e| e.in_section("signer"))
+            .collect();
+        // Signing with the identity in every role is what distinct keypairs
+        // are meant to avoid.
+        let identity = self.identity().pubkey();
+        let roles = [
+            ("commit-fee-payer", &self.commit_fee_payer),
+            ("fee-claim-authority", &self.fee_claim_authority),
+        ];
+        for (name, role) in roles {
+            let Some(role) = role else { continue };
+            let mut role_errors = role.validate();
+            match role.source() {
+                None => {
+                    role_errors.push(ValidationError::new("", "must set `keypair` or `signer`"))
+                }
+                Some(source) if source.pubkey() == identity => {
+                    let message = format!("must differ from the identity {identity}");
+                    role_errors.push(ValidationError::new("", message));
+                }
+                Some(_) => {}
+            }
+            errors.extend(role_errors.into_iter().map(|e| e.in_section(name)));
+        }
+        errors
+    }
+}
+
+impl Validate for RoleKeypair {
+    fn validate(&self) -> Vec<ValidationError> {
+        self.signer
+            .iter()
+            .flat_map(Validate::validate)
+            .map(|e| e.in_section("signer"))
+            .collect()
+    }
+}
+
+impl Validate for RemoteSigner {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let scheme = self.url.scheme();
+        if !["http", "https"].contains(&scheme) {
+            errors.push(ValidationError::new(
+                "url",
+                format!(
+                    "`{}` has scheme `{scheme}`, expected `http` or `https`",
+                    self.url
+                ),
+            ));
+        }
+        if self.token.expose_secret().is_empty() {
+            errors.push(ValidationError::new("token", "must not be empty"));
+        }
+        let connection = self.connection.validate().into_iter();
+        errors.extend(connection.map(|e| e.in_section("connection")));
+        errors
+    }
+}
+
+/// Zero timeouts would fail every request before it is sent.
+impl Validate for ConnectionOptions {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        for (key, timeout) in [
+            ("connect-timeout", self.connect_timeout),
+            ("request-timeout", self.request_timeout),
+        ] {
+            if timeout.is_zero() {
+                errors.push(ValidationError::new(key, "must be greater than zero"));
+            }
+        }
+        if self.pool_size == 0 {
+            errors.push(ValidationError::new(
+                "pool-size",
+                "must be greater than zero",
+            ));
+        }
+        if self.retry.attempts == 0 {
+            errors.push(ValidationError::new(
+                "retry.attempts",
+                "must be at least 1, the first attempt",
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for AccountsDbConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        // Sizes are range-checked by their types while deserializing.
+        let mut errors = Vec::new();
+        if self.snapshot_frequency == 0 {
+            errors.push(ValidationError::new(
+                "snapshot-frequency",
+                "must be greater than 0",
+            ));
+        }
+        if self.max_snapshots == 0 {
+            errors.push(ValidationError::new(
+                "max-snapshots",
+                "must be greater than 0",
+            ));
+        }
+        if self.index_size.get() >= self.database_size.get() {
+            errors.push(ValidationError::new(
+                "index-size",
+                format!(
+                    "must be smaller than `database-size` ({})",
+                    self.database_size
+                ),
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for LedgerConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if self.blocks_per_partition == 0 {
+            errors.push(ValidationError::new(
+                "blocks-per-partition",
+                "must be greater than 0",
+            ));
+        }
+        if self.block_time.is_zero() {
+            errors.push(ValidationError::new(
+                "block-time",
+                "must be a non-zero duration",
+            ));
+        }
+        errors
+    }
+}
+
+impl Validate for AdminConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if matches!(self.listen, BindAddress::Unix(_)) {
+            errors.push(ValidationError::new(
+                "listen",
+                "must be a TCP address, the admin service doesn't serve Unix domain sockets",
+            ));
+        }
+        if self.token.expose_secret().is_empty() {
+            errors.push(ValidationError::new("token", "must not be empty"));
+        }
+        errors
+    }
+}
+
+impl Validate for ChainOperationConfig {
+    fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if !matches!(self.fqdn.scheme(), "http" | "https") || !self.fqdn.has_host() {
+            errors.push(ValidationError::new(
+                "fqdn",
+                format!("`{}` is not an http(s) URL with a host", self.fqdn),
+            ));
+        }
+        if self.claim_fees_frequency.is_zero() {
+            errors.push(ValidationError::new(
+                "claim-fees-frequency",
+                "must be a non-zero duration",
+            ));
+        }
+        errors
+    }
+}
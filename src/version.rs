@@ -0,0 +1,40 @@
+//! Compatibility checks for the `config-version` key.
+
+use crate::{consts, validate::ValidationError, warning::ConfigWarning};
+use figment::Figment;
+
+/// Checks the `config-version` set in `figment`, if any, against
+/// [`consts::CONFIG_VERSION`]. Older versions produce a warning listing what
+/// changed since, newer ones an error.
+pub fn check(figment: &Figment) -> Result<Option<ConfigWarning>, ValidationError> {
+    let key = consts::CONFIG_VERSION_KEY;
+    if !figment.contains(key) {
+        return Ok(None);
+    }
+    let version: Option<u32> = figment.extract_inner(key).map_err(|e| ValidationError {
+        key: key.to_owned(),
+        ..ValidationError::from(&e)
+    })?;
+    // Serialized defaults carry an explicit "unset" value.
+    let Some(version) = version else {
+        return Ok(None);
+    };
+    if version > consts::CONFIG_VERSION {
+        let message = format!(
+            "version {version} is newer than the latest supported version {}, \
+             upgrade magicblock-config or write the configuration for version {}",
+            consts::CONFIG_VERSION,
+            consts::CONFIG_VERSION,
+        );
+        return Err(ValidationError::new(key, message));
+    }
+    if version < consts::CONFIG_VERSION {
+        let changes = consts::CONFIG_VERSION_CHANGES
+            .iter()
+            .filter(|(since, _)| *since > version)
+            .map(|(since, change)| format!("version {since}: {change}"))
+            .collect();
+        return Ok(Some(ConfigWarning::OutdatedVersion { version, changes }));
+    }
+    Ok(None)
+}
@@ -0,0 +1,55 @@
+use crate::{consts, strict::UnknownKey};
+use std::fmt::{self, Display};
+
+/// A non-fatal problem found while assembling the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigWarning {
+    /// A renamed key was set under its deprecated name.
+    DeprecatedKey {
+        /// Dotted path of the deprecated key, e.g. `validator.base-fee`.
+        key: String,
+        /// Dotted path of the key that replaces it.
+        replacement: String,
+        /// The source that set the deprecated key.
+        origin: Option<String>,
+    },
+    /// The sources were written for an older `config-version`.
+    OutdatedVersion {
+        version: u32,
+        /// What changed since `version`.
+        changes: Vec<String>,
+    },
+    /// A key that no field consumes, outside strict mode, where it would be
+    /// an error instead.
+    UnknownKey(UnknownKey),
+}
+
+impl Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeprecatedKey {
+                key,
+                replacement,
+                origin,
+            } => {
+                write!(f, "`{key}` is deprecated, use `{replacement}` instead")?;
+                if let Some(origin) = origin {
+                    write!(f, " (set in {origin})")?;
+                }
+                Ok(())
+            }
+            Self::OutdatedVersion { version, changes } => {
+                write!(
+                    f,
+                    "`config-version` {version} is outdated, the latest is {}",
+                    consts::CONFIG_VERSION
+                )?;
+                for change in changes {
+                    write!(f, "; {change}")?;
+                }
+                Ok(())
+            }
+            Self::UnknownKey(unknown) => unknown.fmt(f),
+        }
+    }
+}
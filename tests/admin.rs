@@ -0,0 +1,174 @@
+//! Integration tests for the admin HTTP service.
+#![cfg(feature = "admin")]
+
+use magicblock_config::{ConfigHandle, MagicBlockParams};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    path::Path,
+};
+use tempfile::tempdir;
+
+const CONFIG: &str = r#"
+[validator]
+basefee = 10
+
+[admin]
+listen = "127.0.0.1:0"
+token = "let-me-in"
+"#;
+
+fn spawn(path: &Path) -> (ConfigHandle, SocketAddr) {
+    let args = ["magic-block", "--config", path.to_str().unwrap()];
+    let handle = ConfigHandle::new(MagicBlockParams::builder, args.map(Into::into))
+        .expect("Failed to load config");
+    let address = handle
+        .spawn_admin()
+        .expect("Failed to serve")
+        .expect("Admin section should be set");
+    (handle, address)
+}
+
+/// Sends a request and returns the status code and the body.
+fn request(address: SocketAddr, head: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{head}\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    (status, body.to_owned())
+}
+
+fn authorized(method_and_path: &str) -> String {
+    format!("{method_and_path} HTTP/1.1\r\nAuthorization: Bearer let-me-in")
+}
+
+#[test]
+fn test_requests_without_token_are_refused() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).unwrap();
+    let (_handle, address) = spawn(&path);
+
+    let (status, _) = request(address, "GET /config HTTP/1.1", "");
+    assert_eq!(status, 401);
+    let head = "GET /config HTTP/1.1\r\nAuthorization: Bearer let-me-out";
+    let (status, _) = request(address, head, "");
+    assert_eq!(status, 401);
+}
+
+#[test]
+fn test_get_config_redacts_token() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).unwrap();
+    let (_handle, address) = spawn(&path);
+
+    let (status, body) = request(address, &authorized("GET /config"), "");
+
+    assert_eq!(status, 200);
+    let config: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(config["validator"]["basefee"], 10);
+    assert_eq!(config["admin"]["token"], "***REDACTED***");
+}
+
+#[test]
+fn test_provenance_names_the_layer() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).unwrap();
+    let (_handle, address) = spawn(&path);
+
+    let (status, body) = request(address, &authorized("GET /config/provenance"), "");
+
+    assert_eq!(status, 200);
+    let keys: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let basefee = &keys["validator.basefee"];
+    assert_eq!(basefee["value"], 10);
+    assert!(
+        basefee["origin"].as_str().unwrap().starts_with("TOML file"),
+        "{basefee}"
+    );
+    assert_eq!(keys["admin.token"]["value"], "***REDACTED***");
+}
+
+#[test]
+fn test_patch_updates_running_configuration() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).unwrap();
+    let (handle, address) = spawn(&path);
+
+    let body = r#"{"commit": {"compute-unit-price": 5000}}"#;
+    let (status, response) = request(address, &authorized("PATCH /config"), body);
+
+    assert_eq!(status, 200, "{response}");
+    let outcome: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(outcome["generation"], 1);
+    assert_eq!(handle.current().commit.compute_unit_price, 5000);
+}
+
+#[test]
+fn test_patch_of_restart_only_key_is_rejected() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).unwrap();
+    let (handle, address) = spawn(&path);
+
+    let body = r#"{"listen": "127.0.0.1:9000"}"#;
+    let (status, response) = request(address, &authorized("PATCH /config"), body);
+
+    assert_eq!(status, 400);
+    assert!(response.contains("requires a restart"), "{response}");
+    assert_eq!(handle.generation(), 0);
+    let (status, _) = request(address, &authorized("DELETE /config"), "");
+    assert_eq!(status, 405);
+}
+
+#[test]
+fn test_oversized_content_length_is_rejected() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).unwrap();
+    let (_handle, address) = spawn(&path);
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "PATCH /config HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+        u64::MAX
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+
+    // The service is still up.
+    let (status, _) = request(address, &authorized("GET /config"), "");
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn test_tls_section_is_an_unknown_key() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let tls = "[admin.tls]\ncert = \"cert.pem\"\nkey = \"key.pem\"\n";
+    std::fs::write(&path, format!("{CONFIG}{tls}")).unwrap();
+    let args = ["magic-block", "--config", path.to_str().unwrap()];
+
+    let (_, warnings) = MagicBlockParams::try_new_with_warnings(args.into_iter().map(Into::into))
+        .expect("Failed to load config");
+    let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("unknown key `admin.tls`")),
+        "{warnings:?}"
+    );
+}
@@ -0,0 +1,85 @@
+//! Integration tests for customizing the configuration assembly via `ConfigBuilder`.
+
+use magicblock_config::{LifecycleMode, MagicBlockParams};
+
+mod common;
+use common::create_config_file;
+
+const EMBEDDED: &str = r#"
+remote = "mainnet"
+lifecycle = "replica"
+[accounts-db]
+max-snapshots = 16
+[validator]
+basefee = 250
+"#;
+
+fn load_embedded(args: Vec<&str>) -> MagicBlockParams {
+    MagicBlockParams::builder()
+        .with_embedded_defaults(EMBEDDED)
+        .try_build(args.into_iter().map(Into::into))
+        .expect("Failed to assemble config for test")
+}
+
+#[test]
+fn test_embedded_defaults_override_builtin_defaults() {
+    let config = load_embedded(vec!["magic-block"]);
+
+    assert_eq!(config.remote, "mainnet".parse().unwrap());
+    assert_eq!(config.lifecycle, LifecycleMode::Replica);
+    assert_eq!(config.accounts_db.max_snapshots, 16);
+    assert_eq!(config.validator.basefee, 250);
+}
+
+#[test]
+fn test_cli_overrides_embedded_defaults() {
+    let config = load_embedded(vec![
+        "magic-block",
+        "--remote",
+        "localhost",
+        "--basefee",
+        "7",
+    ]);
+
+    assert_eq!(config.remote, "localhost".parse().unwrap());
+    assert_eq!(config.validator.basefee, 7);
+    // Not given on the CLI, still embedded.
+    assert_eq!(config.lifecycle, LifecycleMode::Replica);
+}
+
+#[test]
+fn test_config_file_overrides_embedded_defaults() {
+    let (_dir, path) = create_config_file("config.toml", "[accounts-db]\nmax-snapshots = 2");
+    let config = load_embedded(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+    assert_eq!(config.accounts_db.max_snapshots, 2);
+    assert_eq!(config.validator.basefee, 250);
+}
+
+#[test]
+fn test_custom_providers_layered_above_config_files() {
+    use figment::providers::Serialized;
+
+    let (_dir, path) = create_config_file(
+        "config.toml",
+        "listen = \"0.0.0.0:9000\"\n[validator]\nbasefee = 5000",
+    );
+    let providers: Vec<Box<dyn figment::Provider>> = vec![
+        Box::new(Serialized::default("validator.basefee", 6000)),
+        Box::new(Serialized::default("validator.basefee", 7000)),
+        Box::new(Serialized::default("accounts-db.max-snapshots", 9)),
+    ];
+    let config = MagicBlockParams::try_new_with_providers(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+        providers,
+    )
+    .expect("Failed to assemble config for test");
+
+    // The last provider wins over earlier providers and the file.
+    assert_eq!(config.validator.basefee, 7000);
+    assert_eq!(config.accounts_db.max_snapshots, 9);
+    // Untouched by the providers.
+    assert_eq!(config.listen[0].to_string(), "0.0.0.0:9000");
+}
@@ -0,0 +1,296 @@
+//! Integration tests for the remote connectivity checks.
+#![cfg(feature = "check-remote")]
+
+use magicblock_config::{connectivity::EndpointStatus, MagicBlockParams};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serves JSON-RPC requests on a local port, answering as a healthy node of
+/// the cluster with `genesis_hash`. Returns the server's URL.
+fn mock_node(genesis_hash: &'static str) -> String {
+    mock_node_requiring(genesis_hash, &[])
+}
+
+/// Like [`mock_node`], answering with HTTP status 401 to requests whose
+/// head lacks any of the `required` lines, compared ignoring case, e.g. an
+/// `Authorization` header.
+fn mock_node_requiring(genesis_hash: &'static str, required: &'static [&'static str]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock node");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            answer(&mut BufReader::new(&stream), genesis_hash, required);
+        }
+    });
+    url
+}
+
+/// Reads a JSON-RPC request from `reader` and answers it like [`mock_node`].
+fn answer(reader: &mut BufReader<&TcpStream>, genesis_hash: &str, required: &[&str]) {
+    let mut stream = *reader.get_ref();
+    let mut content_length = 0;
+    let mut head = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+        }
+        head.push(line.trim_end().to_owned());
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+    if !required
+        .iter()
+        .all(|line| head.iter().any(|l| l.eq_ignore_ascii_case(line)))
+    {
+        let _ = write!(
+            stream,
+            "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+        return;
+    }
+    let body = String::from_utf8_lossy(&body);
+    let result = if body.contains("getVersion") {
+        r#"{"solana-core":"2.1.0","feature-set":1}"#.to_owned()
+    } else if body.contains("getHealth") {
+        r#""ok""#.to_owned()
+    } else {
+        format!("\"{genesis_hash}\"")
+    };
+    let response = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{result}}}"#);
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+        response.len()
+    );
+}
+
+/// Serves as a proxy on a local port, speaking SOCKS5 if `socks` is set and
+/// HTTP otherwise, that answers every request like a healthy node of the
+/// cluster with `genesis_hash`, whatever its target. Returns the
+/// proxy's address and the targets of the tunnels opened so far.
+fn mock_proxy(genesis_hash: &'static str, socks: bool) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock proxy");
+    let address = listener.local_addr().unwrap().to_string();
+    let targets = Arc::new(Mutex::new(Vec::new()));
+    let opened = Arc::clone(&targets);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let target = if socks {
+                socks5_target(&mut reader)
+            } else {
+                http_proxy_target(&mut reader)
+            };
+            let Some(target) = target else {
+                continue;
+            };
+            opened.lock().unwrap().push(target);
+            answer(&mut reader, genesis_hash, &[]);
+        }
+    });
+    (address, targets)
+}
+
+/// Reads the request line of a request to an HTTP proxy, returning its
+/// target. A `CONNECT` request is accepted, while the rest of a request in
+/// absolute form, e.g. `POST http://host:port/`, is left to be answered.
+fn http_proxy_target(reader: &mut BufReader<&TcpStream>) -> Option<String> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let (method, rest) = line.split_once(' ')?;
+    let target = rest.split(' ').next()?;
+    if method != "CONNECT" {
+        let target = target.strip_prefix("http://")?.split('/').next()?;
+        return Some(target.to_owned());
+    }
+    let target = target.to_owned();
+    while line != "\r\n" {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+    }
+    let mut stream = *reader.get_ref();
+    write!(stream, "HTTP/1.1 200 Connection established\r\n\r\n").ok()?;
+    Some(target)
+}
+
+/// Reads a SOCKS5 greeting and a request to connect to a domain name, and
+/// accepts them, returning the target.
+fn socks5_target(reader: &mut BufReader<&TcpStream>) -> Option<String> {
+    let mut greeting = [0; 2];
+    reader.read_exact(&mut greeting).ok()?;
+    reader.read_exact(&mut vec![0; greeting[1].into()]).ok()?;
+    let mut stream = *reader.get_ref();
+    stream.write_all(&[5, 0]).ok()?;
+    let mut head = [0; 5];
+    reader.read_exact(&mut head).ok()?;
+    let mut domain = vec![0; head[4].into()];
+    reader.read_exact(&mut domain).ok()?;
+    let mut port = [0; 2];
+    reader.read_exact(&mut port).ok()?;
+    let reply = [5, 0, 0, 1, 127, 0, 0, 1, 0, 0];
+    stream.write_all(&reply).ok()?;
+    let domain = String::from_utf8(domain).ok()?;
+    Some(format!("{domain}:{}", u16::from_be_bytes(port)))
+}
+
+/// An address nothing listens on.
+fn closed_port_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind port");
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+fn params(args: &[&str]) -> MagicBlockParams {
+    let mut argv = vec!["magic-block"];
+    argv.extend(args);
+    MagicBlockParams::try_new(argv.into_iter().map(Into::into)).expect("Failed to load config")
+}
+
+#[test]
+fn test_http_endpoint_reports_version_and_genesis_hash() {
+    let node = mock_node("GH1");
+
+    let check = params(&["--remote", &node]).check_remote(TIMEOUT);
+
+    assert_eq!(
+        check.endpoints[0].status,
+        Ok(EndpointStatus::Http {
+            version: "2.1.0".into(),
+            genesis_hash: "GH1".into(),
+        })
+    );
+    // Nothing serves WebSocket connections on the next port.
+    assert!(check.endpoints[1].status.is_err());
+    assert!(!check.is_ok());
+}
+
+#[test]
+fn test_unreachable_endpoint_fails_the_check() {
+    let url = closed_port_url();
+
+    let check = params(&["--remote", &url]).check_remote(TIMEOUT);
+
+    assert!(check.endpoints.iter().all(|e| e.status.is_err()));
+    assert!(check.to_string().starts_with("error "));
+}
+
+#[test]
+fn test_endpoints_of_different_clusters_are_reported() {
+    let (first, second) = (mock_node("GH1"), mock_node("GH2"));
+    let content =
+        format!("remote = [{{ http = \"{first}\", ws = \"ws://127.0.0.1:1\" }}, \"{second}\"]");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, content).unwrap();
+
+    let check = params(&["--config", path.to_str().unwrap()]).check_remote(TIMEOUT);
+
+    assert_eq!(check.endpoints.len(), 4);
+    assert!(check
+        .to_string()
+        .contains("different clusters, with genesis hashes GH1, GH2"));
+}
+
+#[test]
+fn test_pinned_genesis_hash_is_verified() {
+    let node = mock_node("GH1");
+    let hash = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let content = format!("remote = {{ url = \"{node}\", expected-genesis = \"{hash}\" }}");
+    std::fs::write(&path, content).unwrap();
+
+    let check = params(&["--config", path.to_str().unwrap()]).check_remote(TIMEOUT);
+
+    assert_eq!(
+        check.endpoints[0].status,
+        Err(format!("genesis hash is GH1, expected {hash}"))
+    );
+}
+
+#[test]
+fn test_remote_credentials_are_sent() {
+    let required = &[
+        "POST /?api-key=k3y HTTP/1.1",
+        "authorization: Bearer t0ken",
+        "x-token: tr1ton",
+    ];
+    let node = mock_node_requiring("GH1", required);
+    let remote = |token: &str| {
+        format!("[remote]\nurl = \"{node}\"\napi-key = \"k3y\"\ntoken = \"{token}\"\nheaders = {{ x-token = \"tr1ton\" }}")
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+
+    std::fs::write(&path, remote("t0ken")).unwrap();
+    let check = params(&["--config", path.to_str().unwrap()]).check_remote(TIMEOUT);
+    assert!(check.endpoints[0].status.is_ok(), "{check}");
+    assert!(!check.to_string().contains("k3y"), "{check}");
+
+    std::fs::write(&path, remote("wr0ng")).unwrap();
+    let check = params(&["--config", path.to_str().unwrap()]).check_remote(TIMEOUT);
+    assert_eq!(
+        check.endpoints[0].status,
+        Err("getVersion failed with HTTP status 401".into())
+    );
+}
+
+#[test]
+fn test_endpoints_are_checked_through_the_proxy() {
+    for (scheme, socks) in [("http", false), ("socks5h", true)] {
+        let (proxy, targets) = mock_proxy("GH1", socks);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = format!(
+            "remote = \"http://node.invalid:8899\"\n[network.proxy]\nurl = \"{scheme}://{proxy}\""
+        );
+        std::fs::write(&path, content).unwrap();
+
+        let check = params(&["--config", path.to_str().unwrap()]).check_remote(TIMEOUT);
+
+        assert!(check.endpoints[0].status.is_ok(), "{scheme}: {check}");
+        // The mock node doesn't speak WebSocket, but was reached.
+        let ws = check.endpoints[1].status.as_ref().unwrap_err();
+        assert!(ws.starts_with("handshake failed"), "{scheme}: {ws}");
+        // `node.invalid` doesn't resolve, so the WebSocket check went
+        // through the proxy too, on the same port for a remote host.
+        let targets = targets.lock().unwrap();
+        assert!(
+            targets.iter().all(|target| target == "node.invalid:8899"),
+            "{targets:?}"
+        );
+    }
+}
+
+#[test]
+fn test_endpoints_behind_https_proxy_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let content = "remote = \"http://node.invalid:8899\"\n[network.proxy]\nurl = \"https://proxy.invalid:3128\"";
+    std::fs::write(&path, content).unwrap();
+
+    let check = params(&["--config", path.to_str().unwrap()]).check_remote(TIMEOUT);
+
+    assert!(check.is_ok(), "{check}");
+    assert!(check
+        .endpoints
+        .iter()
+        .all(|e| matches!(e.status, Ok(EndpointStatus::Skipped(_)))));
+    assert!(check.to_string().starts_with("skip  "), "{check}");
+}
@@ -0,0 +1,12 @@
+//! Helpers shared by the integration tests.
+
+use std::path::PathBuf;
+use tempfile::{tempdir, TempDir};
+
+/// Writes `content` to `name` inside a fresh temporary directory.
+pub fn create_config_file(name: &str, content: &str) -> (TempDir, PathBuf) {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, content).expect("Failed to write temp config file");
+    (dir, path)
+}
@@ -0,0 +1,26 @@
+//! Integration tests for shell completion generation.
+
+fn completions(shell: &str) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["completions", shell])
+        .output()
+        .expect("Failed to run the binary")
+}
+
+#[test]
+fn test_completions_cover_flattened_options() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = completions(shell);
+        assert!(output.status.success(), "{shell}");
+        let script = String::from_utf8_lossy(&output.stdout);
+        for option in ["config", "lifecycle", "basefee", "keypair"] {
+            assert!(script.contains(option), "{shell} completions lack {option}");
+        }
+    }
+}
+
+#[test]
+fn test_unknown_shell_is_rejected() {
+    let output = completions("tcsh");
+    assert!(!output.status.success());
+}
@@ -0,0 +1,113 @@
+//! Integration tests for loading renamed keys under their deprecated names.
+
+use magicblock_config::{ConfigWarning, MagicBlockParams};
+use tempfile::tempdir;
+
+mod common;
+use common::create_config_file;
+
+fn load(args: Vec<&str>) -> (MagicBlockParams, Vec<ConfigWarning>) {
+    MagicBlockParams::try_new_with_warnings(args.into_iter().map(Into::into))
+        .expect("Failed to assemble config for test")
+}
+
+#[test]
+fn test_deprecated_key_loads_with_warning() {
+    let (_dir, path) = create_config_file("config.toml", "[validator]\nbase-fee = 42");
+    let (config, warnings) = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+    assert_eq!(config.validator.basefee, 42);
+    assert_eq!(warnings.len(), 1);
+    let ConfigWarning::DeprecatedKey {
+        key,
+        replacement,
+        origin,
+    } = &warnings[0]
+    else {
+        panic!("expected a deprecated key, got {:?}", warnings[0]);
+    };
+    assert_eq!(key, "validator.base-fee");
+    assert_eq!(replacement, "validator.basefee");
+    assert!(
+        origin.as_ref().unwrap().contains("config.toml"),
+        "{origin:?}"
+    );
+}
+
+#[test]
+fn test_replacement_key_wins_within_a_file() {
+    let (_dir, path) = create_config_file("config.toml", "[validator]\nbase-fee = 42\nbasefee = 7");
+    let (config, warnings) = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+    assert_eq!(config.validator.basefee, 7);
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn test_deprecated_key_follows_layer_precedence() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let base = dir.path().join("base.toml");
+    let overlay = dir.path().join("overlay.toml");
+    std::fs::write(&base, "[validator]\nbasefee = 1").unwrap();
+    std::fs::write(&overlay, "[validator]\nbase-fee = 2").unwrap();
+
+    let (config, _) = load(vec![
+        "magic-block",
+        "--config",
+        base.to_str().unwrap(),
+        "--config",
+        overlay.to_str().unwrap(),
+    ]);
+    assert_eq!(config.validator.basefee, 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_deprecated_section_keeps_its_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("Failed to create temp dir");
+    let cert = dir.path().join("metrics.crt");
+    let key = dir.path().join("metrics.key");
+    std::fs::write(&cert, "certificate").unwrap();
+    std::fs::write(&key, "private key").unwrap();
+    std::fs::set_permissions(&key, std::fs::Permissions::from_mode(0o600)).unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "metrics = \"127.0.0.1:9100\"\n[metrics-tls]\ncert = \"metrics.crt\"\nkey = \"metrics.key\"",
+    )
+    .unwrap();
+    let (config, warnings) = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+    let tls = config
+        .metrics
+        .tls
+        .expect("The old section should still load");
+    // Relative paths are still resolved against the file that set them.
+    assert_eq!(tls.cert, cert);
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "127.0.0.1:9100");
+    let [ConfigWarning::DeprecatedKey {
+        key, replacement, ..
+    }] = &warnings[..]
+    else {
+        panic!("expected a deprecated key, got {warnings:?}");
+    };
+    assert_eq!(
+        (key.as_str(), replacement.as_str()),
+        ("metrics-tls", "metrics.tls")
+    );
+}
+
+#[test]
+fn test_current_keys_emit_no_warnings() {
+    let (_dir, path) = create_config_file("config.toml", "[validator]\nbasefee = 42");
+    let (_, warnings) = load(vec![
+        "magic-block",
+        "--strict-config",
+        "--config",
+        path.to_str().unwrap(),
+    ]);
+
+    assert!(warnings.is_empty());
+}
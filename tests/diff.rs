@@ -0,0 +1,145 @@
+//! Integration tests for comparing two configurations.
+
+use magicblock_config::{diff::KeyChange, sources::FileOptions, ConfigDiff, MagicBlockParams};
+use solana_keypair::Keypair;
+use std::path::PathBuf;
+
+mod common;
+use common::create_config_file;
+
+fn load(content: &str) -> MagicBlockParams {
+    let (_dir, path) = create_config_file("config.toml", content);
+    MagicBlockParams::from_file(&path, &FileOptions::default()).expect("Failed to load config")
+}
+
+#[test]
+fn test_diff_reports_added_removed_and_changed_keys() {
+    let old = load("storage = \"/var/lib/a\"\n[validator]\nbasefee = 100");
+    let new = load("metrics = \"127.0.0.1:9000\"\n[validator]\nbasefee = 5000");
+
+    let diff = old.diff(&new);
+
+    assert_eq!(
+        diff.changes,
+        [
+            KeyChange::Added {
+                key: "metrics.listen".into(),
+                value: "\"127.0.0.1:9000\"".into(),
+            },
+            KeyChange::Removed {
+                key: "storage".into(),
+                value: "\"/var/lib/a\"".into(),
+            },
+            KeyChange::Changed {
+                key: "validator.basefee".into(),
+                old: "100".into(),
+                new: "5000".into(),
+            },
+        ]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "+ metrics.listen = \"127.0.0.1:9000\"\n- storage = \"/var/lib/a\"\n~ validator.basefee: 100 -> 5000"
+    );
+}
+
+#[test]
+fn test_identical_configs_have_empty_diff() {
+    let config = load("[ledger]\nreset = false");
+    assert_eq!(
+        config.diff(&load("[ledger]\nreset = false")),
+        ConfigDiff::default()
+    );
+}
+
+#[test]
+fn test_diff_redacts_secrets() {
+    let keypair = Keypair::new().to_base58_string();
+    let old = load("");
+    let new = load(&format!("[validator]\nkeypair = \"{keypair}\""));
+
+    let diff = old.diff(&new);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].key(), "validator.keypair");
+    assert!(!diff.to_string().contains(&keypair), "{diff}");
+    assert!(diff.to_string().contains("***REDACTED***"), "{diff}");
+}
+
+#[test]
+fn test_diff_redacts_tracing_headers() {
+    let old = load("[tracing]\nheaders = { x-api-key = \"0ld-s3cret\" }");
+    let new = load("[tracing]\nheaders = { x-api-key = \"n3w-s3cret\" }");
+
+    let diff = old.diff(&new);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].key(), "tracing.headers.x-api-key");
+    let rendered = diff.to_string();
+    assert!(!rendered.contains("s3cret"), "{rendered}");
+    assert!(rendered.contains("***REDACTED***"), "{rendered}");
+}
+
+#[test]
+fn test_diff_redacts_remote_credentials() {
+    let remote = |token: &str| {
+        format!(
+            "[[remote]]\nurl = \"https://rpc.example.com\"\nheaders = {{ x-token = \"{token}\" }}"
+        )
+    };
+    let old = load(&remote("0ld-t0ken"));
+    let new = load(&remote("n3w-t0ken"));
+
+    let diff = old.diff(&new);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].key(), "remote");
+    let rendered = diff.to_string();
+    assert!(!rendered.contains("t0ken"), "{rendered}");
+    assert!(rendered.contains("***REDACTED***"), "{rendered}");
+    assert_eq!(old.fingerprint(), new.fingerprint());
+}
+
+#[test]
+fn test_diff_subcommand_exit_status() {
+    let (_a, old) = create_config_file("old.toml", "[ledger]\nreset = true");
+    let (_b, new) = create_config_file("new.toml", "[ledger]\nreset = false");
+    let diff = |a: &PathBuf, b: &PathBuf| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+            .arg("diff")
+            .args([a, b])
+            .output()
+            .expect("Failed to run the binary")
+    };
+
+    let output = diff(&old, &old);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let output = diff(&old, &new);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "~ ledger.reset: true -> false\n"
+    );
+}
+
+#[test]
+fn test_fingerprint_follows_values_only() {
+    let config = load("[validator]\nbasefee = 5000\n[ledger]\nreset = false");
+    let reordered = load("[ledger]\nreset = false\n[validator]\nbasefee = 5000");
+    let changed = load("[validator]\nbasefee = 5001\n[ledger]\nreset = false");
+    let keypair = Keypair::new().to_base58_string();
+    let other_identity = load(&format!(
+        "[validator]\nbasefee = 5000\nkeypair = \"{keypair}\"\n[ledger]\nreset = false"
+    ));
+
+    assert_eq!(config.fingerprint(), reordered.fingerprint());
+    assert_ne!(config.fingerprint(), changed.fingerprint());
+    // Secrets are left out, so the fingerprint can be shared.
+    assert_eq!(config.fingerprint(), other_identity.fingerprint());
+    assert_ne!(
+        config.fingerprint(),
+        MagicBlockParams::default().fingerprint()
+    );
+}
@@ -0,0 +1,194 @@
+//! Integration tests for the `doctor` subcommand.
+
+use magicblock_config::{
+    doctor::{Diagnosis, Status},
+    MagicBlockParams,
+};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn diagnose(args: &[&str]) -> Diagnosis {
+    let mut argv = vec!["magic-block"];
+    argv.extend(args);
+    MagicBlockParams::doctor(argv.into_iter().map(Into::into), TIMEOUT)
+}
+
+fn status_of(diagnosis: &Diagnosis, check: &str) -> Vec<Status> {
+    diagnosis
+        .findings
+        .iter()
+        .filter(|finding| finding.check == check)
+        .map(|finding| finding.status)
+        .collect()
+}
+
+#[test]
+fn test_invalid_configuration_fails() {
+    let diagnosis = diagnose(&["--lifecycle", "ephemeral", "--listen", "127.0.0.1:0"]);
+
+    assert!(!diagnosis.is_ok());
+    assert!(status_of(&diagnosis, "configuration").contains(&Status::Fail));
+    // The configuration still loads, so the probes run.
+    assert_eq!(status_of(&diagnosis, "ports"), [Status::Pass]);
+}
+
+#[test]
+fn test_unloadable_configuration_skips_probes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "lifecycle = [").unwrap();
+
+    let diagnosis = diagnose(&["--config", path.to_str().unwrap()]);
+
+    assert_eq!(status_of(&diagnosis, "configuration"), [Status::Fail]);
+    assert_eq!(status_of(&diagnosis, "probes"), [Status::Warn]);
+    assert!(status_of(&diagnosis, "ports").is_empty());
+}
+
+#[test]
+fn test_taken_port_fails() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    let diagnosis = diagnose(&["--listen", &address]);
+
+    assert_eq!(status_of(&diagnosis, "configuration"), [Status::Pass]);
+    assert_eq!(status_of(&diagnosis, "ports"), [Status::Fail]);
+    assert!(!diagnosis.is_ok());
+}
+
+#[test]
+fn test_missing_storage_is_checked_where_it_will_be_created() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = dir.path().join("ledger/accounts");
+    let storage = storage.to_str().unwrap();
+
+    let diagnosis = diagnose(&["--storage", storage, "--create-storage-dirs"]);
+    let finding = diagnosis.findings.iter().find(|f| f.check == "storage");
+    let finding = finding.expect("storage should be checked");
+    assert_eq!(finding.status, Status::Pass);
+    assert!(finding.message.contains("will be created"), "{finding}");
+    assert!(!std::path::Path::new(storage).exists());
+
+    let file = dir.path().join("file");
+    std::fs::write(&file, "").unwrap();
+    let storage = file.join("ledger");
+    let diagnosis = diagnose(&[
+        "--storage",
+        storage.to_str().unwrap(),
+        "--create-storage-dirs",
+    ]);
+    assert_eq!(status_of(&diagnosis, "storage"), [Status::Fail]);
+}
+
+#[test]
+fn test_doctor_subcommand_exits_with_failure() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["--lifecycle", "ephemeral", "doctor"])
+        .output()
+        .expect("Failed to run the binary");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[fail] configuration: "), "{stdout}");
+    assert!(stdout.contains(" failed"), "{stdout}");
+}
+
+#[cfg(not(feature = "check-remote"))]
+#[test]
+fn test_remote_is_not_checked_without_feature() {
+    let diagnosis = diagnose(&["--listen", "127.0.0.1:0"]);
+
+    assert_eq!(status_of(&diagnosis, "remote"), [Status::Warn]);
+    assert!(diagnosis.is_ok());
+}
+
+#[cfg(feature = "check-remote")]
+mod remote {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread,
+        time::SystemTime,
+    };
+
+    /// Serves JSON-RPC requests on a local port, answering as a healthy node
+    /// on which every account holds `lamports`. Returns the server's URL.
+    fn mock_node(lamports: u64) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock node");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                if reader.read_exact(&mut body).is_err() {
+                    continue;
+                }
+                let body = String::from_utf8_lossy(&body);
+                let result = if body.contains("getVersion") {
+                    r#"{"solana-core":"2.1.0","feature-set":1}"#.to_owned()
+                } else if body.contains("getHealth") {
+                    r#""ok""#.to_owned()
+                } else if body.contains("getBalance") {
+                    format!(r#"{{"context":{{"slot":1}},"value":{lamports}}}"#)
+                } else {
+                    r#""GH1""#.to_owned()
+                };
+                let response = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{result}}}"#);
+                let _ = write!(
+                    &stream,
+                    "HTTP/1.1 200 OK\r\nDate: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    httpdate::fmt_http_date(SystemTime::now()),
+                    response.len()
+                );
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_funded_keypair_and_clock_pass() {
+        let node = mock_node(1_000_000);
+
+        let diagnosis = diagnose(&["--remote", &node, "--listen", "127.0.0.1:0"]);
+
+        assert_eq!(status_of(&diagnosis, "clock"), [Status::Pass]);
+        assert_eq!(status_of(&diagnosis, "keypair"), [Status::Pass]);
+    }
+
+    #[test]
+    fn test_unfunded_keypair_warns() {
+        let node = mock_node(0);
+
+        let diagnosis = diagnose(&["--remote", &node, "--listen", "127.0.0.1:0"]);
+
+        assert_eq!(status_of(&diagnosis, "keypair"), [Status::Warn]);
+    }
+
+    #[test]
+    fn test_unreachable_remote_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let diagnosis = diagnose(&["--remote", &url, "--listen", "127.0.0.1:0"]);
+
+        assert!(status_of(&diagnosis, "remote").contains(&Status::Fail));
+        assert_eq!(status_of(&diagnosis, "keypair"), [Status::Warn]);
+        assert!(!diagnosis.is_ok());
+    }
+}
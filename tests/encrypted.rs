@@ -0,0 +1,135 @@
+//! Integration tests for loading SOPS- and age-encrypted configuration files.
+
+use magicblock_config::{encrypted::Encryption, sources::ConfigFormat, MagicBlockParams};
+use std::path::{Path, PathBuf};
+use tempfile::{tempdir, TempDir};
+
+/// Writes `content` to `name` inside `dir`.
+fn write_file(dir: &Path, name: &str, content: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, content).expect("Failed to write temp file");
+    path
+}
+
+/// A directory holding a stand-in for the `program` CLI, which runs `script`.
+#[cfg(unix)]
+fn stand_in(program: &str, script: &str) -> TempDir {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = write_file(dir.path(), program, &format!("#!/bin/sh\n{script}\n"));
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    dir
+}
+
+/// Runs `print-config --format json` with the stand-ins in `bin` first on
+/// the `PATH`, returning the printed configuration.
+#[cfg(unix)]
+fn print_config(bin: &Path, args: &[&str], envs: &[(&str, &Path)]) -> serde_json::Value {
+    let path = std::env::var("PATH").unwrap_or_default();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(args)
+        .args(["print-config", "--format", "json"])
+        .env("PATH", format!("{}:{path}", bin.display()))
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Failed to run the binary");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    serde_json::from_slice(&output.stdout).expect("print-config should print JSON")
+}
+
+#[test]
+fn test_encryption_detected_by_name_and_content() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let cases = [
+        ("config.toml.age", "anything", Some(Encryption::Age)),
+        ("config.sops.yaml", "anything", Some(Encryption::Sops)),
+        (
+            "armored.toml",
+            "-----BEGIN AGE ENCRYPTED FILE-----\n",
+            Some(Encryption::Age),
+        ),
+        (
+            "config.yaml",
+            "validator:\n  basefee: ENC[AES256_GCM,data:Mw==,type:int]\nsops:\n  mac: ENC[AES256_GCM,data:x,type:str]\n",
+            Some(Encryption::Sops),
+        ),
+        ("plain.toml", "[validator]\nbasefee = 1\n", None),
+    ];
+
+    for (name, content, expected) in cases {
+        let path = write_file(dir.path(), name, content);
+        let format = ConfigFormat::from_path(&path);
+        assert_eq!(Encryption::detect(&path, format), expected, "{name}");
+    }
+    assert_eq!(
+        ConfigFormat::from_path(Path::new("config.yaml.age")),
+        ConfigFormat::Yaml
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_age_encrypted_file_is_decrypted_with_identity() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let config = write_file(dir.path(), "config.toml.age", "age-encryption.org/v1\n");
+    let identity = write_file(dir.path(), "identity.txt", "AGE-SECRET-KEY-1");
+    let expected = format!(
+        "--decrypt --identity {} {}",
+        identity.display(),
+        config.display()
+    );
+    let bin = stand_in(
+        "age",
+        &format!("[ \"$*\" = \"{expected}\" ] || exit 1\nprintf '[validator]\\nbasefee = 42\\n'"),
+    );
+
+    let printed = print_config(
+        bin.path(),
+        &["--config", config.to_str().unwrap()],
+        &[("MBV_AGE_IDENTITY", &identity)],
+    );
+    assert_eq!(printed["validator"]["basefee"], 42);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_sops_encrypted_file_is_decrypted() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let config = write_file(
+        dir.path(),
+        "config.yaml",
+        "validator:\n  basefee: ENC[AES256_GCM,data:Mw==,type:int]\nsops:\n  mac: ENC[AES256_GCM,data:x,type:str]\n",
+    );
+    let identity = write_file(dir.path(), "identity.txt", "AGE-SECRET-KEY-1");
+    let bin = stand_in(
+        "sops",
+        &format!(
+            "[ \"$SOPS_AGE_KEY_FILE\" = \"{}\" ] || exit 1\nprintf 'validator:\\n  basefee: 7\\n'",
+            identity.display()
+        ),
+    );
+
+    let printed = print_config(
+        bin.path(),
+        &["--config", config.to_str().unwrap()],
+        &[("MBV_AGE_IDENTITY", &identity)],
+    );
+    assert_eq!(printed["validator"]["basefee"], 7);
+}
+
+#[test]
+fn test_age_encrypted_file_requires_identity() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let config = write_file(dir.path(), "config.toml.age", "age-encryption.org/v1\n");
+
+    let err = MagicBlockParams::try_new(
+        ["magic-block", "--config", config.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    )
+    .expect_err("age files can't be decrypted without an identity")
+    .to_string();
+    assert!(err.contains("MBV_AGE_IDENTITY"), "{err}");
+}
@@ -0,0 +1,83 @@
+//! Integration tests for explaining where configuration values come from.
+
+use magicblock_config::{ConfigError, MagicBlockParams};
+
+mod common;
+use common::create_config_file;
+
+#[test]
+fn test_explain_names_the_winning_layer() {
+    let (_dir, path) = create_config_file(
+        "config.toml",
+        "[validator]\nbasefee = 7\n[accounts-db]\nmax-snapshots = 3",
+    );
+    let explain = |key: &str| {
+        let args = [
+            "magic-block",
+            "--listen",
+            "0.0.0.0:9000",
+            "--config",
+            path.to_str().unwrap(),
+            "--set",
+            "accounts-db.max-snapshots=5",
+        ];
+        MagicBlockParams::explain(args.into_iter().map(Into::into), key)
+            .expect("Failed to explain key")
+    };
+
+    let basefee = explain("validator.basefee");
+    assert_eq!(basefee.value.unwrap().to_i128(), Some(7));
+    assert!(basefee.origin.unwrap().contains("config.toml"));
+    assert_eq!(basefee.doc, Some("Base fee in lamports for transactions."));
+
+    let listen = explain("listen");
+    assert_eq!(listen.origin.as_deref(), Some("command-line arguments"));
+
+    let max_snapshots = explain("accounts-db.max-snapshots");
+    assert_eq!(max_snapshots.value.unwrap().to_u128(), Some(5));
+    assert_eq!(max_snapshots.origin.as_deref(), Some("`--set` argument"));
+
+    let block_time = explain("ledger.block-time");
+    assert_eq!(block_time.origin.as_deref(), Some("built-in defaults"));
+    assert_eq!(
+        block_time.to_string().lines().next(),
+        Some("ledger.block-time = \"400ms\"")
+    );
+}
+
+#[test]
+fn test_explain_unset_and_unknown_keys() {
+    let args = || ["magic-block"].into_iter().map(Into::into);
+
+    let metrics =
+        MagicBlockParams::explain(args(), "metrics.listen").expect("Failed to explain key");
+    assert_eq!(metrics.value, None);
+    assert!(metrics.to_string().starts_with("metrics.listen is not set"));
+
+    let err = MagicBlockParams::explain(args(), "ledger.nope").expect_err("Unknown keys");
+    let ConfigError::Invalid(report) = err else {
+        panic!("expected an invalid key, got {err:?}");
+    };
+    assert_eq!(report.errors[0].key, "ledger.nope");
+}
+
+#[test]
+fn test_explain_subcommand() {
+    let (_dir, path) = create_config_file("config.toml", "[ledger]\nreset = false");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args([
+            "explain",
+            "ledger.reset",
+            "--config",
+            path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run the binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("ledger.reset = false\n  set by: TOML file"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,69 @@
+//! Integration tests for validator keypair generation.
+
+use magicblock_config::{keygen, MagicBlockParams};
+use std::{path::Path, process::Output};
+use tempfile::tempdir;
+
+fn run_keygen(args: &[&str], path: &Path) -> Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .arg("keygen")
+        .arg(path)
+        .args(args)
+        .output()
+        .expect("Failed to run the binary")
+}
+
+#[test]
+fn test_generated_keypair_is_readable_by_solana_tools() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("keys/identity.json");
+
+    let keypair = keygen::generate_keypair_file(&path).expect("Failed to write keypair");
+
+    let read = solana_keypair::read_keypair_file(&path).expect("Keypair should be readable");
+    assert_eq!(read, keypair);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_snippet_sets_the_generated_keypair() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("identity.json");
+
+    let output = run_keygen(&["--snippet"], &path);
+
+    assert!(output.status.success());
+    let keypair = solana_keypair::read_keypair_file(&path).unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&keygen::pubkey(&keypair).to_string()),
+        "{stderr}"
+    );
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, &output.stdout).unwrap();
+    let config = MagicBlockParams::from_file(&config_path, &Default::default())
+        .expect("Snippet should load");
+    assert_eq!(config.validator.keypair.expose_secret().0, keypair);
+}
+
+#[test]
+fn test_existing_file_is_kept_without_force() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("identity.json");
+    std::fs::write(&path, "keep me").unwrap();
+
+    let output = run_keygen(&[], &path);
+
+    assert!(!output.status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+
+    let output = run_keygen(&["--force"], &path);
+
+    assert!(output.status.success());
+    assert!(solana_keypair::read_keypair_file(&path).is_ok());
+}
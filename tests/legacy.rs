@@ -0,0 +1,138 @@
+//! Integration tests for migrating legacy configuration files.
+
+use magicblock_config::{consts, ConfigError, LifecycleMode, MagicBlockParams};
+use std::time::Duration;
+
+mod common;
+use common::create_config_file;
+
+const LEGACY: &str = r#"
+[accounts]
+remote = "devnet"
+lifecycle = "replica"
+max-monitored-accounts = 4096
+
+[accounts.commit]
+frequency_millis = 50
+compute_unit_price = 1000000
+
+[accounts.db]
+db-size = 209715200
+block-size = "block512"
+index-map-size = 1048576
+max-snapshots = 5
+snapshot-frequency = 512
+
+[rpc]
+addr = "127.0.0.1"
+port = 7799
+
+[validator]
+millis_per_slot = 100
+sigverify = true
+base_fees = 10
+
+[ledger]
+reset = true
+path = "ledger"
+
+[metrics]
+enabled = true
+port = 9999
+"#;
+
+#[test]
+fn test_legacy_keys_map_to_current_ones() {
+    let params = MagicBlockParams::migrate_legacy(LEGACY).expect("Failed to migrate");
+
+    assert_eq!(params.config_version, Some(consts::CONFIG_VERSION));
+    assert_eq!(params.lifecycle, LifecycleMode::Replica);
+    assert_eq!(params.chainlink.max_monitored_accounts, 4096);
+    assert_eq!(params.commit.compute_unit_price, 1_000_000);
+    assert_eq!(params.accounts_db.database_size, 209_715_200);
+    assert_eq!(params.accounts_db.index_size, 1_048_576);
+    assert_eq!(params.accounts_db.max_snapshots, 5);
+    assert_eq!(params.accounts_db.snapshot_frequency, 512);
+    assert_eq!(params.listen[0].to_string(), "127.0.0.1:7799");
+    assert_eq!(params.metrics.listen.unwrap().to_string(), "0.0.0.0:9999");
+    assert_eq!(params.validator.basefee, 10);
+    assert_eq!(params.ledger.block_time, Duration::from_millis(100));
+    assert!(params.ledger.reset);
+    assert!(params
+        .remote
+        .urls()
+        .any(|url| url.as_str().contains("devnet")));
+}
+
+#[test]
+fn test_migrated_config_round_trips() {
+    let params = MagicBlockParams::migrate_legacy(LEGACY).expect("Failed to migrate");
+    let toml = params
+        .to_toml_exposing_secrets()
+        .expect("Failed to serialize config");
+    let (_dir, path) = create_config_file("config.toml", &toml);
+
+    let reloaded = MagicBlockParams::from_file(&path, &Default::default())
+        .expect("Migrated config should load");
+
+    assert!(params.diff(&reloaded).is_empty());
+}
+
+#[test]
+fn test_legacy_remote_with_separate_websocket_url() {
+    let params = MagicBlockParams::migrate_legacy(
+        "[accounts.remote]\nurl = \"https://rpc.example.com\"\nws-url = \"wss://ws.example.com\"",
+    )
+    .expect("Failed to migrate");
+
+    let urls: Vec<_> = params.remote.urls().map(|url| url.to_string()).collect();
+    assert_eq!(urls, ["https://rpc.example.com/", "wss://ws.example.com/"]);
+}
+
+#[test]
+fn test_legacy_websocket_url_without_url_is_rejected() {
+    let result =
+        MagicBlockParams::migrate_legacy("[accounts.remote]\nws-url = \"wss://ws.example.com\"");
+
+    let Err(ConfigError::Invalid(report)) = result else {
+        panic!("Expected an invalid configuration, got {result:?}");
+    };
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].key, "accounts.remote.ws-url");
+}
+
+#[test]
+fn test_disabled_metrics_are_left_unset() {
+    let params = MagicBlockParams::migrate_legacy("[metrics]\nenabled = false\nport = 9999")
+        .expect("Failed to migrate");
+
+    assert!(params.metrics.listen.is_none());
+}
+
+#[test]
+fn test_unknown_legacy_key_is_rejected() {
+    let result = MagicBlockParams::migrate_legacy("[accounts]\nclone = \"all\"");
+
+    let Err(ConfigError::Invalid(report)) = result else {
+        panic!("Expected an invalid configuration, got {result:?}");
+    };
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].key, "accounts.clone");
+}
+
+#[test]
+fn test_migrate_subcommand_writes_output_file() {
+    let (dir, legacy) = create_config_file("legacy.toml", LEGACY);
+    let output = dir.path().join("config.toml");
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["migrate", legacy.to_str().unwrap(), "--output"])
+        .arg(&output)
+        .status()
+        .expect("Failed to run the binary");
+
+    assert!(status.success());
+    let written = std::fs::read_to_string(&output).expect("Output should be written");
+    assert!(written.starts_with("config-version = 2\n"));
+    assert!(written.contains("basefee = 10"));
+}
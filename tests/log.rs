@@ -0,0 +1,60 @@
+//! Integration tests for the logging section and the verbosity flags.
+
+use magicblock_config::{
+    config::{LogFormat, LogLevel},
+    sources, MagicBlockParams,
+};
+use tempfile::tempdir;
+
+fn load(args: &[&str]) -> MagicBlockParams {
+    let mut argv = vec!["magic-block"];
+    argv.extend(args);
+    MagicBlockParams::try_new(argv.into_iter().map(Into::into)).expect("Failed to load config")
+}
+
+#[test]
+fn test_verbosity_flags_adjust_level() {
+    assert_eq!(load(&[]).log.level, LogLevel::Info);
+    assert_eq!(load(&["-v"]).log.level, LogLevel::Debug);
+    assert_eq!(load(&["-vvvv"]).log.level, LogLevel::Trace);
+    assert_eq!(load(&["-qq"]).log.level, LogLevel::Error);
+    assert_eq!(load(&["--quiet", "-qqq"]).log.level, LogLevel::Off);
+}
+
+#[test]
+fn test_log_section_from_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        r#"
+        [log]
+        level = "warn"
+        format = "json"
+        [log.filters]
+        magicblock_ledger = "debug"
+        "magicblock_accounts::db" = "trace"
+        "#,
+    )
+    .unwrap();
+
+    let config = load(&["--strict-config", "--config", path.to_str().unwrap(), "-v"]);
+
+    assert_eq!(config.log.format, LogFormat::Json);
+    assert_eq!(
+        config.log.filter(),
+        "info,magicblock_accounts::db=trace,magicblock_ledger=debug"
+    );
+}
+
+#[test]
+fn test_module_filters_from_environment() {
+    assert_eq!(
+        sources::env_key_path("MBV_LOG_FILTERS_MAGICBLOCK_LEDGER").as_deref(),
+        Some("log.filters.magicblock_ledger")
+    );
+    assert_eq!(
+        sources::env_key_path("MBV_LOG_LEVEL").as_deref(),
+        Some("log.level")
+    );
+}
@@ -5,6 +5,7 @@
 //! 2. Save this file as `tests/config_layering.rs`.
 //! 3. Run `cargo test`.
 
+use clap::Parser;
 use magicblock_config::LifecycleMode;
 use magicblock_config::{consts, remote::RemoteCluster, MagicBlockParams};
 use std::env;
@@ -33,7 +34,7 @@ fn test_defaults_only() {
     let config = assemble_config_from_simulated_sources(argv);
 
     assert_eq!(config.remote, consts::DEFAULT_REMOTE.parse().unwrap());
-    assert_eq!(config.listen.0.to_string(), consts::DEFAULT_RPC_ADDR);
+    assert_eq!(config.listen[0].to_string(), consts::DEFAULT_RPC_ADDR);
     assert_eq!(config.validator.basefee, consts::DEFAULT_BASE_FEE);
     assert_eq!(
         config.validator.keypair,
@@ -55,7 +56,7 @@ fn test_toml_overrides_cli_defaults() {
     let config = assemble_config_from_simulated_sources(argv);
 
     // Values from TOML
-    assert_eq!(config.listen.0.to_string(), "0.0.0.0:9999");
+    assert_eq!(config.listen[0].to_string(), "0.0.0.0:9999");
     assert_eq!(config.remote, "mainnet".parse().unwrap());
     assert_eq!(config.validator.basefee, 5000);
     // Value from Default (not in TOML)
@@ -124,14 +125,7 @@ fn test_full_permutation_scenario() {
     let (_dir, config_path) = create_toml_config(toml_content);
 
     // Layer 3: CLI Arguments
-    let argv = vec![
-        "magic-block",
-        "--config",
-        config_path.to_str().unwrap(),
-        // This remote value will win as it's not set in ENV or TOML.
-        "--remote",
-        "mainnet",
-    ];
+    let argv = vec!["magic-block", "--config", config_path.to_str().unwrap()];
 
     let config = assemble_config_from_simulated_sources(argv);
 
@@ -140,15 +134,340 @@ fn test_full_permutation_scenario() {
 
     // Assert values based on the precedence: TOML > Env > CLI > Defaults
     // Highest precedence: TOML file
-    assert_eq!(config.listen.0.to_string(), "10.0.0.1:443");
+    assert_eq!(config.listen[0].to_string(), "10.0.0.1:443");
     assert_eq!(config.validator.basefee, 5000);
     // Second highest precedence: Environment variables
     assert_eq!(config.lifecycle, LifecycleMode::Offline);
-    // Third highest precedence: CLI arguments
-    assert_eq!(config.remote, "mainnet".parse().unwrap());
     // Lowest precedence: Default (keypair was never set anywhere else)
     assert_eq!(
         config.validator.keypair,
         consts::DEFAULT_VALIDATOR_KEYPAIR.parse().unwrap()
     );
 }
+
+#[test]
+fn test_cli_remote_wins_over_defaults() {
+    let (_dir, config_path) = create_toml_config("lifecycle = \"replica\"");
+    let argv = vec![
+        "magic-block",
+        "--config",
+        config_path.to_str().unwrap(),
+        "--remote",
+        "mainnet",
+    ];
+
+    let config = assemble_config_from_simulated_sources(argv);
+
+    assert_eq!(config.lifecycle, LifecycleMode::Replica);
+    assert_eq!(config.remote, "mainnet".parse().unwrap());
+}
+
+#[test]
+fn test_offline_lifecycle_rejects_remote_from_another_layer() {
+    let (_dir, config_path) = create_toml_config("lifecycle = \"offline\"");
+    let argv = vec![
+        "magic-block",
+        "--config",
+        config_path.to_str().unwrap(),
+        "--remote",
+        "mainnet",
+    ];
+
+    let err = MagicBlockParams::try_new(argv.into_iter().map(Into::into))
+        .expect_err("An offline validator must not be given a remote");
+    assert!(err.to_string().contains("`remote`"), "{err}");
+}
+
+#[test]
+fn test_set_overrides_file_only_keys_above_toml() {
+    let toml_content = r#"
+        [commit]
+        compute-unit-price = 5
+        [accounts-db]
+        max-snapshots = 8
+    "#;
+    let (_dir, config_path) = create_toml_config(toml_content);
+    let argv = vec![
+        "magic-block",
+        "--set",
+        "commit.compute-unit-price=2000000",
+        "--config",
+        config_path.to_str().unwrap(),
+        "--set",
+        "ledger.block-time=250ms",
+        "--set",
+        "commit.compute-unit-price=3000000",
+    ];
+
+    let config = assemble_config_from_simulated_sources(argv);
+
+    // The last `--set` of a key wins over the TOML file.
+    assert_eq!(config.commit.compute_unit_price, 3000000);
+    assert_eq!(config.ledger.block_time.as_millis(), 250);
+    // Keys without an override keep their TOML value.
+    assert_eq!(config.accounts_db.max_snapshots, 8);
+}
+
+#[test]
+fn test_env_overrides_set() {
+    env::set_var("MBV_LEDGER_RESET", "false");
+    let result = MagicBlockParams::try_new(
+        ["magic-block", "--set", "ledger.reset=true"]
+            .into_iter()
+            .map(Into::into),
+    );
+    env::remove_var("MBV_LEDGER_RESET");
+
+    assert!(!result.expect("Failed to assemble config").ledger.reset);
+}
+
+#[test]
+fn test_malformed_set_is_rejected() {
+    let result = MagicBlockParams::try_parse_from(["magic-block", "--set", "ledger.reset"]);
+    assert!(result.is_err());
+    let result = MagicBlockParams::try_parse_from(["magic-block", "--set", "ledger..reset=1"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_no_env_ignores_environment_fallbacks() {
+    env::set_var("MBV_METRICS", "127.0.0.1:9100");
+
+    let args = ["magic-block"];
+    let with_env = assemble_config_from_simulated_sources(args.to_vec());
+    let without_env = assemble_config_from_simulated_sources(vec!["magic-block", "--no-env"]);
+    let without_env_api =
+        MagicBlockParams::try_new_without_env(args.into_iter().map(Into::into)).unwrap();
+
+    env::remove_var("MBV_METRICS");
+
+    assert_eq!(
+        with_env.metrics.listen.unwrap().to_string(),
+        "127.0.0.1:9100"
+    );
+    assert!(!with_env.no_env);
+    for config in [without_env, without_env_api] {
+        assert!(config.metrics.listen.is_none());
+        assert!(config.no_env);
+    }
+}
+
+#[test]
+fn test_no_env_ignores_environment_layer() {
+    let print_reset = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+            .args(args)
+            .env("MBV_LEDGER_RESET", "false")
+            .output()
+            .expect("Failed to run the binary");
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        stdout
+            .lines()
+            .find(|line| line.starts_with("reset = "))
+            .map(str::to_owned)
+    };
+
+    assert_eq!(
+        print_reset(&["print-config"]).as_deref(),
+        Some("reset = false")
+    );
+    assert_eq!(
+        print_reset(&["--no-env", "print-config"]).as_deref(),
+        Some("reset = true")
+    );
+}
+
+#[test]
+fn test_no_ledger_reset_turns_off_default() {
+    let config = assemble_config_from_simulated_sources(vec!["magic-block"]);
+    assert!(config.ledger.reset);
+
+    let argv = vec!["magic-block", "--no-ledger-reset"];
+    let config = assemble_config_from_simulated_sources(argv);
+
+    assert!(!config.ledger.reset);
+}
+
+#[test]
+fn test_no_metrics_unsets_embedded_default() {
+    let builder =
+        || MagicBlockParams::builder().with_embedded_defaults("metrics = \"127.0.0.1:9100\"");
+    let config = builder().try_build(["magic-block".into()]).unwrap();
+    assert!(config.metrics.listen.is_some());
+
+    let config = builder()
+        .try_build(["magic-block", "--no-metrics"].map(Into::into))
+        .unwrap();
+
+    assert!(config.metrics.listen.is_none());
+}
+
+#[test]
+fn test_metrics_shorthand_keeps_rest_of_section() {
+    let builder = || {
+        MagicBlockParams::builder().with_embedded_defaults(
+            "[metrics]\nlisten = \"127.0.0.1:9100\"\npath = \"/prom\"\nlabels = { cluster = \"devnet\" }",
+        )
+    };
+    let (_dir, path) = create_toml_config("metrics = \"127.0.0.1:9200\"");
+    let config = builder()
+        .try_build(["magic-block", "--config", path.to_str().unwrap()].map(Into::into))
+        .unwrap();
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "127.0.0.1:9200");
+    assert_eq!(config.metrics.path, "/prom");
+    assert_eq!(config.metrics.labels["cluster"], "devnet");
+
+    let config = builder()
+        .try_build(["magic-block", "--metrics", "127.0.0.1:9300"].map(Into::into))
+        .unwrap();
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "127.0.0.1:9300");
+    assert_eq!(config.metrics.path, "/prom");
+}
+
+#[test]
+fn test_last_of_metrics_and_no_metrics_wins() {
+    let argv = vec!["magic-block", "--metrics", "127.0.0.1:9100", "--no-metrics"];
+    let config = assemble_config_from_simulated_sources(argv);
+    assert!(config.metrics.listen.is_none());
+
+    let argv = vec!["magic-block", "--no-metrics", "--metrics", "127.0.0.1:9100"];
+    let config = assemble_config_from_simulated_sources(argv);
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "127.0.0.1:9100");
+}
+
+#[test]
+fn test_disabled_sentinel_unsets_metrics() {
+    let builder =
+        || MagicBlockParams::builder().with_embedded_defaults("metrics = \"127.0.0.1:9100\"");
+    let (_dir, path) = create_toml_config("metrics = \"off\"");
+    let config = builder()
+        .try_build(["magic-block", "--config", path.to_str().unwrap()].map(Into::into))
+        .unwrap();
+    assert!(config.metrics.listen.is_none());
+
+    for value in ["disabled", "None"] {
+        let config = builder()
+            .try_build(["magic-block", "--metrics", value].map(Into::into))
+            .unwrap();
+        assert!(config.metrics.listen.is_none(), "{value}");
+    }
+
+    let (_dir, path) = create_toml_config("metrics = \"127.0.0.1:9100\"");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["--config", path.to_str().unwrap(), "print-config"])
+        .env("MBV_METRICS", "off")
+        .output()
+        .expect("Failed to run the binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!stdout.contains("127.0.0.1:9100"), "{stdout}");
+}
+
+#[test]
+fn test_durations_parse_the_same_everywhere() {
+    let argv = vec!["magic-block", "--block-time", "1s 500ms"];
+    let config = assemble_config_from_simulated_sources(argv);
+    assert_eq!(
+        config.ledger.block_time,
+        std::time::Duration::from_millis(1500)
+    );
+    assert_eq!(config.ledger.block_time.to_string(), "1s 500ms");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .arg("print-config")
+        .env("MBV_LEDGER_BLOCKTIME", "2s")
+        .output()
+        .expect("Failed to run the binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("block-time = \"2s\""), "{stdout}");
+
+    // A bare number is rejected for its missing unit.
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .arg("print-config")
+        .env("MBV_LEDGER_BLOCKTIME", "2")
+        .output()
+        .expect("Failed to run the binary");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("time unit needed"), "{stderr}");
+}
+
+#[test]
+fn test_compute_unit_price_from_cli_and_environment() {
+    let argv = vec!["magic-block", "--compute-unit-price", "2000000"];
+    let config = assemble_config_from_simulated_sources(argv);
+    assert_eq!(config.commit.compute_unit_price, 2000000);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["--strict-config", "print-config"])
+        .env("MBV_COMMIT_COMPUTE_UNIT_PRICE", "3000000")
+        .output()
+        .expect("Failed to run the binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("compute-unit-price = 3000000"), "{stdout}");
+}
+
+#[test]
+fn test_toml_overrides_compute_unit_price_argument() {
+    let (_dir, config_path) = create_toml_config("[commit]\ncompute-unit-price = 5");
+    let argv = vec![
+        "magic-block",
+        "--compute-unit-price",
+        "2000000",
+        "--config",
+        config_path.to_str().unwrap(),
+    ];
+
+    let config = assemble_config_from_simulated_sources(argv);
+
+    assert_eq!(config.commit.compute_unit_price, 5);
+}
+
+#[test]
+fn test_ledger_from_cli_arguments() {
+    let argv = vec![
+        "magic-block",
+        "--ledger-block-time",
+        "50ms",
+        "--ledger-reset=false",
+        "--blocks-per-partition",
+        "4096",
+    ];
+
+    let config = assemble_config_from_simulated_sources(argv);
+
+    assert_eq!(
+        config.ledger.block_time,
+        std::time::Duration::from_millis(50)
+    );
+    assert!(!config.ledger.reset);
+    assert_eq!(config.ledger.blocks_per_partition, 4096);
+
+    let argv = vec!["magic-block", "--no-ledger-reset", "--ledger-reset", "true"];
+    let config = assemble_config_from_simulated_sources(argv);
+    assert!(config.ledger.reset);
+}
+
+#[test]
+fn test_ledger_from_environment_and_toml() {
+    let (_dir, config_path) = create_toml_config("[ledger]\nblocks-per-partition = 2048");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["--strict-config", "--blocks-per-partition", "4096"])
+        .args(["--config", config_path.to_str().unwrap(), "print-config"])
+        .env("MBV_LEDGER_BLOCK_TIME", "1s")
+        .output()
+        .expect("Failed to run the binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("block-time = \"1s\""), "{stdout}");
+    // TOML overrides the CLI argument.
+    assert!(stdout.contains("blocks-per-partition = 2048"), "{stdout}");
+}
@@ -0,0 +1,44 @@
+use magicblock_config::types::Percent;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+struct Thresholds {
+    high_watermark: Percent,
+    multiplier: Percent<100, 500>,
+}
+
+#[test]
+fn test_percent_parses_percentages_and_ratios() {
+    let thresholds: Thresholds =
+        toml::from_str("high_watermark = \"75%\"\nmultiplier = 1.5").unwrap();
+    assert_eq!(thresholds.high_watermark.ratio(), 0.75);
+    assert_eq!(thresholds.multiplier.percent(), 150.0);
+
+    let written = toml::to_string(&thresholds).unwrap();
+    assert_eq!(written, "high_watermark = \"75%\"\nmultiplier = \"150%\"\n");
+
+    let percent: Percent = "0.07".parse().unwrap();
+    assert_eq!(percent.to_string(), "7%");
+    assert_eq!(" 12.5 % ".parse::<Percent>().unwrap().ratio(), 0.125);
+    assert_eq!("1".parse::<Percent>().unwrap().percent(), 100.0);
+}
+
+#[test]
+fn test_percent_is_bounds_checked() {
+    let err = "120%".parse::<Percent>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "120% is out of range, expected a percentage in 0%..=100%"
+    );
+    let err = toml::from_str::<Thresholds>("high_watermark = 0.5\nmultiplier = 0.5")
+        .err()
+        .expect("A multiplier below 100% should be rejected");
+    assert!(err.to_string().contains("50% is out of range"), "{err}");
+
+    let err = "lots".parse::<Percent>().unwrap_err();
+    assert!(
+        err.to_string().contains("invalid percentage `lots`"),
+        "{err}"
+    );
+    assert!("NaN%".parse::<Percent>().is_err());
+}
@@ -0,0 +1,115 @@
+//! Integration tests for the opt-in port availability pre-flight check.
+
+use magicblock_config::{types::BindAddress, ConfigError, MagicBlockParams};
+use std::net::TcpListener;
+
+fn try_load(args: Vec<&str>) -> Result<MagicBlockParams, ConfigError> {
+    MagicBlockParams::try_new(args.into_iter().map(Into::into))
+}
+
+#[test]
+fn test_check_ports_reports_address_in_use() {
+    let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = taken.local_addr().unwrap().to_string();
+    let config = try_load(vec![
+        "magic-block",
+        "--listen",
+        &address,
+        "--metrics",
+        "127.0.0.1:0",
+    ])
+    .expect("Ports are not checked without --preflight");
+
+    let errors = config.check_ports();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].key, "listen");
+    assert!(errors[0].message.contains(&address), "{}", errors[0]);
+    #[cfg(target_os = "linux")]
+    {
+        let owner = "in use by preflight-";
+        let pid = format!("(pid {})", std::process::id());
+        assert!(errors[0].message.contains(owner), "{}", errors[0]);
+        assert!(errors[0].message.contains(&pid), "{}", errors[0]);
+    }
+}
+
+#[test]
+fn test_preflight_flag_fails_the_load() {
+    let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = taken.local_addr().unwrap().to_string();
+
+    let err = try_load(vec!["magic-block", "--preflight", "--metrics", &address])
+        .expect_err("A taken metrics address should fail the pre-flight check");
+    let ConfigError::Invalid(report) = err else {
+        panic!("expected a report, got {err:?}");
+    };
+    assert_eq!(report.errors[0].key, "metrics.listen");
+
+    drop(taken);
+    let config = try_load(vec!["magic-block", "--preflight", "--metrics", &address])
+        .expect("A free metrics address should pass the pre-flight check");
+    assert!(config.preflight);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_socket_is_bound_with_permissions() {
+    use magicblock_config::types::BindAddress;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let address = format!("unix:{}?mode=0600", dir.path().join("rpc.sock").display());
+    let config = try_load(vec!["magic-block", "--preflight", "--listen", &address])
+        .expect("A free socket path should pass the pre-flight check");
+    let [BindAddress::Unix(socket)] = config.listen.as_slice() else {
+        panic!("expected a Unix socket, got {:?}", config.listen);
+    };
+    assert!(
+        !socket.path.exists(),
+        "The pre-flight check should clean up"
+    );
+
+    let listener = socket.bind().expect("Failed to bind the socket");
+    let mode = std::fs::metadata(&socket.path)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o7777, 0o600);
+    let errors = config.check_ports();
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0].message.contains("already in use"),
+        "{}",
+        errors[0]
+    );
+
+    // A socket file left behind by a previous run is replaced.
+    drop(listener);
+    assert!(config.check_ports().is_empty());
+    socket
+        .bind()
+        .expect("A stale socket file should be replaced");
+}
+
+#[test]
+fn test_bind_tcp_applies_v6_only() {
+    // Skip on hosts without IPv6.
+    if TcpListener::bind("[::]:0").is_err() {
+        return;
+    }
+    let bind = |v6_only| {
+        let address = BindAddress::Tcp {
+            address: "[::]:0".parse().unwrap(),
+            v6_only: Some(v6_only),
+        };
+        address.bind_tcp().unwrap()
+    };
+
+    let ipv6_only = bind(true);
+    let port = ipv6_only.local_addr().unwrap().port();
+    assert!(TcpListener::bind(("0.0.0.0", port)).is_ok());
+
+    let dual_stack = bind(false);
+    let port = dual_stack.local_addr().unwrap().port();
+    assert!(TcpListener::bind(("0.0.0.0", port)).is_err());
+}
@@ -0,0 +1,128 @@
+//! Integration tests for printing the effective configuration.
+
+use magicblock_config::{sources::ConfigFormat, MagicBlockParams};
+
+mod common;
+use common::create_config_file;
+
+#[test]
+fn test_printed_config_round_trips() {
+    let (_dir, path) = create_config_file(
+        "config.toml",
+        "lifecycle = \"replica\"\n[accounts-db]\nmax-snapshots = 7\n[ledger]\nblock-time = \"250ms\"",
+    );
+    let config = MagicBlockParams::try_new(
+        [
+            "magic-block",
+            "--config",
+            path.to_str().unwrap(),
+            "--basefee",
+            "42",
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .expect("Failed to load config");
+    let toml = config
+        .to_toml_exposing_secrets()
+        .expect("Failed to serialize config");
+
+    let (_dir, printed) = create_config_file("printed.toml", &toml);
+    let reloaded = MagicBlockParams::try_new(
+        ["magic-block", "--config", printed.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    )
+    .expect("Printed config should load");
+
+    assert_eq!(reloaded.lifecycle, config.lifecycle);
+    assert_eq!(reloaded.validator.basefee, 42);
+    assert_eq!(reloaded.accounts_db.max_snapshots, 7);
+    assert_eq!(reloaded.ledger.block_time, config.ledger.block_time);
+}
+
+#[test]
+fn test_print_config_subcommand_shows_winning_values() {
+    let (_dir, path) = create_config_file("config.toml", "[validator]\nbasefee = 7");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["print-config", "--config", path.to_str().unwrap()])
+        .env("MBV_VALIDATOR_BASEFEE", "9")
+        .output()
+        .expect("Failed to run the binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[validator]\nbasefee = 9\n"), "{stdout}");
+}
+
+#[test]
+fn test_formats_share_key_order() {
+    let config = MagicBlockParams::default();
+    let parse = |format| -> toml::Table {
+        let output = config.to_format(format).unwrap();
+        match format {
+            ConfigFormat::Toml => toml::from_str(&output).expect("Output should be TOML"),
+            ConfigFormat::Json => serde_json::from_str(&output).expect("Output should be JSON"),
+            ConfigFormat::Yaml => serde_yaml::from_str(&output).expect("Output should be YAML"),
+        }
+    };
+
+    let tables = [
+        parse(ConfigFormat::Toml),
+        parse(ConfigFormat::Json),
+        parse(ConfigFormat::Yaml),
+    ];
+
+    let keys: Vec<Vec<&String>> = tables.iter().map(|table| table.keys().collect()).collect();
+    assert_eq!(keys[0][..2], ["remote", "lifecycle"]);
+    assert_eq!(keys[0], keys[1]);
+    assert_eq!(keys[0], keys[2]);
+    assert_eq!(tables[1], tables[2]);
+}
+
+#[test]
+fn test_print_config_subcommand_emits_json() {
+    let (_dir, path) = create_config_file("config.toml", "[validator]\nbasefee = 7");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["print-config", "--format", "json", "--config"])
+        .arg(&path)
+        .output()
+        .expect("Failed to run the binary");
+
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be JSON");
+    assert_eq!(json["validator"]["basefee"], 7);
+    assert!(json.get("config").is_none());
+}
+
+#[test]
+fn test_printed_sources_annotate_every_key() {
+    let (_dir, path) = create_config_file("config.toml", "\n[validator]\nbasefee = 7\n");
+    let config = MagicBlockParams::try_new(
+        [
+            "magic-block",
+            "--config",
+            path.to_str().unwrap(),
+            "--lifecycle",
+            "replica",
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .expect("Failed to load config");
+
+    let toml = config
+        .to_toml_with_sources()
+        .expect("Failed to serialize config");
+
+    let lines: Vec<_> = toml.lines().collect();
+    let basefee = format!("basefee = 7 # from {}:3", path.display());
+    assert!(lines.contains(&basefee.as_str()), "{toml}");
+    assert!(lines.contains(&"lifecycle = \"replica\" # from command-line arguments"));
+    assert!(lines.contains(&"block-time = \"400ms\" # default"));
+    // The comments don't change the document.
+    let annotated: toml::Table = toml.parse().unwrap();
+    let plain: toml::Table = config.to_toml().unwrap().parse().unwrap();
+    assert_eq!(annotated, plain);
+}
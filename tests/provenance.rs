@@ -0,0 +1,73 @@
+//! Integration tests for tracking where every configuration value was set.
+
+use magicblock_config::{provenance::Source, MagicBlockParams};
+use tempfile::tempdir;
+
+#[test]
+fn test_provenance_locates_every_layer() {
+    let dir = tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        "# Fees\n[validator]\nbasefee = 7\n\n[accounts-db]\nmax-snapshots = 3\n",
+    )
+    .unwrap();
+    let env_file = dir.path().join("node.env");
+    std::fs::write(
+        &env_file,
+        "# Ledger\nMBV_LEDGER_RESET=false\nMBV_LEDGER_BLOCKS_PER_PARTITION=50000\n",
+    )
+    .unwrap();
+    // No other test of this binary reads the variable.
+    std::env::set_var("MBV_CHAINLINK_AUTO_AIRDROP_LAMPORTS", "42");
+    let args = [
+        "magic-block",
+        "--listen",
+        "0.0.0.0:9000",
+        "--config",
+        config.to_str().unwrap(),
+        "--env-file",
+        env_file.to_str().unwrap(),
+    ];
+
+    let params = MagicBlockParams::try_new(args.into_iter().map(Into::into)).unwrap();
+    let provenance = params.provenance();
+    std::env::remove_var("MBV_CHAINLINK_AUTO_AIRDROP_LAMPORTS");
+
+    let config = config.display().to_string();
+    let basefee = &provenance["validator.basefee"];
+    assert_eq!(basefee.layer, "TOML file");
+    assert_eq!(basefee.file.as_deref(), Some(config.as_str()));
+    assert_eq!(basefee.line, Some(3));
+    assert_eq!(provenance["accounts-db.max-snapshots"].line, Some(6));
+    assert_eq!(basefee.to_string(), format!("TOML file `{config}:3`"));
+
+    let reset = &provenance["ledger.reset"];
+    assert_eq!(reset.variable.as_deref(), Some("MBV_LEDGER_RESET"));
+    assert_eq!(reset.line, Some(2));
+    let partition = &provenance["ledger.blocks-per-partition"];
+    assert_eq!(
+        partition.variable.as_deref(),
+        Some("MBV_LEDGER_BLOCKS_PER_PARTITION")
+    );
+    assert_eq!(partition.line, Some(3));
+
+    let airdrop = &provenance["chainlink.auto-airdrop-lamports"];
+    assert_eq!(
+        airdrop.variable.as_deref(),
+        Some("MBV_CHAINLINK_AUTO_AIRDROP_LAMPORTS")
+    );
+    assert_eq!(airdrop.file, None);
+
+    assert_eq!(
+        provenance["listen"],
+        Source {
+            layer: "command-line arguments".into(),
+            file: None,
+            line: None,
+            variable: None,
+        }
+    );
+    assert_eq!(provenance["ledger.block-time"].layer, "built-in defaults");
+    assert!(!provenance.contains_key("config-timeout"));
+}
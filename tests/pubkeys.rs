@@ -0,0 +1,79 @@
+use clap::Parser;
+use magicblock_config::types::{SerdePubkey, SerdePubkeyList, SerdePubkeySet};
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+
+#[derive(Deserialize, Serialize)]
+struct Lists {
+    list: SerdePubkeyList,
+    set: SerdePubkeySet,
+}
+
+#[derive(Parser)]
+struct Cli {
+    #[arg(long, value_delimiter = ',')]
+    allow: Vec<SerdePubkey>,
+    #[arg(long, default_value = "")]
+    deny: SerdePubkeySet,
+}
+
+fn keys() -> [Pubkey; 3] {
+    [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ]
+}
+
+#[test]
+fn test_pubkey_lists_parse_strings_and_arrays() {
+    let [a, b, c] = keys();
+    let toml = format!("list = \"{c}, {a},{c}\"\nset = [\"{c}\", \"{a}\", \"{c}\"]");
+    let lists: Lists = toml::from_str(&toml).unwrap();
+    assert_eq!(
+        *lists.list,
+        [c, a],
+        "Lists keep the first occurrence in order"
+    );
+    assert_eq!(lists.set.iter().copied().collect::<Vec<_>>(), {
+        let mut sorted = vec![a, c];
+        sorted.sort();
+        sorted
+    });
+    assert!(!lists.set.contains(&b));
+
+    let written = toml::to_string(&lists).unwrap();
+    assert!(
+        written.contains(&format!("list = [\"{c}\", \"{a}\"]")),
+        "{written}"
+    );
+    let reparsed: Lists = toml::from_str(&written).unwrap();
+    assert_eq!(reparsed.list, lists.list);
+    assert_eq!(reparsed.set, lists.set);
+
+    assert_eq!(lists.list.to_string(), format!("{c},{a}"));
+    assert_eq!(
+        lists.list.to_string().parse::<SerdePubkeyList>().unwrap(),
+        lists.list
+    );
+
+    let err = toml::from_str::<Lists>("list = \"not-a-key\"\nset = []")
+        .err()
+        .expect("Invalid keys should be rejected");
+    assert!(err.to_string().contains("Invalid"), "{err}");
+}
+
+#[test]
+fn test_pubkey_lists_from_command_line() {
+    let [a, b, c] = keys();
+    let cli = Cli::parse_from([
+        "magic-block".to_owned(),
+        format!("--allow={a},{b}"),
+        format!("--allow={a}"),
+        format!("--deny={c},{b}"),
+    ]);
+    let allow: SerdePubkeyList = cli.allow.into_iter().collect();
+    assert_eq!(*allow, [a, b]);
+    assert_eq!(cli.deny.len(), 2);
+    assert!(cli.deny.contains(&b) && cli.deny.contains(&c));
+}
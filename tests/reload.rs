@@ -0,0 +1,400 @@
+//! Integration tests for reloading the configuration while running.
+
+use magicblock_config::{
+    config::LogLevel,
+    diff::KeyChange,
+    reload::{self, ConfigEvent},
+    ConfigError, ConfigHandle, MagicBlockParams,
+};
+use std::{path::Path, sync::mpsc, time::Duration};
+use tempfile::tempdir;
+
+fn handle(path: &Path) -> ConfigHandle {
+    let args = ["magic-block", "--config", path.to_str().unwrap()];
+    ConfigHandle::new(MagicBlockParams::builder, args.map(Into::into))
+        .expect("Failed to load config")
+}
+
+#[test]
+fn test_reload_applies_changed_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let handle = handle(&path);
+    let before = handle.current();
+
+    std::fs::write(&path, "[validator]\nbasefee = 20").unwrap();
+    let outcome = handle.reload().expect("Reload should succeed");
+
+    assert_eq!(outcome.applied.len(), 1);
+    assert!(outcome.restart_required.is_empty());
+    assert_eq!(handle.current().validator.basefee, 20);
+    // Earlier readers keep the configuration they started with.
+    assert_eq!(before.validator.basefee, 10);
+}
+
+#[test]
+fn test_invalid_reload_keeps_active_configuration() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let handle = handle(&path);
+
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 100000001").unwrap();
+    let result = handle.reload();
+
+    assert!(matches!(result, Err(ConfigError::Invalid(_))));
+    assert_eq!(handle.current().validator.basefee, 10);
+}
+
+#[test]
+fn test_rejected_reload_is_sent_with_generation() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let handle = handle(&path);
+    let subscription = handle.subscribe();
+
+    std::fs::write(&path, "[validator]\nbasefee = 20").unwrap();
+    assert_eq!(handle.reload().unwrap().generation, 1);
+    // Reloading unchanged sources keeps the generation.
+    assert_eq!(handle.reload().unwrap().generation, 1);
+    std::fs::write(&path, "[validator\nbasefee = 30").unwrap();
+    assert!(handle.reload().is_err());
+
+    let events: Vec<_> = subscription.try_iter().collect();
+    assert_eq!(events.len(), 2);
+    let ConfigEvent::Rejected(rejected) = &events[1] else {
+        panic!("Rejection should be sent");
+    };
+    assert_eq!(rejected.generation, 1);
+    assert!(!rejected.report.errors.is_empty());
+    assert_eq!(handle.snapshot().0, 1);
+    assert_eq!(handle.current().validator.basefee, 20);
+}
+
+#[test]
+fn test_reload_skips_preflight_port_checks() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let listen = listener.local_addr().unwrap();
+    drop(listener);
+    std::fs::write(&path, format!("listen = \"{listen}\"")).unwrap();
+    let args = [
+        "magic-block",
+        "--preflight",
+        "--config",
+        path.to_str().unwrap(),
+    ];
+    let handle = ConfigHandle::new(MagicBlockParams::builder, args.map(Into::into)).unwrap();
+
+    // The running application now holds the port.
+    let _listener = std::net::TcpListener::bind(listen).unwrap();
+
+    assert!(handle.reload().is_ok());
+}
+
+#[test]
+fn test_restart_required_changes_keep_old_values() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "listen = \"127.0.0.1:8000\"\n[commit]\ncompute-unit-price = 5",
+    )
+    .unwrap();
+    let handle = handle(&path);
+
+    std::fs::write(
+        &path,
+        "listen = \"127.0.0.1:9000\"\n[commit]\ncompute-unit-price = 7\n[log]\nlevel = \"debug\"",
+    )
+    .unwrap();
+    let outcome = handle.reload().expect("Reload should succeed");
+
+    let keys = |changes: &[KeyChange]| -> Vec<String> {
+        changes
+            .iter()
+            .map(|change| change.key().to_owned())
+            .collect()
+    };
+    assert_eq!(
+        keys(&outcome.applied),
+        ["commit.compute-unit-price", "log.level"]
+    );
+    assert_eq!(keys(&outcome.restart_required), ["listen"]);
+    let current = handle.current();
+    assert_eq!(current.commit.compute_unit_price, 7);
+    assert_eq!(current.log.level, LogLevel::Debug);
+    assert_eq!(current.listen[0].to_string(), "127.0.0.1:8000");
+    assert!(outcome
+        .to_string()
+        .contains("restart required:\n  ~ listen"));
+}
+
+#[test]
+fn test_reloadable_keys() {
+    assert!(reload::is_reloadable("commit.compute-unit-price"));
+    assert!(reload::is_reloadable("log.filters.magicblock_ledger"));
+    assert!(!reload::is_reloadable("logger"));
+    assert!(!reload::is_reloadable("validator.keypair"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sighup_reloads_configuration() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let handle = handle(&path);
+    let (reloaded, reloads) = mpsc::channel();
+    handle
+        .reload_on_sighup(move |result| {
+            let _ = reloaded.send(result.map_err(|error| error.to_string()));
+        })
+        .expect("Failed to install handler");
+    assert!(handle.reload_on_sighup(|_| {}).is_err());
+
+    std::fs::write(&path, "[validator]\nbasefee = 20").unwrap();
+    // SAFETY: signals this process, whose handler was installed above.
+    unsafe { libc::raise(libc::SIGHUP) };
+    let outcome = reloads
+        .recv_timeout(Duration::from_secs(5))
+        .expect("SIGHUP should reload")
+        .expect("Reload should succeed");
+    assert_eq!(outcome.applied.len(), 1);
+    assert_eq!(handle.current().validator.basefee, 20);
+
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 100000001").unwrap();
+    // SAFETY: as above.
+    unsafe { libc::raise(libc::SIGHUP) };
+    let rejection = reloads
+        .recv_timeout(Duration::from_secs(5))
+        .expect("SIGHUP should reload")
+        .expect_err("Invalid reload should be rejected");
+    assert!(rejection.contains("compute-unit-price"), "{rejection}");
+    assert_eq!(handle.current().validator.basefee, 20);
+}
+
+#[test]
+fn test_subscribers_receive_applied_changes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 5").unwrap();
+    let handle = handle(&path);
+    let subscription = handle.subscribe();
+    let dropped = handle.subscribe();
+    drop(dropped);
+
+    std::fs::write(
+        &path,
+        "listen = \"127.0.0.1:9000\"\n[commit]\ncompute-unit-price = 5",
+    )
+    .unwrap();
+    handle.reload().expect("Reload should succeed");
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 7").unwrap();
+    handle.reload().expect("Reload should succeed");
+
+    // Changes requiring a restart aren't applied, so aren't sent.
+    let Ok(ConfigEvent::Changed(change)) = subscription.try_recv() else {
+        panic!("Change should be sent");
+    };
+    assert_eq!(change.generation, 1);
+    assert!(change.changed("commit.compute-unit-price"));
+    assert!(change.changed("commit"));
+    assert!(!change.changed("listen"));
+    assert_eq!(change.previous.commit.compute_unit_price, 5);
+    assert_eq!(change.current.commit.compute_unit_price, 7);
+    assert!(subscription.try_recv().is_err());
+}
+
+#[test]
+fn test_watch_returns_only_the_latest_configuration() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 5").unwrap();
+    let handle = handle(&path);
+    let mut watch = handle.watch();
+    assert!(!watch.has_changed());
+    assert!(watch.changed_timeout(Duration::from_millis(10)).is_none());
+
+    for price in [6, 7] {
+        std::fs::write(&path, format!("[commit]\ncompute-unit-price = {price}")).unwrap();
+        handle.reload().expect("Reload should succeed");
+    }
+    assert!(watch.has_changed());
+    assert_eq!(watch.changed().commit.compute_unit_price, 7);
+    assert!(!watch.has_changed());
+
+    let waiter = std::thread::spawn(move || watch.changed().commit.compute_unit_price);
+    std::thread::sleep(Duration::from_millis(50));
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 8").unwrap();
+    handle.reload().expect("Reload should succeed");
+    assert_eq!(waiter.join().unwrap(), 8);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_async_watch_follows_applied_reloads() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[commit]\ncompute-unit-price = 5").unwrap();
+    let handle = handle(&path);
+    let mut watch = handle.watch_async();
+    assert!(!watch.has_changed().unwrap());
+    assert_eq!(watch.borrow().commit.compute_unit_price, 5);
+
+    for price in [6, 7] {
+        std::fs::write(&path, format!("[commit]\ncompute-unit-price = {price}")).unwrap();
+        handle.reload().expect("Reload should succeed");
+    }
+    assert!(watch.has_changed().unwrap());
+    assert_eq!(watch.borrow_and_update().commit.compute_unit_price, 7);
+    assert!(!watch.has_changed().unwrap());
+
+    // Restart-required changes leave the active configuration as it is.
+    std::fs::write(
+        &path,
+        "listen = \"127.0.0.1:9000\"\n[commit]\ncompute-unit-price = 7",
+    )
+    .unwrap();
+    handle.reload().expect("Reload should succeed");
+    assert!(!watch.has_changed().unwrap());
+}
+
+#[test]
+fn test_patches_apply_and_survive_reloads() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let handle = handle(&path);
+
+    let patch = [("commit.compute-unit-price".into(), 5000.into())].into();
+    let outcome = handle.patch(patch).expect("Patch should apply");
+
+    assert_eq!(outcome.generation, 1);
+    assert_eq!(handle.current().commit.compute_unit_price, 5000);
+    std::fs::write(&path, "[validator]\nbasefee = 20").unwrap();
+    handle.reload().expect("Reload should succeed");
+    assert_eq!(handle.current().validator.basefee, 20);
+    assert_eq!(handle.current().commit.compute_unit_price, 5000);
+}
+
+#[test]
+fn test_patch_of_restart_only_key_is_rejected() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let handle = handle(&path);
+
+    let patch = [
+        ("listen".into(), "127.0.0.1:9000".into()),
+        ("validator.basefee".into(), 30.into()),
+    ]
+    .into();
+    let Err(ConfigError::Invalid(report)) = handle.patch(patch) else {
+        panic!("Patch should be rejected");
+    };
+
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].key, "listen");
+    assert_eq!(handle.current().validator.basefee, 10);
+    assert_eq!(handle.generation(), 0);
+}
+
+#[test]
+fn test_refresh_follows_changed_sources() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "config-refresh-interval = \"50ms\"").unwrap();
+    let handle = handle(&path);
+    let (sender, results) = mpsc::channel();
+
+    let started = handle
+        .refresh_periodically(move |result| {
+            let _ = sender.send(result.map(|outcome| outcome.generation));
+        })
+        .expect("Failed to start refreshing");
+    assert!(started);
+
+    // Ends after 5 seconds without a result, rather than hanging the test.
+    let mut results = std::iter::from_fn(|| results.recv_timeout(Duration::from_secs(5)).ok());
+    std::fs::write(&path, "[validator\nbasefee = 20").unwrap();
+    let failed = results.find_map(|result| result.err());
+    assert!(
+        matches!(failed, Some(ConfigError::Figment(_))),
+        "{failed:?}"
+    );
+    std::fs::write(
+        &path,
+        "config-refresh-interval = \"50ms\"\n[validator]\nbasefee = 20",
+    )
+    .unwrap();
+    // A refresh may read the file half-written, and find nothing changed.
+    let recovered = results.any(|result| matches!(result, Ok(1)));
+    assert!(recovered, "Refresh should recover");
+    assert_eq!(handle.current().validator.basefee, 20);
+}
+
+#[test]
+fn test_refresh_needs_an_interval() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "").unwrap();
+
+    let started = handle(&path).refresh_periodically(|_| {}).unwrap();
+
+    assert!(!started);
+}
+
+#[test]
+fn test_reload_keeps_the_document_read_from_stdin() {
+    // Stdin can only be fed to a process of its own, so the test runs
+    // itself again with the document on stdin.
+    if std::env::var_os("RELOAD_FROM_STDIN").is_some() {
+        let args = ["magic-block", "--config", "-"];
+        let handle = ConfigHandle::new(MagicBlockParams::builder, args.map(Into::into))
+            .expect("Failed to load config");
+        let outcome = handle.reload().expect("Reload should succeed");
+        assert!(outcome.is_unchanged(), "{outcome}");
+        assert_eq!(handle.current().validator.basefee, 5000);
+        let provenance = handle.provenance().expect("Provenance should load");
+        let basefee = provenance
+            .iter()
+            .find(|explanation| explanation.key == "validator.basefee")
+            .unwrap();
+        let value = basefee.value.as_ref().and_then(|value| value.to_i128());
+        assert_eq!(value, Some(5000));
+        return;
+    }
+
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "test_reload_keeps_the_document_read_from_stdin",
+            "--exact",
+            "--nocapture",
+        ])
+        .env("RELOAD_FROM_STDIN", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run the test binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"[validator]\nbasefee = 5000\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("1 passed"),
+        "{output:?}"
+    );
+}
@@ -0,0 +1,117 @@
+//! Integration tests for the URLs derived from remote definitions.
+
+use magicblock_config::remote::{Remote, RemoteCluster};
+
+fn remote(s: &str) -> Remote {
+    let cluster: RemoteCluster = s.parse().expect("URL should parse");
+    cluster.remotes()[0].clone()
+}
+
+#[test]
+fn test_unified_remote_derives_websocket_url() {
+    let local = remote("http://127.0.0.1:8899");
+    assert_eq!(local.http_url().unwrap().as_str(), "http://127.0.0.1:8899/");
+    assert_eq!(local.ws_url().unwrap().as_str(), "ws://127.0.0.1:8900/");
+
+    let mainnet = remote("mainnet");
+    assert_eq!(
+        mainnet.ws_url().unwrap().as_str(),
+        "wss://api.mainnet-beta.solana.com/"
+    );
+
+    let ws = remote("wss://rpc.example.com:8900/path?api-key=k");
+    assert_eq!(
+        ws.http_url().unwrap().as_str(),
+        "https://rpc.example.com:8900/path?api-key=k"
+    );
+    assert_eq!(
+        ws.ws_url().unwrap().as_str(),
+        "wss://rpc.example.com:8900/path?api-key=k"
+    );
+
+    let provider = remote("https://rpc.example.com:8443");
+    assert_eq!(
+        provider.ws_url().unwrap().as_str(),
+        "wss://rpc.example.com:8443/"
+    );
+
+    let ws = remote("ws://localhost:8900");
+    assert_eq!(ws.http_url().unwrap().as_str(), "http://localhost:8899/");
+}
+
+#[test]
+fn test_port_is_kept_at_the_end_of_the_range() {
+    let local = remote("http://127.0.0.1:65535");
+    assert_eq!(local.ws_url().unwrap().as_str(), "ws://127.0.0.1:65535/");
+    let local = remote("ws://[::1]:0");
+    assert_eq!(local.http_url().unwrap().as_str(), "http://[::1]:0/");
+}
+
+#[test]
+fn test_disjointed_remote_keeps_its_urls() {
+    let remote: Remote = toml::from_str(
+        "http = \"https://rpc.example.com\"\nws = \"wss://stream.example.com:9000\"",
+    )
+    .unwrap();
+    assert_eq!(
+        remote.http_url().unwrap().as_str(),
+        "https://rpc.example.com/"
+    );
+    assert_eq!(
+        remote.ws_url().unwrap().as_str(),
+        "wss://stream.example.com:9000/"
+    );
+}
+
+#[test]
+fn test_grpc_remote_streams_without_json_rpc() {
+    let remote: Remote = toml::from_str(
+        r#"
+endpoint = "https://grpc.example.com:10000"
+x-token = "t0ken"
+filters = { owners = ["TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"], transactions = true }
+"#,
+    )
+    .unwrap();
+    assert!(remote.is_grpc());
+    assert_eq!(remote.http_url(), None);
+    assert_eq!(remote.ws_url(), None);
+    let Remote::Grpc {
+        endpoint, filters, ..
+    } = &remote
+    else {
+        panic!("A table with `endpoint` should be a gRPC remote");
+    };
+    assert_eq!(endpoint.url.as_str(), "https://grpc.example.com:10000/");
+    let filters = filters.as_deref().unwrap();
+    assert!(filters.accounts.is_empty());
+    assert_eq!(filters.owners.len(), 1);
+    assert!(filters.transactions);
+
+    let cluster: RemoteCluster = toml::from_str::<toml::Table>(
+        "remote = [{ endpoint = \"https://grpc.example.com\" }, \"https://rpc.example.com\"]",
+    )
+    .unwrap()["remote"]
+        .clone()
+        .try_into()
+        .unwrap();
+    assert_eq!(cluster.primary(), cluster.remotes().get(1));
+    assert_eq!(cluster.fallbacks().count(), 0);
+    assert_eq!(cluster.grpc().count(), 1);
+}
+
+#[test]
+fn test_remote_urls_serialize_as_written() {
+    let cluster: RemoteCluster =
+        "dev, https://api.mainnet-beta.solana.com, https://rpc.example.com"
+            .parse()
+            .unwrap();
+    assert_eq!(
+        serde_json::to_value(&cluster).unwrap(),
+        serde_json::json!(["dev", "mainnet", "https://rpc.example.com/"])
+    );
+
+    let helius = remote("helius");
+    assert_eq!(serde_json::to_value(&helius).unwrap(), "helius");
+    assert_eq!(remote("localhost"), remote("http://127.0.0.1:8899"));
+}
@@ -0,0 +1,189 @@
+//! Integration tests for reporting every configuration problem at once.
+
+use magicblock_config::{ConfigError, ConfigReport, MagicBlockParams};
+
+mod common;
+use common::create_config_file;
+
+fn load_report(content: &str) -> ConfigReport {
+    let (_dir, path) = create_config_file("config.toml", content);
+    let result = MagicBlockParams::try_new(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    );
+    match result {
+        Err(ConfigError::Invalid(report)) => report,
+        other => panic!("expected an invalid configuration, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_all_extraction_errors_are_collected() {
+    let report = load_report(
+        r#"
+listen = "not an address"
+[validator]
+basefee = "a lot"
+[accounts-db]
+index-size = 1
+[ledger]
+block-time = "0s"
+"#,
+    );
+    // Extraction errors come first, followed by the semantic ones.
+    let keys: Vec<_> = report.errors.iter().map(|e| e.key.as_str()).collect();
+
+    assert_eq!(
+        keys,
+        [
+            "accounts-db.index-size",
+            "listen",
+            "validator.basefee",
+            "ledger.block-time"
+        ]
+    );
+    assert!(report.errors[0].message.contains("config.toml"), "{report}");
+}
+
+#[test]
+fn test_missing_field_is_reported_with_other_errors() {
+    let report = load_report(
+        "[chain-operation]\ncountry-code = \"US\"\n[accounts-db]\nsnapshot-frequency = 0",
+    );
+    let keys: Vec<_> = report.errors.iter().map(|e| e.key.as_str()).collect();
+
+    assert_eq!(keys, ["chain-operation", "accounts-db.snapshot-frequency"]);
+    assert!(report.errors[0].message.contains("fqdn"), "{report}");
+}
+
+#[test]
+fn test_report_includes_warnings() {
+    let report = load_report("[validator]\nbase-fee = 5\n[ledger]\nblocks-per-partition = 0");
+
+    assert!(!report.is_ok());
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.to_string().contains("warning: `validator.base-fee`"));
+}
+
+#[test]
+fn test_validate_sources_reports_without_failing() {
+    let (_dir, path) = create_config_file("config.toml", "[validator]\nbase-fee = 5");
+    let args = ["magic-block", "--config", path.to_str().unwrap()];
+
+    let report = MagicBlockParams::validate_sources(args.into_iter().map(Into::into));
+    assert!(report.is_ok(), "{report}");
+    assert_eq!(report.warnings.len(), 1);
+}
+
+#[test]
+fn test_validate_subcommand_exit_status() {
+    let validate = |content: &str| {
+        let (_dir, path) = create_config_file("config.toml", content);
+        std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+            .args(["validate", "--config", path.to_str().unwrap()])
+            .output()
+            .expect("Failed to run the binary")
+    };
+
+    let output = validate("[validator]\nbasefee = 5");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "Configuration is valid"
+    );
+
+    let output = validate("[ledger]\nblocks-per-partition = 0");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("`ledger.blocks-per-partition`: must be greater than 0"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_extraction_errors_name_value_and_line() {
+    let report = load_report("\nlisten = \"not an address\"\n[validator]\nbasefee = \"a lot\"\n");
+
+    let listen = &report.errors[0];
+    assert_eq!(listen.key, "listen");
+    assert!(
+        listen.message.contains(r#"found "not an address""#),
+        "{listen}"
+    );
+    assert!(listen.message.contains("config.toml:2`"), "{listen}");
+    let basefee = &report.errors[1];
+    assert!(
+        basefee.message.contains("invalid amount `a lot`"),
+        "{basefee}"
+    );
+    assert!(basefee.message.contains(r#"found "a lot""#), "{basefee}");
+    assert!(basefee.message.contains("config.toml:4`"), "{basefee}");
+}
+
+#[test]
+fn test_extraction_errors_name_environment_variable() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .arg("validate")
+        .env("MBV_ACCOUNTS_DB_MAX_SNAPSHOTS", "many")
+        .output()
+        .expect("Failed to run the binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report = format!("{stdout}{stderr}");
+    assert!(
+        report.contains("in environment variable `MBV_ACCOUNTS_DB_MAX_SNAPSHOTS`"),
+        "{report}"
+    );
+}
+
+#[test]
+fn test_extraction_errors_redact_secret_values() {
+    let report = load_report("[admin]\nlisten = \"127.0.0.1:9900\"\ntoken = 123456789\n");
+
+    let token = &report.errors[0];
+    assert_eq!(token.key, "admin.token");
+    assert!(
+        token.message.contains("found signed int ***REDACTED***"),
+        "{token}"
+    );
+    assert!(!token.message.contains("123456789"), "{token}");
+}
+
+#[test]
+fn test_remote_errors_name_the_field() {
+    let report = load_report("remote = { http = \"https://a.example\", ws = 5 }\n");
+    assert_eq!(report.errors[0].key, "remote.ws", "{report}");
+
+    let report = load_report(
+        "[remotes.helius]\nurl = \"https://rpc.example.com\"\ntoken = 5\n[remote]\ndefault = [\"mainnet\", { endpoint = \"https://grpc.example.com\", health = {} }]\n",
+    );
+    let keys: Vec<_> = report.errors.iter().map(|e| e.key.as_str()).collect();
+    assert_eq!(
+        keys,
+        ["remote.default.1", "remotes.helius.token"],
+        "{report}"
+    );
+    assert!(
+        report.errors[0]
+            .message
+            .contains("`health` doesn't apply to a gRPC `endpoint`"),
+        "{report}"
+    );
+    assert!(
+        report.errors[1]
+            .message
+            .contains("found signed int ***REDACTED***"),
+        "{report}"
+    );
+
+    let report = load_report("[remote]\nhttp = \"https://a.example\"\n");
+    assert_eq!(report.errors[0].key, "remote", "{report}");
+    assert!(
+        report.errors[0].message.contains("`http` requires `ws`"),
+        "{report}"
+    );
+}
@@ -0,0 +1,131 @@
+//! Integration tests for the JSON Schema of the configuration.
+#![cfg(feature = "schema")]
+
+use magicblock_config::{secrets, MagicBlockParams};
+use serde_json::Value;
+
+fn schema() -> Value {
+    serde_json::to_value(MagicBlockParams::json_schema()).expect("Failed to serialize schema")
+}
+
+#[test]
+fn test_schema_covers_every_section() {
+    let schema = schema();
+    let properties = schema["properties"].as_object().unwrap();
+
+    for key in [
+        "remote",
+        "lifecycle",
+        "listen",
+        "validator",
+        "commit",
+        "accounts-db",
+        "ledger",
+        "chainlink",
+        "chain-operation",
+    ] {
+        assert!(properties.contains_key(key), "missing {key}");
+    }
+    // Loader flags that files can't set are left out.
+    assert!(!properties.contains_key("strict-config"));
+    assert!(!properties.contains_key("command"));
+}
+
+#[test]
+fn test_schema_includes_enums_ranges_and_defaults() {
+    let schema = schema();
+    let definitions = &schema["definitions"];
+
+    assert_eq!(
+        schema["properties"]["ledger"]["default"]["block-time"],
+        "400ms"
+    );
+    assert_eq!(
+        definitions["LifecycleMode"]["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["enum"][0].as_str().unwrap())
+            .collect::<Vec<_>>(),
+        ["ephemeral", "replica", "offline", "programs-replica"]
+    );
+    let definition = |reference: &Value| {
+        &definitions[reference["$ref"]
+            .as_str()
+            .unwrap()
+            .trim_start_matches("#/definitions/")]
+    };
+    let basefee = definition(&definitions["ValidatorConfig"]["properties"]["basefee"]["allOf"][0]);
+    let bounds = definition(&basefee["anyOf"][0]);
+    assert_eq!(bounds["maximum"], 1e9);
+    assert_eq!(basefee["anyOf"][1]["type"], "string");
+}
+
+#[test]
+fn test_schema_subcommand_prints_json() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .arg("schema")
+        .output()
+        .expect("Failed to run the binary");
+
+    assert!(output.status.success());
+    let printed: Value = serde_json::from_slice(&output.stdout).expect("Schema should be JSON");
+    assert_eq!(printed, schema());
+}
+
+/// Collects the dotted key of every secret reference in `schema`, set at
+/// `key`, with `*` for map entries and list items.
+fn secret_keys(
+    definitions: &Value,
+    schema: &Value,
+    key: &str,
+    seen: &mut Vec<String>,
+) -> Vec<String> {
+    let join = |name: &str| match key {
+        "" => name.to_owned(),
+        key => format!("{key}.{name}"),
+    };
+    let mut keys = Vec::new();
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/definitions/").to_owned();
+        if name == "SecretRef" {
+            keys.push(key.to_owned());
+        } else if !seen.contains(&name) {
+            seen.push(name.clone());
+            keys.extend(secret_keys(definitions, &definitions[&name], key, seen));
+            seen.pop();
+        }
+    }
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        for schema in schema[combinator].as_array().into_iter().flatten() {
+            keys.extend(secret_keys(definitions, schema, key, seen));
+        }
+    }
+    for (name, schema) in schema["properties"].as_object().into_iter().flatten() {
+        keys.extend(secret_keys(definitions, schema, &join(name), seen));
+    }
+    for nested in ["additionalProperties", "items"] {
+        if schema[nested].is_object() {
+            keys.extend(secret_keys(definitions, &schema[nested], &join("*"), seen));
+        }
+    }
+    keys
+}
+
+#[test]
+fn test_every_secret_field_is_redacted() {
+    let schema = schema();
+    let keys = secret_keys(&schema["definitions"], &schema, "", &mut Vec::new());
+
+    assert!(
+        keys.iter().any(|key| key == "tracing.headers.*"),
+        "{keys:?}"
+    );
+    assert!(
+        keys.iter().any(|key| key == "remotes.*.x-token"),
+        "{keys:?}"
+    );
+    for key in keys {
+        assert!(secrets::is_secret_key(&key), "`{key}` is not redacted");
+    }
+}
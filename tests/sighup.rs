@@ -0,0 +1,53 @@
+//! Integration tests for failing to install the `SIGHUP` handler, in a
+//! process of their own as the handler is process-wide.
+#![cfg(unix)]
+
+use magicblock_config::{ConfigHandle, MagicBlockParams};
+use std::io::ErrorKind;
+use tempfile::tempdir;
+
+extern "C" fn foreign_handler(_: libc::c_int) {}
+
+/// The current `SIGHUP` handler.
+fn sighup_handler() -> libc::sighandler_t {
+    // SAFETY: `sigaction` is plain data, and a null action only queries.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        libc::sigaction(libc::SIGHUP, std::ptr::null(), &mut action);
+        action.sa_sigaction
+    }
+}
+
+/// The number of open file descriptors of this process.
+#[cfg(target_os = "linux")]
+fn open_fds() -> usize {
+    std::fs::read_dir("/proc/self/fd").unwrap().count()
+}
+
+#[test]
+fn test_sighup_handler_of_another_library_is_kept() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[validator]\nbasefee = 10").unwrap();
+    let args = ["magic-block", "--config", path.to_str().unwrap()];
+    let handle = ConfigHandle::new(MagicBlockParams::builder, args.map(Into::into)).unwrap();
+
+    let foreign = foreign_handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: `foreign_handler` does nothing.
+    unsafe { libc::signal(libc::SIGHUP, foreign) };
+    #[cfg(target_os = "linux")]
+    let fds = open_fds();
+    let error = handle.reload_on_sighup(|_| {}).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(sighup_handler(), foreign);
+    #[cfg(target_os = "linux")]
+    assert_eq!(open_fds(), fds);
+
+    // A failed attempt doesn't count as installed.
+    // SAFETY: restores the default action.
+    unsafe { libc::signal(libc::SIGHUP, libc::SIG_DFL) };
+    handle
+        .reload_on_sighup(|_| {})
+        .expect("Failed to install handler");
+    assert_ne!(sighup_handler(), libc::SIG_DFL);
+}
@@ -0,0 +1,693 @@
+//! Integration tests for the supported configuration file sources.
+
+use magicblock_config::{remote::RemoteCluster, sources, LifecycleMode, MagicBlockParams};
+use std::io::Write;
+use std::path::PathBuf;
+use tempfile::{tempdir, TempDir};
+
+mod common;
+use common::create_config_file;
+
+fn load(args: Vec<&str>) -> MagicBlockParams {
+    MagicBlockParams::try_new(args.into_iter().map(Into::into))
+        .expect("Failed to assemble config for test")
+}
+
+#[test]
+fn test_yaml_config_detected_by_extension() {
+    let yaml_content = r#"
+listen: "0.0.0.0:7000"
+lifecycle: offline
+validator:
+  basefee: 4242
+accounts-db:
+  max-snapshots: 8
+"#;
+    for name in ["config.yaml", "config.yml"] {
+        let (_dir, path) = create_config_file(name, yaml_content);
+        let config = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+        assert_eq!(config.listen[0].to_string(), "0.0.0.0:7000");
+        assert_eq!(config.lifecycle, LifecycleMode::Offline);
+        assert_eq!(config.validator.basefee, 4242);
+        assert_eq!(config.accounts_db.max_snapshots, 8);
+    }
+}
+
+#[test]
+fn test_yaml_config_overrides_cli() {
+    let (_dir, path) = create_config_file("config.yml", "remote: mainnet\n");
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path.to_str().unwrap(),
+        "--remote",
+        "localhost",
+    ]);
+
+    assert_eq!(config.remote, "mainnet".parse().unwrap());
+}
+
+#[test]
+fn test_json_config_detected_by_extension() {
+    let json_content = r#"{ "remote": "testnet", "ledger": { "reset": false } }"#;
+    let (_dir, path) = create_config_file("config.json", json_content);
+    let config = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+    assert_eq!(config.remote, "testnet".parse().unwrap());
+    assert!(!config.ledger.reset);
+}
+
+#[test]
+fn test_explicit_config_format_overrides_extension() {
+    let json_content = r#"{ "validator": { "basefee": 777 } }"#;
+    let (_dir, path) = create_config_file("validator.conf", json_content);
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path.to_str().unwrap(),
+        "--config-format",
+        "json",
+    ]);
+
+    assert_eq!(config.validator.basefee, 777);
+}
+
+#[test]
+fn test_multiple_configs_merge_in_order() {
+    let (_base_dir, base) = create_config_file(
+        "base.toml",
+        r#"
+        listen = "0.0.0.0:9000"
+        remote = "mainnet"
+        [validator]
+        basefee = 5000
+        "#,
+    );
+    let (_overlay_dir, overlay) = create_config_file(
+        "overlay.yaml",
+        "remote: testnet\nvalidator:\n  basefee: 6000\n",
+    );
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        base.to_str().unwrap(),
+        "--config",
+        overlay.to_str().unwrap(),
+    ]);
+
+    // Only set by the base file.
+    assert_eq!(config.listen[0].to_string(), "0.0.0.0:9000");
+    // Set by both, the overlay wins.
+    assert_eq!(config.remote, "testnet".parse().unwrap());
+    assert_eq!(config.validator.basefee, 6000);
+}
+
+#[test]
+fn test_colon_separated_config_list() {
+    let (_base_dir, base) = create_config_file("base.toml", "[validator]\nbasefee = 1");
+    let (_overlay_dir, overlay) = create_config_file("overlay.toml", "[validator]\nbasefee = 2");
+    let list = format!("{}:{}", base.display(), overlay.display());
+    // In a child process, as other tests would pick up the variable.
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .arg("print-config")
+        .env("MBV_CONFIG", &list)
+        .output()
+        .expect("Failed to run the binary");
+
+    assert!(output.status.success(), "{output:?}");
+    let printed = String::from_utf8(output.stdout).unwrap();
+    assert!(printed.contains("basefee = 2\n"), "{printed}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_flag_is_not_split_on_colons() {
+    let (_dir, path) = create_config_file("base:prod.toml", "[validator]\nbasefee = 3");
+    let config = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+
+    assert_eq!(config.config, vec![path]);
+    assert_eq!(config.validator.basefee, 3);
+}
+
+/// Writes `content` to `name` inside an existing directory.
+fn write_file(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create parent dir");
+    std::fs::write(&path, content).expect("Failed to write temp config file");
+    path
+}
+
+#[test]
+fn test_include_resolves_relative_to_including_file() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write_file(
+        &dir,
+        "sections/accounts.toml",
+        "[accounts-db]\nmax-snapshots = 12\nsnapshot-frequency = 64",
+    );
+    write_file(&dir, "sections/ledger.yaml", "ledger:\n  reset: false\n");
+    let main = write_file(
+        &dir,
+        "main.toml",
+        r#"
+        include = ["sections/accounts.toml", "sections/ledger.yaml"]
+        [accounts-db]
+        snapshot-frequency = 128
+        "#,
+    );
+    let config = load(vec!["magic-block", "--config", main.to_str().unwrap()]);
+
+    assert_eq!(config.accounts_db.max_snapshots, 12);
+    assert!(!config.ledger.reset);
+    // The including file overrides what it includes.
+    assert_eq!(config.accounts_db.snapshot_frequency, 128);
+}
+
+#[test]
+fn test_include_cycle_is_rejected() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write_file(&dir, "a.toml", r#"include = ["b.toml"]"#);
+    let b = write_file(&dir, "b.toml", r#"include = ["a.toml"]"#);
+    let result = MagicBlockParams::try_new(
+        ["magic-block", "--config", b.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    );
+
+    let err = result.expect_err("include cycle should fail");
+    assert!(err.to_string().contains("include cycle"), "{err}");
+}
+
+#[test]
+fn test_missing_include_is_rejected() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let main = write_file(&dir, "main.toml", r#"include = ["nope.toml"]"#);
+    let result = MagicBlockParams::try_new(
+        ["magic-block", "--config", main.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_config_directory_merges_fragments_in_order() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    write_file(&dir, "10-base.toml", "listen = \"0.0.0.0:9000\"\n[validator]\nbasefee = 1");
+    write_file(&dir, "20-override.toml", "[validator]\nbasefee = 2");
+    // Not a TOML fragment, must be ignored.
+    write_file(&dir, "30-notes.txt", "this is not toml");
+    write_file(&dir, "nested/99-ignored.toml", "[validator]\nbasefee = 3");
+    let config = load(vec!["magic-block", "--config", dir.path().to_str().unwrap()]);
+
+    assert_eq!(config.listen[0].to_string(), "0.0.0.0:9000");
+    assert_eq!(config.validator.basefee, 2);
+}
+
+#[test]
+fn test_default_search_path_used_without_config_flag() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = write_file(&dir, "magicblock/config.toml", "[validator]\nbasefee = 31337");
+    // Only this test loads without `--config`, so setting the variable is safe.
+    std::env::set_var("XDG_CONFIG_HOME", dir.path());
+
+    let search_path = magicblock_config::sources::default_search_path();
+    let config = load(vec!["magic-block"]);
+
+    std::env::remove_var("XDG_CONFIG_HOME");
+    assert!(search_path.contains(&path));
+    assert_eq!(config.config, vec![path]);
+    assert_eq!(config.validator.basefee, 31337);
+}
+
+#[test]
+fn test_env_file_layered_below_process_env() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let env_file = write_file(
+        &dir,
+        "dev.env",
+        "# local development\nMBV_VALIDATOR_BASEFEE=55\nMBV_METRICS=\"10.0.0.1:9100\"\nOTHER_VAR=ignored\n",
+    );
+    // No other test in this file reads `metrics`.
+    std::env::set_var("MBV_METRICS", "10.0.0.2:9100");
+
+    let config = load(vec!["magic-block", "--env-file", env_file.to_str().unwrap()]);
+
+    std::env::remove_var("MBV_METRICS");
+    assert_eq!(config.validator.basefee, 55);
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "10.0.0.2:9100");
+}
+
+#[test]
+fn test_env_file_is_only_read_when_given() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let env_file = write_file(&dir, ".env", "MBV_VALIDATOR_BASEFEE=77\n");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .current_dir(dir.path())
+        .env_remove("MBV_ENV_FILE")
+        .output()
+        .expect("Failed to run the binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("basefee: 100,"), "{stdout}");
+
+    let args = [
+        "magic-block",
+        "--no-env",
+        "--env-file",
+        env_file.to_str().unwrap(),
+    ];
+    let config = MagicBlockParams::try_new(args.into_iter().map(Into::into))
+        .expect("Failed to assemble config for test");
+    assert_eq!(config.validator.basefee, 100);
+}
+
+#[test]
+fn test_comma_separated_remotes_from_cli_and_env() {
+    let config = load(vec![
+        "magic-block",
+        "--remote",
+        "mainnet, https://backup.example.com",
+    ]);
+    let urls: Vec<_> = config.remote.urls().map(|url| url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://api.mainnet-beta.solana.com/",
+            "https://backup.example.com/"
+        ]
+    );
+    assert!(matches!(config.remote, RemoteCluster::Multiple(_)));
+
+    let dir = tempdir().expect("Failed to create temp dir");
+    let env_file = write_file(&dir, "dev.env", "MBV_REMOTE=\"devnet,localhost\"\n");
+    let config = load(vec![
+        "magic-block",
+        "--env-file",
+        env_file.to_str().unwrap(),
+    ]);
+    let urls: Vec<_> = config.remote.urls().map(|url| url.as_str()).collect();
+    assert_eq!(
+        urls,
+        ["https://api.devnet.solana.com/", "http://127.0.0.1:8899/"]
+    );
+}
+
+#[test]
+fn test_repeated_remote_flags_append_in_order() {
+    let config = load(vec![
+        "magic-block",
+        "--remote",
+        "mainnet",
+        "-r",
+        "https://backup.example.com,devnet",
+    ]);
+    let urls: Vec<_> = config.remote.urls().map(|url| url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://api.mainnet-beta.solana.com/",
+            "https://backup.example.com/",
+            "https://api.devnet.solana.com/"
+        ]
+    );
+    assert_eq!(
+        config.remote.primary().and_then(|remote| remote.http_url()),
+        Some("https://api.mainnet-beta.solana.com".parse().unwrap())
+    );
+}
+
+#[test]
+fn test_lifecycle_defaults_apply_unless_remote_is_set() {
+    let (_dir, path) = create_config_file(
+        "config.toml",
+        "[lifecycle-defaults]\nreplica = \"mainnet\"\nprograms-replica = \"testnet\"",
+    );
+    let path = path.to_str().unwrap();
+    let primary = |config: &MagicBlockParams| config.remote.urls().next().unwrap().to_string();
+
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path,
+        "--lifecycle",
+        "replica",
+    ]);
+    assert_eq!(primary(&config), "https://api.mainnet-beta.solana.com/");
+    let source = &config.provenance()["remote"];
+    assert_eq!(source.file.as_deref(), Some(path));
+
+    let config = load(vec!["magic-block", "--config", path]);
+    assert_eq!(primary(&config), "https://api.testnet.solana.com/");
+
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path,
+        "--lifecycle",
+        "offline",
+    ]);
+    assert_eq!(primary(&config), "https://api.devnet.solana.com/");
+
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path,
+        "--lifecycle",
+        "replica",
+        "--remote",
+        "localhost",
+    ]);
+    assert_eq!(primary(&config), "http://127.0.0.1:8899/");
+}
+
+#[test]
+fn test_missing_env_file_is_rejected() {
+    let result = MagicBlockParams::try_new(
+        ["magic-block", "--env-file", "/nonexistent/magicblock.env"]
+            .into_iter()
+            .map(Into::into),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_env_vars_map_to_multi_word_keys() {
+    let cases = [
+        ("MBV_VALIDATOR_BASEFEE", "validator.basefee"),
+        ("MBV_ACCOUNTS_DB_DATABASE_SIZE", "accounts-db.database-size"),
+        ("MBV_ACCOUNTSDB_DATABASE_SIZE", "accounts-db.database-size"),
+        ("MBV_ACCOUNTSDB_MAX_SNAPSHOTS", "accounts-db.max-snapshots"),
+        (
+            "MBV_LEDGER_BLOCKS_PER_PARTITION",
+            "ledger.blocks-per-partition",
+        ),
+        (
+            "MBV_CHAIN_OPERATION_COUNTRY_CODE",
+            "chain-operation.country-code",
+        ),
+        ("MBV_NOT_A_KEY", "not.a.key"),
+    ];
+
+    for (var, key) in cases {
+        assert_eq!(sources::env_key_path(var).as_deref(), Some(key), "{var}");
+    }
+    assert_eq!(sources::env_key_path("MBV_CONFIG"), None);
+    assert_eq!(sources::env_key_path("OTHER_VAR"), None);
+    // The prefix would end inside a multi-byte character.
+    assert_eq!(sources::env_key_path("MBVé_LEDGER"), None);
+    assert_eq!(sources::env_key_path("MÄÖ_LEDGER"), None);
+}
+
+#[test]
+fn test_env_file_sets_accounts_db() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let env_file = write_file(
+        &dir,
+        "dev.env",
+        "MBV_ACCOUNTSDB_DATABASE_SIZE=209715200\nMBV_ACCOUNTS_DB_MAX_SNAPSHOTS=8\n",
+    );
+
+    let config = load(vec![
+        "magic-block",
+        "--strict-config",
+        "--env-file",
+        env_file.to_str().unwrap(),
+    ]);
+
+    assert_eq!(config.accounts_db.database_size, 209715200);
+    assert_eq!(config.accounts_db.max_snapshots, 8);
+}
+
+/// Serves `body` to a single HTTP request on a local port, returning the URL
+/// prefix and a handle yielding the raw request that was received.
+#[cfg(feature = "http")]
+fn serve_once(body: &'static str) -> (String, std::thread::JoinHandle<String>) {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    respond_once(response)
+}
+
+/// Answers a single HTTP request on a local port with `response`, returning
+/// the URL prefix and a handle yielding the raw request that was received.
+#[cfg(feature = "http")]
+fn respond_once(response: String) -> (String, std::thread::JoinHandle<String>) {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test server");
+    let addr = listener.local_addr().unwrap();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept request");
+        let mut request = String::new();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+            request.push_str(&line);
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        request
+    });
+    (format!("http://{addr}"), handle)
+}
+
+#[test]
+#[cfg(feature = "http")]
+fn test_config_fetched_from_url() {
+    let (base_url, server) = serve_once("remote = \"mainnet\"\n[validator]\nbasefee = 8080\n");
+    let url = format!("{base_url}/magicblock/prod.toml");
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        &url,
+        "--config-token",
+        "s3cret",
+    ]);
+
+    let request = server.join().unwrap();
+    assert!(
+        request.starts_with("GET /magicblock/prod.toml"),
+        "{request}"
+    );
+    assert!(
+        request.contains("Authorization: Bearer s3cret"),
+        "{request}"
+    );
+    assert_eq!(config.remote, "mainnet".parse().unwrap());
+    assert_eq!(config.validator.basefee, 8080);
+}
+
+#[test]
+#[cfg(feature = "http")]
+fn test_config_token_is_not_sent_over_plain_http() {
+    let result = MagicBlockParams::try_new(
+        [
+            "magic-block",
+            "--config",
+            "http://config.invalid/prod.toml",
+            "--config-token",
+            "s3cret",
+        ]
+        .into_iter()
+        .map(Into::into),
+    );
+
+    let error = result.expect_err("The token should be refused").to_string();
+    assert!(error.contains("over plain HTTP"), "{error}");
+}
+
+#[test]
+#[cfg(feature = "http")]
+fn test_config_token_is_dropped_on_redirect_to_another_host() {
+    let (target, target_server) = serve_once("[validator]\nbasefee = 8080\n");
+    // The same server under another name, as far as the client can tell.
+    let target = target.replace("127.0.0.1", "localhost");
+    let redirect = format!(
+        "HTTP/1.1 302 Found\r\nLocation: {target}/prod.toml\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    let (origin, origin_server) = respond_once(redirect);
+    let url = format!("{origin}/prod.toml");
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        &url,
+        "--config-token",
+        "s3cret",
+    ]);
+
+    assert!(origin_server.join().unwrap().contains("Bearer s3cret"));
+    let request = target_server.join().unwrap();
+    assert!(!request.contains("s3cret"), "{request}");
+    assert_eq!(config.validator.basefee, 8080);
+}
+
+#[test]
+fn test_config_timeout_is_a_loader_option() {
+    let config = load(vec!["magic-block", "--config-timeout", "5s"]);
+
+    assert_eq!(config.config_timeout, std::time::Duration::from_secs(5));
+    let toml = config.to_toml().expect("Failed to serialize");
+    assert!(!toml.contains("config-timeout"), "{toml}");
+}
+
+#[test]
+fn test_config_list_keeps_urls_intact() {
+    let list = PathBuf::from(
+        "base.toml:https://config.internal/prod.toml:http://10.0.0.1:8080/node.toml:/etc/overlay.toml",
+    );
+    let paths = magicblock_config::sources::split_config_list(&list);
+
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("base.toml"),
+            PathBuf::from("https://config.internal/prod.toml"),
+            PathBuf::from("http://10.0.0.1:8080/node.toml"),
+            PathBuf::from("/etc/overlay.toml"),
+        ]
+    );
+}
+
+/// Runs the binary with `args`, feeding `stdin` to it, and returns its stdout.
+fn run_binary_with_stdin(args: &[&str], stdin: &str) -> String {
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_config_read_from_stdin() {
+    let stdout = run_binary_with_stdin(&["--config", "-"], "[validator]\nbasefee = 4321\n");
+    assert!(stdout.contains("basefee: 4321"), "{stdout}");
+
+    let stdout = run_binary_with_stdin(
+        &["--config", "-"],
+        r#"{ "validator": { "basefee": 1234 } }"#,
+    );
+    assert!(stdout.contains("basefee: 1234"), "{stdout}");
+}
+
+#[test]
+fn test_profile_overrides_default_table() {
+    let (_dir, path) = create_config_file(
+        "config.toml",
+        r#"
+        remote = "devnet"
+        [validator]
+        basefee = 1
+
+        [profile.prod]
+        remote = "mainnet"
+        [profile.prod.validator]
+        basefee = 2
+
+        [profile.staging.validator]
+        basefee = 3
+        "#,
+    );
+    let path = path.to_str().unwrap();
+
+    let config = load(vec!["magic-block", "--config", path]);
+    assert_eq!(config.profile, None);
+    assert_eq!(config.validator.basefee, 1);
+
+    let config = load(vec!["magic-block", "--config", path, "--profile", "prod"]);
+    assert_eq!(config.profile.as_deref(), Some("prod"));
+    assert_eq!(config.remote, "mainnet".parse().unwrap());
+    assert_eq!(config.validator.basefee, 2);
+
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path,
+        "--profile",
+        "staging",
+    ]);
+    assert_eq!(config.remote, "devnet".parse().unwrap());
+    assert_eq!(config.validator.basefee, 3);
+}
+
+#[test]
+fn test_paths_expand_variables_and_resolve_against_config_file() {
+    let (dir, path) = create_config_file(
+        "config.toml",
+        "storage = \"data\"\ncreate-storage-dirs = true",
+    );
+    let config = load(vec!["magic-block", "--config", path.to_str().unwrap()]);
+    assert_eq!(
+        config.storage.unwrap(),
+        dir.path().join("data"),
+        "Relative paths in files are relative to the file"
+    );
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let config = load(vec![
+        "magic-block",
+        "--config",
+        path.to_str().unwrap(),
+        "--set",
+        "storage=${CARGO_MANIFEST_DIR}/target",
+    ]);
+    assert_eq!(config.storage.unwrap(), manifest_dir.join("target"));
+
+    let (_dir, path) =
+        create_config_file("config.toml", "storage = \"$MBV_TEST_UNSET_VARIABLE/data\"");
+    let err = MagicBlockParams::try_new(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    )
+    .expect_err("Unset variables should be rejected");
+    assert!(
+        err.to_string()
+            .contains("environment variable `MBV_TEST_UNSET_VARIABLE` is not set"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_tilde_expands_to_home() {
+    let home = tempdir().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+        .args(["--storage", "~/data", "--create-storage-dirs"])
+        .env("HOME", home.path())
+        .output()
+        .expect("Failed to run the binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!("{:?}", home.path().join("data"));
+    assert!(stdout.contains(&expected), "{stdout}");
+    assert!(home.path().join("data").is_dir());
+}
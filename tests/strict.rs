@@ -0,0 +1,197 @@
+//! Integration tests for rejecting unknown configuration keys in strict mode.
+
+use magicblock_config::{ConfigError, MagicBlockParams};
+use tempfile::tempdir;
+
+mod common;
+use common::create_config_file;
+
+fn try_load(args: Vec<&str>) -> Result<MagicBlockParams, ConfigError> {
+    MagicBlockParams::try_new(args.into_iter().map(Into::into))
+}
+
+const TYPO: &str = "[ledger]\nblocks-per-partiton = 8\n[validator]\nbasefee = 10";
+
+#[test]
+fn test_unknown_keys_ignored_by_default() {
+    let (_dir, path) = create_config_file("config.toml", TYPO);
+    let config = try_load(vec!["magic-block", "--config", path.to_str().unwrap()])
+        .expect("Unknown keys should be ignored outside strict mode");
+
+    assert_eq!(config.validator.basefee, 10);
+    assert!(!config.strict_config);
+}
+
+#[test]
+fn test_unknown_keys_reported_as_warnings_by_default() {
+    let (_dir, path) = create_config_file("config.toml", TYPO);
+    let report = MagicBlockParams::validate_sources(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    );
+
+    assert!(report.is_ok(), "{report}");
+    let unknown: Vec<_> = report.unknown_keys().collect();
+    assert_eq!(unknown.len(), 1, "{report}");
+    assert_eq!(unknown[0].key, "ledger.blocks-per-partiton");
+    assert_eq!(
+        unknown[0].suggestion.as_deref(),
+        Some("blocks-per-partition")
+    );
+    assert!(
+        report.to_string().contains("warning: unknown key"),
+        "{report}"
+    );
+
+    let strict = MagicBlockParams::validate_sources(
+        [
+            "magic-block",
+            "--strict-config",
+            "--config",
+            path.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(Into::into),
+    );
+    assert_eq!(strict.unknown_keys().count(), 0);
+    assert_eq!(strict.errors.len(), 1);
+}
+
+#[test]
+fn test_strict_config_rejects_unknown_keys_with_suggestion() {
+    let (_dir, path) = create_config_file("config.toml", TYPO);
+    let err = try_load(vec![
+        "magic-block",
+        "--strict-config",
+        "--config",
+        path.to_str().unwrap(),
+    ])
+    .expect_err("Unknown keys should be rejected in strict mode");
+
+    let ConfigError::Invalid(report) = &err else {
+        panic!("expected unknown keys, got {err:?}");
+    };
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].key, "ledger.blocks-per-partiton");
+    let message = &report.errors[0].message;
+    assert!(message.contains("config.toml"), "{message}");
+    assert!(
+        message.contains("did you mean `blocks-per-partition`?"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_strict_config_rejects_unknown_env_keys() {
+    std::env::set_var("MBV_LEDGER_RESTE", "true");
+    let result = MagicBlockParams::builder()
+        .with_strict_config(true)
+        .try_build(["magic-block"].into_iter().map(Into::into));
+    std::env::remove_var("MBV_LEDGER_RESTE");
+
+    let Err(ConfigError::Invalid(report)) = result else {
+        panic!("expected unknown keys, got {result:?}");
+    };
+    assert_eq!(report.errors[0].key, "ledger.reste");
+    assert!(
+        report.errors[0].message.contains("did you mean `reset`?"),
+        "{report}"
+    );
+}
+
+#[test]
+fn test_unknown_env_section_names_the_variable() {
+    std::env::set_var("MBV_FOO_BAR", "1");
+    let report = MagicBlockParams::validate_sources(["magic-block"].into_iter().map(Into::into));
+    std::env::remove_var("MBV_FOO_BAR");
+
+    let unknown = report
+        .unknown_keys()
+        .find(|unknown| unknown.key == "foo")
+        .expect("The section should be reported");
+    let origin = unknown.origin.as_deref().unwrap_or_default();
+    assert!(origin.contains("`MBV_FOO_BAR`"), "{report}");
+}
+
+#[test]
+fn test_strict_config_accepts_directives_and_known_keys() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    std::fs::write(dir.path().join("base.toml"), "[ledger]\nreset = true").unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "include = [\"base.toml\"]\n[validator]\nbasefee = 1\n[profile.prod.validator]\nbasefee = 2",
+    )
+    .unwrap();
+
+    let config = try_load(vec![
+        "magic-block",
+        "--strict-config",
+        "--profile",
+        "prod",
+        "--config",
+        path.to_str().unwrap(),
+    ])
+    .expect("Known keys and file directives should pass strict mode");
+
+    assert!(config.ledger.reset);
+    assert_eq!(config.validator.basefee, 2);
+    assert!(config.strict_config);
+}
+
+#[test]
+fn test_unknown_keys_inside_remotes_are_reported() {
+    let content = r#"
+[remote]
+url = "https://rpc.example.com"
+helth = { interval = "5s" }
+rate-limit = { requests-per-secnd = 10 }
+
+[remotes.backup]
+http = "https://backup.example.com"
+ws = "wss://backup.example.com"
+tokn = "t0ken"
+
+[lifecycle-defaults.ephemeral]
+default = [{ endpoint = "https://grpc.example.com", filters = { owner = [] } }]
+"#;
+    let (_dir, path) = create_config_file("config.toml", content);
+    let report = MagicBlockParams::validate_sources(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    );
+
+    let unknown: Vec<_> = report.unknown_keys().map(|u| u.key.as_str()).collect();
+    assert_eq!(
+        unknown,
+        [
+            "lifecycle-defaults.ephemeral.default.0.filters.owner",
+            "remote.helth",
+            "remote.rate-limit.requests-per-secnd",
+            "remotes.backup.tokn",
+        ]
+    );
+}
+
+#[test]
+fn test_strict_config_rejects_typo_inside_remote() {
+    let (_dir, path) = create_config_file(
+        "config.toml",
+        "[remote]\nurl = \"https://rpc.example.com\"\nhelth = { interval = \"5s\" }",
+    );
+    let err = try_load(vec![
+        "magic-block",
+        "--strict-config",
+        "--config",
+        path.to_str().unwrap(),
+    ])
+    .expect_err("A typo inside a remote should be rejected in strict mode");
+
+    let ConfigError::Invalid(report) = &err else {
+        panic!("expected unknown keys, got {err:?}");
+    };
+    assert_eq!(report.errors.len(), 1, "{report}");
+    assert_eq!(report.errors[0].key, "remote.helth");
+}
@@ -0,0 +1,234 @@
+//! Integration tests for the commented configuration template.
+
+use magicblock_config::MagicBlockParams;
+use tempfile::tempdir;
+use toml::Table;
+
+/// Dotted paths of every value in `table`.
+fn leaf_keys(table: &Table, prefix: &str) -> Vec<String> {
+    table
+        .iter()
+        .flat_map(|(key, value)| {
+            let key = format!("{prefix}{key}");
+            match value.as_table() {
+                Some(section) => leaf_keys(section, &format!("{key}.")),
+                None => vec![key],
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_template_lists_every_default_key() {
+    let template: Table = MagicBlockParams::template()
+        .parse()
+        .expect("Template should be valid TOML");
+    let defaults: Table = MagicBlockParams::default()
+        .to_toml()
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    let mut expected = leaf_keys(&defaults, "");
+    expected.push("config-version".to_owned());
+    let mut listed = leaf_keys(&template, "");
+    expected.sort();
+    listed.sort();
+    assert_eq!(listed, expected);
+}
+
+#[test]
+fn test_template_loads_as_defaults_in_strict_mode() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, MagicBlockParams::template()).unwrap();
+
+    let config = MagicBlockParams::try_new(
+        [
+            "magic-block",
+            "--strict-config",
+            "--config",
+            path.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .expect("Template should load");
+    let defaults = MagicBlockParams::default();
+
+    assert_eq!(config.validator.basefee, defaults.validator.basefee);
+    assert_eq!(config.accounts_db.max_snapshots, 4);
+    assert_eq!(config.ledger.block_time, defaults.ledger.block_time);
+    assert!(config.chain_operation.is_none());
+}
+
+#[test]
+fn test_template_documents_each_key() {
+    let template = MagicBlockParams::template();
+    let lines: Vec<&str> = template.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let is_value = !line.is_empty() && !line.starts_with("# ") || line.starts_with("# [");
+        if is_value && i > 0 {
+            assert!(lines[i - 1].starts_with("# "), "undocumented: {line}");
+        }
+    }
+    assert!(template.contains("# Base fee in lamports for transactions.\nbasefee = 100\n"));
+    assert!(template.contains("[metrics]\n"));
+    assert!(template.contains("# listen = \"127.0.0.1:9000\"\n"));
+}
+
+/// Dotted paths of every key and section in the template, including the
+/// ones left commented out.
+#[cfg(feature = "schema")]
+fn template_keys() -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut section = String::new();
+    for line in MagicBlockParams::template().lines() {
+        let line = line.strip_prefix("# ").unwrap_or(line);
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = format!("{name}.");
+            keys.push(name.to_owned());
+        } else if let Some((name, _)) = line.split_once(" = ") {
+            if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                keys.push(format!("{section}{name}"));
+            }
+        }
+    }
+    keys
+}
+
+/// The object schema `schema` refers to, directly or as the non-null
+/// alternative, if it describes a section.
+#[cfg(feature = "schema")]
+fn section_schema<'a>(
+    schema: &'a serde_json::Value,
+    definitions: &'a serde_json::Value,
+) -> Option<&'a serde_json::Value> {
+    if let Some(name) = schema["$ref"].as_str() {
+        return section_schema(
+            &definitions[name.trim_start_matches("#/definitions/")],
+            definitions,
+        );
+    }
+    if schema["properties"].is_object() {
+        return Some(schema);
+    }
+    ["allOf", "anyOf"]
+        .into_iter()
+        .filter_map(|key| schema[key].as_array())
+        .flatten()
+        .find_map(|variant| section_schema(variant, definitions))
+}
+
+/// Dotted paths of every key and section the JSON Schema describes.
+#[cfg(feature = "schema")]
+fn schema_keys(
+    schema: &serde_json::Value,
+    definitions: &serde_json::Value,
+    prefix: &str,
+    keys: &mut Vec<String>,
+) {
+    for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+        let key = format!("{prefix}{name}");
+        keys.push(key.clone());
+        if let Some(section) = section_schema(property, definitions) {
+            schema_keys(section, definitions, &format!("{key}."), keys);
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_template_matches_the_schema_keys() {
+    let schema = serde_json::to_value(MagicBlockParams::json_schema()).unwrap();
+    let mut keys = Vec::new();
+    schema_keys(&schema, &schema["definitions"], "", &mut keys);
+    keys.retain(|key| !magicblock_config::consts::LOADER_KEYS.contains(&key.as_str()));
+    let listed = template_keys();
+
+    for key in &listed {
+        assert!(keys.contains(key), "not a configuration key: {key}");
+    }
+    let nested = |key: &str, parent: &str| {
+        key.strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with('.'))
+    };
+    // A key documented as one value, like `listen`, covers its nested keys.
+    let values: Vec<_> = listed
+        .iter()
+        .filter(|parent| !listed.iter().any(|key| nested(key, parent)))
+        .collect();
+    for key in &keys {
+        if !values.iter().any(|value| nested(key, value)) {
+            assert!(listed.contains(key), "not in the template: {key}");
+        }
+    }
+}
+
+#[test]
+fn test_init_subcommand_writes_without_overwriting() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("config.toml");
+    let init = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_magicblock-config"))
+            .arg("init")
+            .arg(&path)
+            .args(extra)
+            .output()
+            .expect("Failed to run the binary")
+    };
+
+    assert!(init(&[]).status.success());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        MagicBlockParams::template()
+    );
+
+    std::fs::write(&path, "# edited").unwrap();
+    let output = init(&[]);
+    assert!(!output.status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "# edited");
+
+    assert!(init(&["--force"]).status.success());
+    assert_ne!(std::fs::read_to_string(&path).unwrap(), "# edited");
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_reference_lists_every_key_with_its_variable() {
+    let reference = MagicBlockParams::reference();
+    let rows: Vec<Vec<&str>> = reference
+        .lines()
+        .filter(|line| line.starts_with("| `"))
+        .map(|line| line.split(" | ").collect())
+        .collect();
+
+    let keys: Vec<_> = rows
+        .iter()
+        .map(|row| row[0].trim_matches(['|', ' ', '`']))
+        .collect();
+    // Values given as tables in the template, like `validator.signer.connection`,
+    // are sections of their own in the reference.
+    for key in template_keys() {
+        let section = format!("## `[{key}]`");
+        assert!(
+            keys.contains(&key.as_str()) || reference.contains(&section),
+            "missing {key}"
+        );
+    }
+    for (key, row) in keys.iter().zip(&rows) {
+        let var = row[3].trim_matches('`');
+        if var.is_empty() {
+            continue;
+        }
+        assert_eq!(
+            magicblock_config::sources::env_key_path(var).as_deref(),
+            Some(*key),
+            "{var}"
+        );
+    }
+    assert!(reference.contains(
+        "| `validator.basefee` | integer | `100` | `MBV_VALIDATOR_BASEFEE` | `--basefee` |"
+    ));
+}
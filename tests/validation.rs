@@ -0,0 +1,1188 @@
+//! Integration tests for the semantic validation of the assembled configuration.
+
+use magicblock_config::{
+    config::{IdentitySource, KeyRole, OtlpProtocol},
+    consts, keygen,
+    remote::{HealthCheck, HealthCheckMethod, RateLimit, Remote, RemotePurpose},
+    types::{BindAddress, CommitmentLevel, RetryPolicy},
+    validate::Requirement,
+    ConfigError, LifecycleMode, MagicBlockParams, Validate,
+};
+use solana_keypair::Keypair;
+use std::{path::PathBuf, time::Duration};
+use tempfile::tempdir;
+
+mod common;
+use common::create_config_file;
+
+fn try_load(content: &str) -> Result<MagicBlockParams, ConfigError> {
+    let (_dir, path) = create_config_file("config.toml", content);
+    MagicBlockParams::try_new(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    )
+}
+
+const CHAIN_OPERATION: &str = r#"
+[chain-operation]
+country-code = "US"
+fqdn = "https://validator.example.com"
+claim-fees-frequency = "1h"
+"#;
+
+/// Returns the keys reported as invalid, failing if the load succeeded or
+/// failed for another reason.
+fn invalid_keys(content: &str) -> Vec<String> {
+    match try_load(content) {
+        Err(ConfigError::Invalid(report)) => report.errors.into_iter().map(|e| e.key).collect(),
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_defaults_are_valid() {
+    let config = try_load("").expect("Defaults should be valid");
+    assert!(config.validate().is_empty());
+}
+
+#[test]
+fn test_out_of_range_sizes_are_rejected_with_range() {
+    let err = try_load("[accounts-db]\ndatabase-size = 0")
+        .expect_err("Zero database size should be rejected");
+    let message = err.to_string();
+
+    assert!(message.contains("database-size"), "{message}");
+    assert!(
+        message.contains("0 is out of range, expected a value in 1048576..=1099511627776"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_sizes_accept_units() {
+    let config = try_load("[accounts-db]\ndatabase-size = \"8GB\"\nindex-size = \"512 mib\"")
+        .expect("Sizes with units should be accepted");
+    assert_eq!(config.accounts_db.database_size, 8_000_000_000);
+    assert_eq!(config.accounts_db.index_size, 512 * 1024 * 1024);
+    let toml = config.to_toml().unwrap();
+    assert!(toml.contains("database-size = \"8GB\""), "{toml}");
+    assert!(toml.contains("index-size = \"512MiB\""), "{toml}");
+
+    let err = try_load("[accounts-db]\ndatabase-size = \"12XB\"")
+        .expect_err("Unknown units should be rejected");
+    assert!(err.to_string().contains("invalid size `12XB`"), "{err}");
+    let err = try_load("[accounts-db]\ndatabase-size = \"1KiB\"")
+        .expect_err("Sizes are range-checked in bytes");
+    assert!(err.to_string().contains("1024 is out of range"), "{err}");
+}
+
+#[test]
+fn test_block_size_accepts_numbers() {
+    for (value, expected) in [("128", 128), ("\"256\"", 256), ("\"block512\"", 512)] {
+        let config = try_load(&format!("[accounts-db]\nblock-size = {value}"))
+            .expect("Supported block sizes should be accepted");
+        assert_eq!(config.accounts_db.block_size.bytes(), expected);
+    }
+    let toml = try_load("[accounts-db]\nblock-size = \"512\"")
+        .unwrap()
+        .to_toml()
+        .unwrap();
+    assert!(toml.contains("block-size = 512\n"), "{toml}");
+
+    let err = try_load("[accounts-db]\nblock-size = 300")
+        .expect_err("Unsupported block sizes should be rejected");
+    assert!(
+        err.to_string()
+            .contains("invalid block size `300`, expected one of 128, 256 or 512"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_lamports_accept_sol_amounts() {
+    let config = try_load(
+        "[validator]\nbasefee = \"0.000005 SOL\"\n[chainlink]\nauto-airdrop-lamports = \"1.5 sol\"",
+    )
+    .expect("SOL amounts should be accepted");
+    assert_eq!(config.validator.basefee, 5000);
+    assert_eq!(config.chainlink.auto_airdrop_lamports, 1_500_000_000);
+    let toml = config.to_toml().unwrap();
+    assert!(toml.contains("basefee = 5000\n"), "{toml}");
+
+    let config = try_load("[validator]\nbasefee = \"5000 lamports\"").unwrap();
+    assert_eq!(config.validator.basefee, 5000);
+
+    let err = try_load("[validator]\nbasefee = \"0.0000000001 SOL\"")
+        .expect_err("Fractions of a lamport should be rejected");
+    assert!(
+        err.to_string()
+            .contains("invalid amount `0.0000000001 SOL`"),
+        "{err}"
+    );
+    let err = try_load("[validator]\nbasefee = \"2 SOL\"")
+        .expect_err("SOL amounts are range-checked in lamports");
+    assert!(
+        err.to_string().contains("2000000000 is out of range"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_out_of_range_fees_are_rejected() {
+    let err = try_load("[commit]\ncompute-unit-price = 100000001")
+        .expect_err("Absurd compute unit price should be rejected");
+    assert!(err.to_string().contains("0..=100000000"), "{err}");
+
+    let config = try_load("[validator]\nbasefee = 0\n[accounts-db]\nindex-size = 2048")
+        .expect("In-range values should be accepted");
+    assert_eq!(config.validator.basefee, 0);
+    assert_eq!(config.accounts_db.index_size.get(), 2048);
+}
+
+#[test]
+fn test_zero_durations_are_rejected() {
+    let keys = invalid_keys("[ledger]\nblock-time = \"0ms\"");
+    assert_eq!(keys, ["ledger.block-time"]);
+}
+
+#[test]
+fn test_non_http_fqdn_is_rejected() {
+    let keys = invalid_keys(
+        "[chain-operation]\ncountry-code = \"US\"\nfqdn = \"ftp://validator.example.com\"\nclaim-fees-frequency = \"1h\"",
+    );
+    assert_eq!(keys, ["chain-operation.fqdn"]);
+}
+
+#[test]
+fn test_all_errors_are_reported_together() {
+    let err = try_load("[ledger]\nblocks-per-partition = 0\n[accounts-db]\nsnapshot-frequency = 0")
+        .expect_err("Invalid configuration should be rejected");
+    let message = err.to_string();
+
+    assert!(
+        message.contains("`accounts-db.snapshot-frequency`"),
+        "{message}"
+    );
+    assert!(
+        message.contains("`ledger.blocks-per-partition`"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_accounts_db_and_ledger_must_be_consistent() {
+    let keys = invalid_keys(
+        "[accounts-db]\nmax-snapshots = 0\ndatabase-size = 1048576\nindex-size = 1048576\nsnapshot-frequency = 2048\n[ledger]\nblocks-per-partition = 1024",
+    );
+    assert_eq!(
+        keys,
+        [
+            "accounts-db.max-snapshots",
+            "accounts-db.index-size",
+            "accounts-db.snapshot-frequency"
+        ]
+    );
+
+    let err =
+        try_load("[accounts-db]\nsnapshot-frequency = 16\n[ledger]\nblocks-per-partition = 8")
+            .expect_err("Snapshots less frequent than partitions should be rejected");
+    assert!(
+        err.to_string()
+            .contains("must not exceed `ledger.blocks-per-partition` (8)"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_metrics_must_differ_from_listen() {
+    let keys = invalid_keys("listen = \"0.0.0.0:9000\"\nmetrics = \"0.0.0.0:9000\"");
+    assert_eq!(keys, ["metrics.listen"]);
+}
+
+#[test]
+fn test_bind_addresses_resolve_hostnames() {
+    let config = try_load("listen = \"localhost:7000\"\nmetrics = \"[::]:7001\"")
+        .expect("Hostnames and IPv6 addresses should be accepted");
+    assert_eq!(config.listen[0].to_string(), "127.0.0.1:7000");
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "[::]:7001");
+
+    let err = try_load("listen = \"no-such-host.invalid:7000\"")
+        .expect_err("Unresolvable hostnames should be rejected");
+    assert!(
+        err.to_string()
+            .contains("cannot resolve `no-such-host.invalid:7000`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_port_only_bind_addresses() {
+    let config = try_load("listen = 9000\nmetrics = \":9001\"").expect("Ports should be accepted");
+    assert_eq!(config.listen[0].to_string(), "127.0.0.1:9000");
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "127.0.0.1:9001");
+
+    let config = try_load("bind-all = true\nlisten = 9000\nmetrics = \"127.0.0.1:9001\"")
+        .expect("Ports should be accepted");
+    assert_eq!(config.listen[0].to_string(), "0.0.0.0:9000");
+    // Only port-only addresses follow `bind-all`.
+    assert_eq!(config.metrics.listen.unwrap().to_string(), "127.0.0.1:9001");
+
+    let config = MagicBlockParams::try_new_without_env(
+        ["magic-block", "--listen", ":9000", "--bind-all"]
+            .into_iter()
+            .map(Into::into),
+    )
+    .expect("Ports should be accepted on the command line");
+    assert_eq!(config.listen[0].to_string(), "0.0.0.0:9000");
+
+    assert_eq!(invalid_keys("listen = 70000"), ["listen"]);
+}
+
+#[test]
+fn test_multiple_listen_addresses() {
+    let config = try_load("listen = [\"127.0.0.1:7000\", \"[::1]:7000\", 7001]")
+        .expect("A list of addresses should be accepted");
+    let listen: Vec<String> = config.listen.iter().map(ToString::to_string).collect();
+    assert_eq!(listen, ["127.0.0.1:7000", "[::1]:7000", "127.0.0.1:7001"]);
+
+    let config = MagicBlockParams::try_new_without_env(
+        [
+            "magic-block",
+            "--listen",
+            "127.0.0.1:7000",
+            "-l",
+            "[::1]:7000",
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .expect("--listen should be repeatable");
+    assert_eq!(config.listen.len(), 2);
+    assert!(config.to_toml().unwrap().contains("listen = ["));
+
+    let keys = invalid_keys(
+        "listen = [\"127.0.0.1:7000\", \"[::1]:7000\", \"127.0.0.1:7000\"]\nmetrics = \"[::1]:7000\"",
+    );
+    assert_eq!(keys, ["listen.2", "metrics.listen"]);
+    assert_eq!(invalid_keys("listen = []"), ["listen"]);
+}
+
+#[test]
+fn test_unix_socket_bind_addresses() {
+    let config = try_load(
+        "listen = \"unix:/run/magicblock/rpc.sock?mode=660&owner=magicblock&group=proxy\"\n\
+         metrics = \"unix:/run/magicblock/metrics.sock\"",
+    )
+    .expect("Unix sockets should be accepted");
+    let [BindAddress::Unix(socket)] = config.listen.as_slice() else {
+        panic!("expected a Unix socket, got {:?}", config.listen);
+    };
+    assert_eq!(socket.path, PathBuf::from("/run/magicblock/rpc.sock"));
+    assert_eq!(socket.mode, Some(0o660));
+    assert_eq!(socket.owner.as_deref(), Some("magicblock"));
+    assert_eq!(socket.group.as_deref(), Some("proxy"));
+    assert_eq!(
+        config.listen[0].to_string(),
+        "unix:/run/magicblock/rpc.sock?mode=0660&owner=magicblock&group=proxy"
+    );
+
+    let err = try_load("listen = \"unix:/run/rpc.sock?mode=999\"")
+        .expect_err("Non-octal modes should be rejected");
+    assert!(err.to_string().contains("invalid mode `999`"), "{err}");
+
+    let long = format!("listen = \"unix:/{}\"", "a".repeat(200));
+    assert_eq!(invalid_keys(&long), ["listen"]);
+    let same = "listen = \"unix:/run/rpc.sock?mode=0600\"\nmetrics = \"unix:/run/rpc.sock\"";
+    assert_eq!(invalid_keys(same), ["metrics.listen"]);
+    let admin = "[admin]\nlisten = \"unix:/run/admin.sock\"\ntoken = \"secret\"";
+    assert_eq!(invalid_keys(admin), ["admin.listen"]);
+}
+
+#[test]
+fn test_offline_lifecycle_rejects_remote() {
+    let keys = invalid_keys("lifecycle = \"offline\"\nremote = \"mainnet\"");
+    assert_eq!(keys, ["remote"]);
+}
+
+#[test]
+fn test_ephemeral_lifecycle_requirements() {
+    let keys = invalid_keys("lifecycle = \"ephemeral\"");
+    assert_eq!(keys, ["chain-operation", "validator.keypair"]);
+
+    let keypair = Keypair::new().to_base58_string();
+    try_load(&format!(
+        "lifecycle = \"ephemeral\"\n[validator]\nkeypair = \"{keypair}\"\n{CHAIN_OPERATION}"
+    ))
+    .expect("Ephemeral mode with chain operation and a keypair should be valid");
+}
+
+#[test]
+fn test_ephemeral_lifecycle_rejects_processed_commitment() {
+    let keypair = Keypair::new().to_base58_string();
+    let base = format!(
+        "lifecycle = \"ephemeral\"\n{CHAIN_OPERATION}\n[validator]\nkeypair = \"{keypair}\""
+    );
+    let keys = invalid_keys(&format!("{base}\n[chainlink]\ncommitment = \"processed\""));
+    assert_eq!(keys, ["chainlink.commitment"]);
+
+    let config = try_load(&format!("{base}\n[chainlink]\ncommitment = \"finalized\""))
+        .expect("Finalized commitment should be valid in ephemeral mode");
+    assert_eq!(config.chainlink.commitment, CommitmentLevel::Finalized);
+    assert_eq!(config.chainlink.commitment.to_string(), "finalized");
+    let config = try_load("[chainlink]\ncommitment = \"processed\"")
+        .expect("Other lifecycles accept any commitment");
+    assert_eq!(config.chainlink.commitment, CommitmentLevel::Processed);
+}
+
+#[test]
+fn test_offline_lifecycle_rejects_chainlink() {
+    let keys = invalid_keys("lifecycle = \"offline\"\n[chainlink]\nauto-airdrop-lamports = 1");
+    assert_eq!(keys, ["chainlink"]);
+}
+
+#[test]
+fn test_lifecycle_requirements_api() {
+    assert_eq!(
+        LifecycleMode::Offline.requirements(),
+        [Requirement::DefaultRemote, Requirement::NoChainLink]
+    );
+    assert!(LifecycleMode::Replica
+        .requirements()
+        .contains(&Requirement::ReachableRemote));
+
+    let config = try_load("").unwrap();
+    for requirement in LifecycleMode::ProgramsReplica.requirements() {
+        assert!(requirement.is_met(&config), "{requirement:?}");
+    }
+    assert!(!Requirement::CustomKeypair.is_met(&config));
+}
+
+#[test]
+fn test_cross_field_errors_are_reported_in_one_pass() {
+    let keys = invalid_keys(
+        "lifecycle = \"ephemeral\"\nlisten = \"0.0.0.0:9000\"\nmetrics = \"0.0.0.0:9000\"\n[ledger]\nblock-time = \"0s\"",
+    );
+    assert_eq!(
+        keys,
+        [
+            "ledger.block-time",
+            "metrics.listen",
+            "chain-operation",
+            "validator.keypair"
+        ]
+    );
+}
+
+#[test]
+fn test_missing_storage_is_rejected_unless_created() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let storage = dir.path().join("data").join("ledger");
+    let content = format!("storage = {:?}", storage.to_str().unwrap());
+
+    let err = try_load(&content).expect_err("Missing storage should be rejected");
+    assert!(err.to_string().contains(storage.to_str().unwrap()), "{err}");
+    assert!(!storage.exists());
+
+    let config = try_load(&format!("{content}\ncreate-storage-dirs = true"))
+        .expect("Storage should be created");
+    assert!(config.create_storage_dirs);
+    assert!(storage.is_dir());
+}
+
+#[test]
+fn test_validating_storage_creates_nothing() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let storage = dir.path().join("data").join("ledger");
+    let (_config_dir, path) = create_config_file(
+        "config.toml",
+        &format!(
+            "storage = {:?}\ncreate-storage-dirs = true",
+            storage.to_str().unwrap()
+        ),
+    );
+    let args = ["magic-block", "--config", path.to_str().unwrap()];
+
+    let report = MagicBlockParams::validate_sources(args.into_iter().map(Into::into));
+    assert!(report.is_ok(), "{report}");
+    assert!(!dir.path().join("data").exists());
+
+    let config = MagicBlockParams::try_new(args.into_iter().map(Into::into))
+        .expect("Storage should be created");
+    assert!(storage.is_dir());
+    assert!(config.prepare_storage().is_empty());
+    assert_eq!(std::fs::read_dir(&storage).unwrap().count(), 0);
+}
+
+#[test]
+fn test_storage_must_be_a_directory() {
+    let (_dir, path) = create_config_file("not-a-dir", "");
+    let keys = invalid_keys(&format!("storage = {:?}", path.to_str().unwrap()));
+    assert_eq!(keys, ["storage"]);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_storage_min_free_space() {
+    let dir = tempdir().expect("Failed to create temp dir");
+    let storage = format!("storage = {:?}", dir.path().to_str().unwrap());
+
+    try_load(&format!("{storage}\nstorage-min-free-space = 1"))
+        .expect("A byte of free space should be available");
+    let keys = invalid_keys(&format!("{storage}\nstorage-min-free-space = {}", i64::MAX));
+    assert_eq!(keys, ["storage"]);
+}
+
+#[test]
+fn test_disjointed_remote_schemes_are_checked() {
+    let keys =
+        invalid_keys("[remote]\nhttp = \"ws://127.0.0.1:8900\"\nws = \"https://127.0.0.1:8899\"");
+    assert_eq!(keys, ["remote.http", "remote.ws"]);
+
+    try_load("[remote]\nhttp = \"https://rpc.example.com\"\nws = \"wss://rpc.example.com\"")
+        .expect("Matching schemes should be valid");
+}
+
+#[test]
+fn test_unified_remote_must_support_http_and_ws() {
+    let keys = invalid_keys(
+        "remote = [\"mainnet\", \"ftp://rpc.example.com\", { http = \"http://a.example.com\", ws = \"http://a.example.com\" }]",
+    );
+    assert_eq!(keys, ["remote.1", "remote.2.ws"]);
+
+    try_load("remote = \"ws://127.0.0.1:8900\"").expect("WebSocket URLs convert to HTTP");
+}
+
+#[test]
+fn test_remote_credentials_are_checked() {
+    let content = r#"
+[[remote]]
+url = "https://rpc.example.com"
+token = ""
+
+[[remote]]
+http = "https://a.example.com"
+ws = "wss://a.example.com"
+api-key = "k3y"
+headers = { "x token" = "t0ken", x-api-key = "" }
+"#;
+
+    assert_eq!(
+        invalid_keys(content),
+        [
+            "remote.0.token",
+            "remote.1.headers",
+            "remote.1.headers.x-api-key"
+        ]
+    );
+}
+
+#[test]
+fn test_remote_roles_pick_the_primary() {
+    let config = try_load(
+        r#"
+remote = [
+    "https://a.example.com",
+    { url = "https://b.example.com", role = "primary" },
+    { url = "https://c.example.com", role = "fallback" },
+]
+
+[failover]
+error-threshold = 5
+cooldown = "1m"
+"#,
+    )
+    .expect("A single primary should be valid");
+    let index = |remote: &magicblock_config::remote::Remote| {
+        config.remote.remotes().iter().position(|r| r == remote)
+    };
+    assert_eq!(config.remote.primary().and_then(index), Some(1));
+    let fallbacks: Vec<_> = config.remote.fallbacks().filter_map(index).collect();
+    assert_eq!(fallbacks, [0, 2]);
+    assert_eq!(config.failover.error_threshold, 5);
+    assert_eq!(config.failover.cooldown, std::time::Duration::from_secs(60));
+
+    let config = try_load(
+        r#"remote = [{ url = "https://a.example.com", role = "fallback" }, "https://b.example.com"]"#,
+    )
+    .unwrap();
+    assert_eq!(config.remote.primary(), config.remote.remotes().get(1));
+
+    let keys = invalid_keys(
+        r#"
+remote = [
+    { url = "https://a.example.com", role = "primary" },
+    { url = "https://b.example.com", role = "primary" },
+]
+
+[failover]
+error-threshold = 0
+cooldown = "0s"
+"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "remote.1.role",
+            "failover.error-threshold",
+            "failover.cooldown"
+        ]
+    );
+    let keys = invalid_keys(r#"remote = { url = "https://a.example.com", role = "fallback" }"#);
+    assert_eq!(keys, ["remote"]);
+}
+
+#[test]
+fn test_remote_genesis_pins_are_checked() {
+    let config = try_load(
+        r#"remote = { url = "https://api.mainnet-beta.solana.com", expected-genesis = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d" }"#,
+    )
+    .expect("The genesis hash should be valid");
+    let remote = &config.remote.remotes()[0];
+    assert_eq!(
+        remote.expected_genesis(),
+        Some("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d")
+    );
+    assert!(remote
+        .verify_genesis("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d")
+        .is_ok());
+    let mismatch = remote
+        .verify_genesis("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG")
+        .unwrap_err();
+    assert_eq!(
+        mismatch.actual,
+        "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG"
+    );
+
+    let keys = invalid_keys(
+        r#"remote = [{ http = "https://rpc.example.com", ws = "wss://rpc.example.com", expected-genesis = "mainnet" }, "devnet"]"#,
+    );
+    assert_eq!(keys, ["remote.0.expected-genesis"]);
+}
+
+#[test]
+fn test_proxy_is_resolved_per_remote() {
+    let config = try_load(
+        r#"
+remote = [
+    "mainnet",
+    "http://node.internal:8899",
+    { url = "https://rpc.example.com", proxy = "none" },
+    { url = "https://partner.example.com", proxy = "http://partner-proxy:3128" },
+]
+
+[network.proxy]
+url = "socks5h://proxy.example.com:1080"
+no-proxy = [".internal"]
+"#,
+    )
+    .expect("The proxy settings should be valid");
+    let proxies: Vec<_> = config
+        .remote
+        .remotes()
+        .iter()
+        .map(|remote| config.proxy_for(remote).map(|url| url.as_str()))
+        .collect();
+    assert_eq!(
+        proxies,
+        [
+            Some("socks5h://proxy.example.com:1080"),
+            None,
+            None,
+            Some("http://partner-proxy:3128/")
+        ]
+    );
+
+    let keys = invalid_keys(
+        r#"
+remote = { url = "https://rpc.example.com", proxy = "ftp://proxy" }
+
+[network.proxy]
+url = "file:///tmp/proxy"
+no-proxy = [""]
+"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "remote.proxy",
+            "network.proxy.url",
+            "network.proxy.no-proxy"
+        ]
+    );
+}
+
+#[test]
+fn test_metrics_settings_are_checked() {
+    let config = try_load(
+        r#"
+[metrics]
+listen = "127.0.0.1:9100"
+labels = { cluster = "devnet" }
+buckets = { rpc-request-duration = [0.005, 0.01, 0.1, 1.0] }
+subsystems = { accounts-db = false }
+push-gateway = { url = "http://pushgateway:9091", interval = "30s" }
+"#,
+    )
+    .expect("Metrics settings should be valid");
+    let metrics = &config.metrics;
+    assert_eq!(metrics.path, consts::DEFAULT_METRICS_PATH);
+    assert_eq!(metrics.buckets["rpc-request-duration"].len(), 4);
+    assert!(!metrics.is_enabled("accounts-db"));
+    assert!(metrics.is_enabled("ledger"));
+    assert_eq!(*metrics.push_gateway.interval, Duration::from_secs(30));
+
+    let keys = invalid_keys(
+        r#"
+[metrics]
+path = "metrics"
+labels = { "__name__" = "x", "0day" = "y" }
+buckets = { a = [], b = [1.0, 0.5] }
+push-gateway = { url = "udp://pushgateway", interval = "0s" }
+"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "metrics.path",
+            "metrics.labels",
+            "metrics.labels",
+            "metrics.buckets.a",
+            "metrics.buckets.b",
+            "metrics.push-gateway.url",
+            "metrics.push-gateway.interval",
+        ]
+    );
+}
+
+#[test]
+fn test_tracing_settings_are_checked() {
+    let config = try_load(
+        r#"
+[tracing]
+endpoint = "https://otlp.example.com:4318"
+protocol = "http/protobuf"
+headers = { x-api-key = "k3y" }
+sampling-ratio = "10%"
+resource-attributes = { "service.name" = "magicblock-validator" }
+"#,
+    )
+    .expect("Tracing settings should be valid");
+    let tracing = &config.tracing;
+    assert_eq!(tracing.protocol, OtlpProtocol::HttpProtobuf);
+    assert_eq!(tracing.sampling_ratio.ratio(), 0.1);
+    assert_eq!(
+        tracing.resource_attributes["service.name"],
+        "magicblock-validator"
+    );
+    assert_eq!(tracing.headers["x-api-key"].expose_secret(), "k3y");
+
+    let keys = invalid_keys(
+        r#"
+[tracing]
+endpoint = "udp://collector"
+headers = { "bad header" = "v" }
+"#,
+    );
+    assert_eq!(keys, ["tracing.endpoint", "tracing.headers"]);
+
+    let error = try_load("tracing.sampling-ratio = 1.5").unwrap_err();
+    assert!(error.to_string().contains("sampling-ratio"), "{error}");
+}
+
+#[test]
+fn test_remote_rate_limits_are_checked() {
+    let config = try_load(
+        r#"
+remote = [
+    { url = "https://api.mainnet-beta.solana.com", rate-limit = { requests-per-second = 10, max-concurrent-subscriptions = 100 } },
+    "https://rpc.example.com",
+]
+"#,
+    )
+    .expect("Rate limits should be valid");
+    let limit = config.remote.remotes()[0].rate_limit();
+    assert_eq!(limit.requests_per_second, Some(10));
+    assert_eq!(limit.burst(), Some(10));
+    assert_eq!(limit.max_concurrent_subscriptions, Some(100));
+    assert_eq!(
+        config.remote.remotes()[1].rate_limit(),
+        RateLimit::default()
+    );
+
+    let keys = invalid_keys(
+        r#"remote = { url = "https://rpc.example.com", rate-limit = { burst = 5, max-concurrent-subscriptions = 0 } }"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "remote.rate-limit.max-concurrent-subscriptions",
+            "remote.rate-limit.burst"
+        ]
+    );
+}
+
+#[test]
+fn test_connection_options_are_shared() {
+    let config = try_load(
+        r#"
+[[remote]]
+url = "https://rpc.example.com"
+connection = { connect-timeout = "2s", keepalive = "0s", retry = { attempts = 5, backoff = "1s" } }
+
+[validator.signer]
+url = "https://signer.internal:8443"
+token = "t0ken"
+pubkey = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"
+connection = { request-timeout = "5s", pool-size = 2 }
+"#,
+    )
+    .expect("Connection options should be valid");
+    let remote = config.remote.remotes()[0].connection();
+    assert_eq!(remote.connect_timeout, Duration::from_secs(2));
+    assert_eq!(remote.request_timeout, Duration::from_secs(30));
+    assert!(remote.keepalive.is_zero());
+    assert_eq!(remote.retry.attempts, 5);
+    assert_eq!(remote.retry.backoff, Duration::from_secs(1));
+    let signer = config.validator.signer.as_ref().unwrap().connection;
+    assert_eq!(signer.request_timeout, Duration::from_secs(5));
+    assert_eq!(signer.pool_size, 2);
+    assert_eq!(signer.retry, RetryPolicy::default());
+
+    let keys = invalid_keys(
+        r#"remote = { url = "https://rpc.example.com", connection = { request-timeout = "0s", pool-size = 0, retry = { attempts = 0 } } }"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "remote.connection.request-timeout",
+            "remote.connection.pool-size",
+            "remote.connection.retry.attempts"
+        ]
+    );
+}
+
+#[test]
+fn test_grpc_remotes_depend_on_the_lifecycle() {
+    let remote = r#"remote = ["https://rpc.example.com", { endpoint = "https://grpc.example.com", x-token = "t0ken" }]"#;
+    let config = try_load(&format!("lifecycle = \"replica\"\n{remote}"))
+        .expect("Replicas should stream from gRPC remotes");
+    assert_eq!(config.remote.grpc().count(), 1);
+
+    let keys = invalid_keys(&format!("lifecycle = \"programs-replica\"\n{remote}"));
+    assert_eq!(keys, ["remote"]);
+
+    let keys = invalid_keys(
+        r#"lifecycle = "replica"
+remote = { endpoint = "wss://grpc.example.com", x-token = "" }"#,
+    );
+    assert_eq!(keys, ["remote.endpoint", "remote.x-token", "remote"]);
+}
+
+#[test]
+fn test_named_remotes_are_referenced() {
+    let config = try_load(
+        r#"
+remote = "https://api.mainnet-beta.solana.com"
+
+[remotes.helius]
+url = "https://mainnet.helius-rpc.com"
+api-key = "k3y"
+
+[remotes.triton]
+http = "https://example.rpcpool.com"
+ws = "wss://example.rpcpool.com"
+
+[chainlink]
+remote = "helius"
+"#,
+    )
+    .expect("Known names should be valid");
+    let helius = config.remote_for(config.chainlink.remote.as_deref());
+    assert_eq!(helius, config.remotes.get("helius"));
+    assert!(helius.is_some_and(|remote| remote.auth().api_key.is_some()));
+    let commit = config.remote_for(config.commit.remote.as_deref());
+    assert_eq!(commit, config.remote.primary());
+
+    let keys = invalid_keys(
+        r#"
+[remotes.helius]
+url = "ftp://mainnet.helius-rpc.com"
+
+[commit]
+remote = "triton"
+"#,
+    );
+    assert_eq!(keys, ["remotes.helius.url", "commit.remote"]);
+    let err = try_load("[chainlink]\nremote = \"helius\"").unwrap_err();
+    assert!(err.to_string().contains("`[remotes]` is empty"), "{err}");
+}
+
+#[test]
+fn test_remote_health_checks_are_typed() {
+    let config = try_load(
+        r#"
+[[remote]]
+url = "https://mainnet.helius-rpc.com"
+health = { interval = "30s", method = "getSlot" }
+
+[[remote]]
+http = "https://example.rpcpool.com"
+ws = "wss://example.rpcpool.com"
+"#,
+    )
+    .expect("Health checks should be valid");
+    let [custom, default] = config.remote.remotes() else {
+        panic!("Two remotes should be loaded");
+    };
+    let health = custom.health();
+    assert_eq!(health.interval, Duration::from_secs(30));
+    assert_eq!(health.timeout, Duration::from_secs(5));
+    assert_eq!(health.unhealthy_threshold, 3);
+    assert_eq!(health.method, HealthCheckMethod::GetSlot);
+    assert_eq!(default.health(), HealthCheck::default());
+    assert_eq!(default.health().method, HealthCheckMethod::GetHealth);
+
+    let keys = invalid_keys(
+        r#"
+[[remote]]
+url = "https://mainnet.helius-rpc.com"
+health = { interval = "2s", timeout = "2s", unhealthy-threshold = 0 }
+"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "remote.0.health.timeout",
+            "remote.0.health.unhealthy-threshold"
+        ]
+    );
+    let err =
+        try_load("remote = { url = \"https://a.example.com\", health = { method = \"ping\" } }")
+            .unwrap_err();
+    assert!(err.to_string().contains("remote"), "{err}");
+}
+
+#[test]
+fn test_remote_aliases_are_resolved() {
+    let aliases = "[remote-aliases]\nhelius = \"https://mainnet.helius-rpc.com/\"\n";
+    let (_dir, path) = create_config_file("config.toml", aliases);
+    let config = MagicBlockParams::try_new(
+        [
+            "magic-block",
+            "--config",
+            path.to_str().unwrap(),
+            "--remote",
+            "helius",
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .expect("Aliases from the file should apply to `--remote`");
+    assert_eq!(
+        config.remote,
+        "https://mainnet.helius-rpc.com/".parse().unwrap()
+    );
+
+    let config = try_load(&format!(
+        "{aliases}\n[remotes.backup]\nhttp = \"helius\"\nws = \"wss://ws.example.com\""
+    ))
+    .expect("Aliases should apply to named remotes");
+    let Remote::Disjointed { http, ws, .. } = &config.remotes["backup"] else {
+        panic!("The remote should keep separate URLs");
+    };
+    assert_eq!(http.url.as_str(), "https://mainnet.helius-rpc.com/");
+    assert_eq!(ws.url.as_str(), "wss://ws.example.com/");
+
+    let keys = invalid_keys(
+        "remote = [\"mainnet\", \"triton\"]\n[remote-aliases]\ndevnet = \"https://devnet.example.com\"",
+    );
+    assert_eq!(keys, ["remote.1", "remote-aliases.devnet"]);
+    let err = try_load("remote = \"triton\"").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("`triton` is neither a URL nor a key of `[remote-aliases]`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_remote_roles_scope_operations() {
+    let config = try_load(
+        r#"
+lifecycle = "replica"
+
+[remote]
+default = "https://api.mainnet-beta.solana.com"
+
+[remote.roles]
+clone = "helius"
+commit = "sender"
+subscribe = "geyser"
+
+[remotes.helius]
+url = "https://mainnet.helius-rpc.com"
+
+[remotes.sender]
+url = "https://sender.example.com"
+
+[remotes.geyser]
+endpoint = "https://grpc.example.com"
+
+[commit]
+remote = "helius"
+"#,
+    )
+    .expect("Roles referring to `[remotes]` should be valid");
+    let remote = |purpose| config.remote_for_purpose(purpose);
+    assert_eq!(remote(RemotePurpose::Clone), config.remotes.get("helius"));
+    assert_eq!(
+        remote(RemotePurpose::Commit),
+        config.remotes.get("helius"),
+        "Sections take precedence over roles"
+    );
+    assert_eq!(
+        remote(RemotePurpose::Subscribe),
+        config.remotes.get("geyser")
+    );
+    assert_eq!(config.remote.remotes().len(), 1);
+    assert_eq!(config.remote.primary(), config.remote.remotes().first());
+
+    let keys = invalid_keys(
+        r#"
+lifecycle = "replica"
+
+[remote]
+default = ["https://rpc.example.com", "ftp://rpc.example.com"]
+roles = { clone = "geyser", subscribe = "missing" }
+
+[remotes.geyser]
+endpoint = "https://grpc.example.com"
+"#,
+    );
+    assert_eq!(
+        keys,
+        [
+            "remote.default.1",
+            "remote.roles.clone",
+            "remote.roles.subscribe"
+        ]
+    );
+}
+
+#[test]
+fn test_admin_section_is_checked() {
+    let content = r#"
+listen = "127.0.0.1:8899"
+
+[admin]
+listen = "127.0.0.1:8899"
+token = ""
+"#;
+
+    assert_eq!(invalid_keys(content), ["admin.token", "admin.listen"]);
+}
+
+#[test]
+fn test_remote_signer_replaces_the_keypair() {
+    let pubkey = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+    let config = try_load(&format!(
+        "lifecycle = \"ephemeral\"\n{CHAIN_OPERATION}\n[validator.signer]\nurl = \"https://signer.internal:8443\"\ntoken = \"s3cret\"\npubkey = \"{pubkey}\""
+    ))
+    .expect("A remote signer stands in for a custom keypair");
+
+    let identity = config.validator.identity();
+    let IdentitySource::Remote(signer) = &identity else {
+        panic!("expected a remote signer, got {identity:?}");
+    };
+    assert_eq!(signer.url.as_str(), "https://signer.internal:8443/");
+    assert_eq!(identity.pubkey().to_string(), pubkey);
+    assert!(!format!("{config:?}").contains("s3cret"));
+    assert!(matches!(
+        MagicBlockParams::default().validator.identity(),
+        IdentitySource::Local(_)
+    ));
+}
+
+#[test]
+fn test_remote_signer_is_checked() {
+    let content = r#"
+[validator.signer]
+url = "ftp://signer.internal"
+token = ""
+pubkey = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"
+"#;
+
+    assert_eq!(
+        invalid_keys(content),
+        ["validator.signer.url", "validator.signer.token"]
+    );
+}
+
+#[test]
+fn test_role_keypairs_fall_back_to_the_identity() {
+    let fee_payer = Keypair::new();
+    let dir = tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("fee-payer.json");
+    let bytes: Vec<String> = fee_payer.to_bytes().iter().map(u8::to_string).collect();
+    std::fs::write(&path, format!("[{}]", bytes.join(","))).unwrap();
+    let authority = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+    let config = try_load(&format!(
+        r#"
+[validator.commit-fee-payer]
+keypair = "file:{}"
+
+[validator.fee-claim-authority.signer]
+url = "https://signer.internal:8443"
+token = "s3cret"
+pubkey = "{authority}"
+"#,
+        path.display()
+    ))
+    .expect("Role keypairs should load");
+
+    let validator = &config.validator;
+    let pubkey = |role| validator.keypair_for(role).pubkey().to_string();
+    assert_eq!(
+        pubkey(KeyRole::Identity),
+        validator.identity().pubkey().to_string()
+    );
+    assert_eq!(
+        pubkey(KeyRole::CommitFeePayer),
+        keygen::pubkey(&fee_payer).to_string()
+    );
+    assert!(matches!(
+        validator.keypair_for(KeyRole::FeeClaimAuthority),
+        IdentitySource::Remote(_)
+    ));
+    assert_eq!(pubkey(KeyRole::FeeClaimAuthority), authority);
+    assert!(!format!("{config:?}").contains(&fee_payer.to_base58_string()));
+
+    let defaults = MagicBlockParams::default().validator;
+    assert_eq!(
+        defaults.keypair_for(KeyRole::CommitFeePayer).pubkey(),
+        defaults.identity().pubkey()
+    );
+}
+
+#[test]
+fn test_role_keypairs_are_checked() {
+    let content = format!(
+        r#"
+[validator.commit-fee-payer]
+
+[validator.fee-claim-authority]
+keypair = "{}"
+"#,
+        magicblock_config::consts::DEFAULT_VALIDATOR_KEYPAIR
+    );
+    assert_eq!(
+        invalid_keys(&content),
+        [
+            "validator.commit-fee-payer",
+            "validator.fee-claim-authority"
+        ]
+    );
+
+    let content = r#"
+[validator.commit-fee-payer.signer]
+url = "ftp://signer.internal"
+token = ""
+pubkey = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"
+"#;
+    assert_eq!(
+        invalid_keys(content),
+        [
+            "validator.commit-fee-payer.signer.url",
+            "validator.commit-fee-payer.signer.token"
+        ]
+    );
+}
+
+#[test]
+fn test_zero_refresh_interval_is_rejected() {
+    assert_eq!(
+        invalid_keys("config-refresh-interval = \"0s\""),
+        ["config-refresh-interval"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tls_identity_files_are_checked() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let cert = dir.path().join("cert.pem");
+    let key = dir.path().join("key.pem");
+    std::fs::write(&cert, "certificate").unwrap();
+    std::fs::write(&key, "private key").unwrap();
+    std::fs::set_permissions(&key, std::fs::Permissions::from_mode(0o644)).unwrap();
+    let identity = format!("cert = \"{}\"\nkey = \"{}\"", cert.display(), key.display());
+
+    let keys = invalid_keys(&format!(
+        "[metrics.tls]\ncert = \"{}\"\nkey = \"{}\"\n[tls]\n{identity}\nca = \"{}\"",
+        cert.display(),
+        dir.path().join("missing.pem").display(),
+        dir.path().join("ca.pem").display(),
+    ));
+    assert_eq!(
+        keys,
+        ["metrics.tls.key", "metrics.tls", "tls.ca", "tls.key"]
+    );
+    let err = try_load(&format!("[tls]\n{identity}"))
+        .expect_err("A private key readable by others should be rejected");
+    assert!(
+        err.to_string()
+            .contains("accessible to group or others (mode 0644)"),
+        "{err}"
+    );
+
+    std::fs::set_permissions(&key, std::fs::Permissions::from_mode(0o600)).unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[tls]\ncert = \"cert.pem\"\nkey = \"key.pem\"",
+    )
+    .unwrap();
+    let config = MagicBlockParams::try_new(
+        ["magic-block", "--config", config_path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    )
+    .expect("Relative certificate paths resolve against the configuration file");
+    let tls = config.tls.unwrap();
+    assert_eq!(tls.cert, cert);
+    assert_eq!(tls.key, key);
+}
+
+#[test]
+fn test_dual_stack_bind_addresses() {
+    let config = try_load("listen = { addr = \"[::]:9000\", v6-only = false }")
+        .expect("IPv6 addresses should accept `v6-only`");
+    assert_eq!(
+        config.listen,
+        [BindAddress::Tcp {
+            address: "[::]:9000".parse().unwrap(),
+            v6_only: Some(false),
+        }]
+    );
+    let toml = config.to_toml().unwrap();
+    assert!(toml.contains("v6-only = false"), "{toml}");
+    let config =
+        try_load("listen = [{ addr = \"[::1]:9000\", v6-only = true }, \"127.0.0.1:9000\"]")
+            .expect("An IPv6-only address should not clash with IPv4 ones");
+    assert_eq!(config.listen.len(), 2);
+
+    let err = try_load("listen = { addr = \"127.0.0.1:9000\", v6-only = true }")
+        .expect_err("`v6-only` should be rejected for IPv4 addresses");
+    assert!(
+        err.to_string()
+            .contains("`v6-only` only applies to IPv6 addresses"),
+        "{err}"
+    );
+    let keys = invalid_keys(
+        "listen = { addr = \"[::]:9000\", v6-only = false }\nmetrics = \"0.0.0.0:9000\"",
+    );
+    assert_eq!(keys, ["metrics.listen"]);
+}
@@ -0,0 +1,69 @@
+//! Integration tests for `config-version` compatibility checks.
+
+use magicblock_config::{consts, ConfigError, ConfigWarning, MagicBlockParams};
+
+mod common;
+use common::create_config_file;
+
+fn load(content: &str) -> Result<(MagicBlockParams, Vec<ConfigWarning>), ConfigError> {
+    let (_dir, path) = create_config_file("config.toml", content);
+    MagicBlockParams::try_new_with_warnings(
+        ["magic-block", "--config", path.to_str().unwrap()]
+            .into_iter()
+            .map(Into::into),
+    )
+}
+
+#[test]
+fn test_current_and_missing_versions_load_cleanly() {
+    let (config, warnings) = load(&format!("config-version = {}", consts::CONFIG_VERSION))
+        .expect("The current version should load");
+    assert_eq!(config.config_version, Some(consts::CONFIG_VERSION));
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    let (config, warnings) = load("").expect("An unversioned config should load");
+    assert_eq!(config.config_version, None);
+    assert!(warnings.is_empty(), "{warnings:?}");
+}
+
+#[test]
+fn test_older_version_warns_with_changes() {
+    let (config, warnings) = load("config-version = 1").expect("Older versions should load");
+
+    assert_eq!(config.config_version, Some(1));
+    let [ConfigWarning::OutdatedVersion { version, changes }] = warnings.as_slice() else {
+        panic!("expected an outdated version warning, got {warnings:?}");
+    };
+    assert_eq!(*version, 1);
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].contains("validator.basefee"), "{changes:?}");
+}
+
+#[test]
+fn test_newer_version_is_rejected_with_guidance() {
+    let err = load("config-version = 99").expect_err("Newer versions should be rejected");
+
+    let ConfigError::Invalid(report) = &err else {
+        panic!("expected an invalid configuration, got {err:?}");
+    };
+    assert_eq!(report.errors[0].key, "config-version");
+    assert!(
+        report.errors[0]
+            .message
+            .contains("upgrade magicblock-config"),
+        "{report}"
+    );
+}
+
+#[test]
+fn test_malformed_version_is_reported() {
+    let err = load("config-version = \"two\"").expect_err("Versions must be integers");
+
+    let ConfigError::Invalid(report) = &err else {
+        panic!("expected an invalid configuration, got {err:?}");
+    };
+    assert!(
+        report.errors.iter().all(|e| e.key == "config-version"),
+        "{report}"
+    );
+}